@@ -0,0 +1,104 @@
+//! Add a new kind of shape to the ray tracer by implementing `Geometry`.
+//!
+//! cargo run --release --example cube
+
+use rtracer::{
+    save_canvas, Bounds, Camera, Color, Geometry, Material, Matrix4x4, OutputTransform, Point,
+    PointLight, Ray, Shape, Vector, World,
+};
+use std::sync::Arc;
+
+/// A cube from -1 to 1 on every axis
+#[derive(Debug)]
+struct Cube;
+
+impl Geometry for Cube {
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let axis = |origin: f64, direction: f64| {
+            let t1 = (-1.0 - origin) / direction;
+            let t2 = (1.0 - origin) / direction;
+            (t1.min(t2), t1.max(t2))
+        };
+        let (origin, direction) = (ray.origin(), ray.direction());
+        let (xmin, xmax) = axis(origin.x(), direction.x());
+        let (ymin, ymax) = axis(origin.y(), direction.y());
+        let (zmin, zmax) = axis(origin.z(), direction.z());
+
+        let tmin = xmin.max(ymin).max(zmin);
+        let tmax = xmax.min(ymax).min(zmax);
+        if tmin > tmax {
+            vec![]
+        } else {
+            vec![tmin, tmax]
+        }
+    }
+
+    fn local_normal_at(&self, point: Point) -> Vector {
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        let largest = x.abs().max(y.abs()).max(z.abs());
+        if largest == x.abs() {
+            Vector::new(x, 0.0, 0.0)
+        } else if largest == y.abs() {
+            Vector::new(0.0, y, 0.0)
+        } else {
+            Vector::new(0.0, 0.0, z)
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+}
+
+fn main() {
+    let floor = Material {
+        color: Color::new(1.0, 0.9, 0.9),
+        specular: 0.0,
+        ..Material::default()
+    };
+    let blue = Material {
+        color: Color::new(0.2, 0.4, 1.0),
+        diffuse: 0.7,
+        specular: 0.3,
+        ..Material::default()
+    };
+
+    // One cube's geometry shared by two shapes
+    let cube: Arc<dyn Geometry> = Arc::new(Cube);
+
+    let world = World::new()
+        .add_light(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::white(),
+        ))
+        .add_object(Shape::plane(Matrix4x4::identity(), floor))
+        .add_object(Shape::custom(
+            cube.clone(),
+            Matrix4x4::rotation_y(0.5).translate(-1.0, 1.0, 0.5),
+            blue.clone(),
+        ))
+        .add_object(Shape::custom(
+            cube,
+            Matrix4x4::scaling(0.4, 0.4, 0.4).translate(1.2, 0.4, -0.8),
+            Material {
+                color: Color::new(1.0, 0.8, 0.1),
+                ..blue
+            },
+        ))
+        .generate();
+
+    let camera = Camera::new(
+        320,
+        160,
+        std::f64::consts::FRAC_PI_3,
+        Matrix4x4::view(
+            Point::new(0.0, 2.5, -6.0),
+            Point::new(0.0, 1.0, 0.0),
+            Vector::up(),
+        ),
+    );
+
+    let canvas = camera.render(&world);
+    save_canvas(&canvas, "cube.png".to_owned(), &OutputTransform::default())
+        .expect("failed to save cube.png");
+}
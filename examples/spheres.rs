@@ -0,0 +1,61 @@
+//! Render a small scene built in code with the rtracer library.
+//!
+//! cargo run --release --example spheres
+
+use rtracer::{
+    save_canvas, Camera, Color, Material, Matrix4x4, OutputTransform, Point, PointLight, Shape,
+    Vector, World,
+};
+
+fn main() {
+    let floor = Material {
+        color: Color::new(1.0, 0.9, 0.9),
+        specular: 0.0,
+        ..Material::default()
+    };
+    let middle = Material {
+        color: Color::new(0.1, 1.0, 0.5),
+        diffuse: 0.7,
+        specular: 0.3,
+        ..Material::default()
+    };
+    let small = Material {
+        color: Color::new(1.0, 0.8, 0.1),
+        ..middle.clone()
+    };
+
+    let world = World::new()
+        .add_light(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::white(),
+        ))
+        .add_object(Shape::plane(Matrix4x4::identity(), floor))
+        .add_object(Shape::sphere(
+            Matrix4x4::translation(-0.5, 1.0, 0.5),
+            middle,
+        ))
+        .add_object(Shape::sphere(
+            Matrix4x4::scaling(0.33, 0.33, 0.33).translate(-1.5, 0.33, -0.75),
+            small,
+        ))
+        .generate();
+
+    let camera = Camera::new(
+        320,
+        160,
+        std::f64::consts::FRAC_PI_3,
+        Matrix4x4::view(
+            Point::new(0.0, 1.5, -5.0),
+            Point::new(0.0, 1.0, 0.0),
+            Vector::up(),
+        ),
+    );
+
+    let canvas = camera.render(&world);
+    save_canvas(
+        &canvas,
+        "spheres.png".to_owned(),
+        &OutputTransform::default(),
+    )
+    .expect("failed to save spheres.png");
+}
@@ -0,0 +1,244 @@
+use crate::preview::PreviewOptions;
+use clap::{ArgGroup, Parser};
+use rtracer::image::{ExrPrecision, OutputTransform, PngDepth, PpmEncoding, ToneMap};
+use rtracer::primitives::{Aov, Integrator, IntegratorKind, TileOrder};
+use rtracer::render::{BatchOptions, OutputFormat, SaveOptions};
+use std::str::FromStr;
+
+/// Render a scene with the ray tracer
+#[derive(Parser, Debug)]
+#[command(name = "rtracer", version, about)]
+#[command(group(ArgGroup::new("no_window").args(["headless", "batch"]).multiple(true)))]
+pub struct Options {
+    /// The scene file to render. The built-in Cornell box is rendered when not given.
+    pub scene: Option<String>,
+
+    /// Where to save the image
+    #[arg(short, long, default_value = "out.png")]
+    pub output: String,
+
+    /// The format of the image. One of png, ppm, exr or hdr. Taken from the output's extension
+    /// when not given.
+    #[arg(long, value_name = "FORMAT", value_parser = OutputFormat::from_str)]
+    pub format: Option<OutputFormat>,
+
+    /// Override the width of the scene's camera
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..))]
+    pub width: Option<i32>,
+
+    /// Override the height of the scene's camera
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..))]
+    pub height: Option<i32>,
+
+    /// How light is simulated. One of whitted, path or spectral. Defaults to the path tracer
+    /// when samples are given.
+    #[arg(long, value_name = "NAME", value_parser = IntegratorKind::from_str)]
+    pub integrator: Option<IntegratorKind>,
+
+    /// Samples per pixel for the path tracers
+    #[arg(short, long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub samples: Option<u32>,
+
+    /// The most times a path can bounce in the path tracers
+    #[arg(long, default_value_t = 8)]
+    pub max_depth: u32,
+
+    /// Seed for the path tracers' random numbers. The same seed renders the same image.
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+
+    /// How many threads to render with. Defaults to one per core.
+    #[arg(short = 'j', long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub threads: Option<u32>,
+
+    /// Brighten or darken the image by this many stops before it's displayed or saved
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    pub exposure: f64,
+
+    /// How bright colors are fitted into the displayed & PNG images. One of clamp, reinhard,
+    /// aces or hable.
+    #[arg(long, value_name = "NAME", default_value = "clamp", value_parser = ToneMap::from_str)]
+    pub tone_map: ToneMap,
+
+    /// Gamma encode the window, PNGs & PPMs for sRGB displays instead of saving the values
+    /// linearly as the book does
+    #[arg(long)]
+    pub srgb: bool,
+
+    /// Bits per channel of PNG images, 8 or 16
+    #[arg(long, value_name = "BITS", default_value = "8", value_parser = parse_png_depth)]
+    pub bit_depth: PngDepth,
+
+    /// Save PPM images as text, as the book does, instead of binary
+    #[arg(long)]
+    pub plain_ppm: bool,
+
+    /// Make pixels where the camera sees the background transparent. Only PNGs have an
+    /// alpha channel.
+    #[arg(long)]
+    pub alpha: bool,
+
+    /// Save OpenEXR images with 16 bit floats instead of 32 bit ones
+    #[arg(long)]
+    pub half: bool,
+
+    /// Show the whole image in the window straight away & improve it a pass at a time. Closing
+    /// the window early saves the passes which are done.
+    #[arg(long, conflicts_with = "headless")]
+    pub progressive: bool,
+
+    /// The width & height in pixels of the tiles the window renders at a time
+    #[arg(long, default_value_t = 32, value_parser = clap::value_parser!(i32).range(1..))]
+    pub tile_size: i32,
+
+    /// The order the window renders tiles in. One of scanline, spiral, hilbert or cursor, which
+    /// starts nearest the mouse.
+    #[arg(long, value_name = "ORDER", default_value = "spiral", value_parser = TileOrder::from_str)]
+    pub tile_order: TileOrder,
+
+    /// Render without opening a window
+    #[arg(long)]
+    pub headless: bool,
+
+    /// Render a pass at a time, saving the samples so far to this file every so often so the
+    /// render can be carried on with --resume if it's stopped
+    #[arg(
+        long,
+        value_name = "FILE",
+        requires = "headless",
+        conflicts_with = "batch"
+    )]
+    pub checkpoint: Option<String>,
+
+    /// Seconds between checkpoints
+    #[arg(long, value_name = "SECONDS", default_value_t = 60)]
+    pub checkpoint_interval: u64,
+
+    /// Carry on from the checkpoint instead of starting again. The scene, size, integrator, max
+    /// depth & seed have to be the same, but the samples can be raised.
+    #[arg(long, requires = "checkpoint")]
+    pub resume: bool,
+
+    /// Also save an AOV next to the output, e.g. `out.depth.png`, or as a layer of an EXR output.
+    /// Works with --headless & --batch.
+    /// One of depth, normal, albedo, shadow, object-id, material-id or alpha. Can be given more
+    /// than once.
+    #[arg(long = "aov", value_name = "NAME", value_parser = Aov::from_str, requires = "no_window")]
+    pub aovs: Vec<Aov>,
+
+    /// Render every job in a manifest one after another, without a window
+    #[arg(long, value_name = "MANIFEST", conflicts_with = "scene")]
+    pub batch: Option<String>,
+
+    /// Where to write the batch's summary as JSON
+    #[arg(long, default_value = "summary.json")]
+    pub summary: String,
+}
+
+const DEFAULT_SAMPLES: u32 = 16;
+
+impl Options {
+    pub fn output_format(&self) -> Result<OutputFormat, String> {
+        if let Some(format) = self.format {
+            return Ok(format);
+        }
+
+        OutputFormat::from_path(&self.output)
+            .map_err(|error| format!("{}. Use --format to choose one.", error))
+    }
+
+    pub fn exr_precision(&self) -> ExrPrecision {
+        if self.half {
+            ExrPrecision::Half
+        } else {
+            ExrPrecision::Float
+        }
+    }
+
+    pub fn output_transform(&self) -> OutputTransform {
+        OutputTransform {
+            exposure: self.exposure,
+            tone_map: self.tone_map,
+            srgb: self.srgb,
+        }
+    }
+
+    pub fn save_options(&self, format: OutputFormat) -> Result<SaveOptions, String> {
+        if self.alpha && format != OutputFormat::Png {
+            return Err("--alpha can only be used when saving a PNG".to_owned());
+        }
+
+        Ok(SaveOptions {
+            format,
+            precision: self.exr_precision(),
+            png_depth: self.bit_depth,
+            ppm_encoding: if self.plain_ppm {
+                PpmEncoding::Plain
+            } else {
+                PpmEncoding::Binary
+            },
+            transform: self.output_transform(),
+        })
+    }
+
+    /// How the jobs of a batch are rendered when they don't say otherwise
+    pub fn batch_options(&self) -> Result<BatchOptions, String> {
+        let integrator = self.integrator;
+        let samples = self.samples;
+        let (max_depth, seed) = (self.max_depth, self.seed);
+        Ok(BatchOptions {
+            format: self.format,
+            // The format is replaced by each job's
+            save: self.save_options(OutputFormat::Png)?,
+            width: self.width,
+            height: self.height,
+            aovs: self.aovs.clone(),
+            alpha: self.alpha,
+            integrator_for: Box::new(move |job_samples| {
+                choose_integrator(integrator, job_samples.or(samples), max_depth, seed)
+            }),
+        })
+    }
+
+    pub fn preview_options(&self) -> PreviewOptions {
+        PreviewOptions {
+            progressive: self.progressive,
+            tile_size: self.tile_size,
+            tile_order: self.tile_order,
+        }
+    }
+
+    pub fn integrator(&self) -> Integrator {
+        choose_integrator(self.integrator, self.samples, self.max_depth, self.seed)
+    }
+}
+
+/// The integrator asked for, or the path tracer if only samples were given
+fn choose_integrator(
+    kind: Option<IntegratorKind>,
+    samples: Option<u32>,
+    max_depth: u32,
+    seed: u64,
+) -> Integrator {
+    let kind = match (kind, samples) {
+        (Some(kind), _) => kind,
+        (None, Some(_)) => IntegratorKind::Path,
+        (None, None) => IntegratorKind::Whitted,
+    };
+    let samples = samples.unwrap_or(DEFAULT_SAMPLES);
+
+    match kind {
+        IntegratorKind::Whitted => Integrator::Whitted,
+        IntegratorKind::Path => Integrator::path_tracer(samples, max_depth),
+        IntegratorKind::Spectral => Integrator::spectral_path_tracer(samples, max_depth),
+    }
+    .with_seed(seed)
+}
+
+fn parse_png_depth(bits: &str) -> Result<PngDepth, String> {
+    match bits {
+        "8" => Ok(PngDepth::Eight),
+        "16" => Ok(PngDepth::Sixteen),
+        _ => Err("PNGs can only have 8 or 16 bits per channel".to_owned()),
+    }
+}
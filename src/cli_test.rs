@@ -0,0 +1,195 @@
+use super::cli::Options;
+use clap::Parser;
+use rtracer::{
+    image::{ExrPrecision, OutputTransform, PngDepth, PpmEncoding, ToneMap},
+    primitives::{Aov, IntegratorKind, TileOrder},
+    render::{aov_path, OutputFormat},
+    Integrator,
+};
+
+fn parse(args: &[&str]) -> Options {
+    Options::try_parse_from(std::iter::once("rtracer").chain(args.iter().copied())).unwrap()
+}
+
+#[test]
+fn the_defaults_render_the_cornell_box_in_a_window() {
+    let options = parse(&[]);
+
+    assert_eq!(options.scene, None);
+    assert_eq!(options.output, "out.png");
+    assert!(!options.headless);
+    assert_eq!(options.output_format(), Ok(OutputFormat::Png));
+    assert_eq!(options.integrator(), Integrator::Whitted);
+}
+
+#[test]
+fn all_the_options_can_be_set() {
+    let options = parse(&[
+        "scenes/cornell_box.yml",
+        "--output",
+        "render.png",
+        "--width",
+        "320",
+        "--height",
+        "200",
+        "--samples",
+        "64",
+        "--max-depth",
+        "4",
+        "--seed",
+        "7",
+        "--threads",
+        "2",
+        "--headless",
+    ]);
+
+    assert_eq!(options.scene, Some("scenes/cornell_box.yml".to_owned()));
+    assert_eq!(options.output, "render.png");
+    assert_eq!((options.width, options.height), (Some(320), Some(200)));
+    assert_eq!(options.threads, Some(2));
+    assert!(options.headless);
+    assert_eq!(
+        options.integrator(),
+        Integrator::path_tracer(64, 4).with_seed(7)
+    );
+}
+
+#[test]
+fn the_integrator_can_be_chosen() {
+    let options = parse(&["--integrator", "spectral"]);
+    assert_eq!(options.integrator, Some(IntegratorKind::Spectral));
+    assert_eq!(
+        options.integrator(),
+        Integrator::spectral_path_tracer(16, 8)
+    );
+
+    let options = parse(&["--integrator", "whitted", "--samples", "4"]);
+    assert_eq!(options.integrator(), Integrator::Whitted);
+}
+
+#[test]
+fn the_format_comes_from_the_output_extension() {
+    assert_eq!(
+        parse(&["-o", "IMAGE.PNG"]).output_format(),
+        Ok(OutputFormat::Png)
+    );
+    assert_eq!(
+        parse(&["-o", "image.exr"]).output_format(),
+        Ok(OutputFormat::Exr)
+    );
+    assert_eq!(
+        parse(&["-o", "image.hdr"]).output_format(),
+        Ok(OutputFormat::Hdr)
+    );
+    assert_eq!(
+        parse(&["-o", "image.ppm"]).output_format(),
+        Ok(OutputFormat::Ppm)
+    );
+    assert!(parse(&["-o", "image.bmp"]).output_format().is_err());
+    assert_eq!(
+        parse(&["-o", "image", "--format", "png"]).output_format(),
+        Ok(OutputFormat::Png)
+    );
+}
+
+#[test]
+fn exr_images_can_be_saved_at_half_precision() {
+    assert_eq!(parse(&[]).exr_precision(), ExrPrecision::Float);
+    assert_eq!(parse(&["--half"]).exr_precision(), ExrPrecision::Half);
+    assert!(OutputFormat::Exr.is_float());
+    assert!(!OutputFormat::Png.is_float());
+}
+
+#[test]
+fn the_output_transform_can_be_chosen() {
+    let transform = parse(&[]).output_transform();
+    assert_eq!(transform, OutputTransform::default());
+    assert!(!transform.srgb);
+    assert_eq!(
+        parse(&["--srgb"]).output_transform(),
+        OutputTransform::srgb()
+    );
+
+    let options = parse(&["--exposure", "-1.5", "--tone-map", "aces", "-o", "out.exr"]);
+    let save = options
+        .save_options(options.output_format().unwrap())
+        .unwrap();
+    assert_eq!(save.format, OutputFormat::Exr);
+    assert_eq!(save.transform.exposure, -1.5);
+    assert_eq!(save.transform.tone_map, ToneMap::Aces);
+
+    assert!(Options::try_parse_from(["rtracer", "--tone-map", "filmic"]).is_err());
+}
+
+#[test]
+fn pngs_can_have_16_bits_and_an_alpha_channel() {
+    let save = parse(&[]).save_options(OutputFormat::Png).unwrap();
+    assert_eq!(save.png_depth, PngDepth::Eight);
+    assert_eq!(save.ppm_encoding, PpmEncoding::Binary);
+
+    let options = parse(&["--headless", "--bit-depth", "16", "--alpha", "--plain-ppm"]);
+    let save = options.save_options(OutputFormat::Png).unwrap();
+    assert_eq!(save.png_depth, PngDepth::Sixteen);
+    assert_eq!(save.ppm_encoding, PpmEncoding::Plain);
+
+    // Only PNGs have an alpha channel
+    assert!(options.save_options(OutputFormat::Ppm).is_err());
+    assert!(options.save_options(OutputFormat::Exr).is_err());
+    assert!(Options::try_parse_from(["rtracer", "--bit-depth", "12"]).is_err());
+}
+
+#[test]
+fn the_window_can_render_tiles_in_another_order() {
+    let preview = parse(&[]).preview_options();
+    assert_eq!(
+        (preview.tile_size, preview.tile_order),
+        (32, TileOrder::Spiral)
+    );
+
+    let preview = parse(&["--tile-size", "16", "--tile-order", "hilbert"]).preview_options();
+    assert_eq!(
+        (preview.tile_size, preview.tile_order),
+        (16, TileOrder::Hilbert)
+    );
+
+    let preview = parse(&["--tile-order", "cursor"]).preview_options();
+    assert_eq!(preview.tile_order, TileOrder::Cursor);
+
+    assert!(Options::try_parse_from(["rtracer", "--tile-size", "0"]).is_err());
+    assert!(Options::try_parse_from(["rtracer", "--tile-order", "random"]).is_err());
+}
+
+#[test]
+fn checkpoints_are_for_headless_renders() {
+    let options = parse(&["--headless", "--checkpoint", "render.ckpt", "--resume"]);
+    assert_eq!(options.checkpoint, Some("render.ckpt".to_owned()));
+    assert_eq!(options.checkpoint_interval, 60);
+    assert!(options.resume);
+
+    assert!(Options::try_parse_from(["rtracer", "--checkpoint", "render.ckpt"]).is_err());
+    assert!(Options::try_parse_from(["rtracer", "--headless", "--resume"]).is_err());
+}
+
+#[test]
+fn invalid_options_are_rejected() {
+    assert!(Options::try_parse_from(["rtracer", "--width", "0"]).is_err());
+    assert!(Options::try_parse_from(["rtracer", "--samples", "lots"]).is_err());
+    assert!(Options::try_parse_from(["rtracer", "--format", "gif"]).is_err());
+}
+
+#[test]
+fn aovs_are_saved_next_to_the_output() {
+    let options = parse(&["--headless", "--aov", "depth", "--aov", "object-id"]);
+
+    assert_eq!(options.aovs, vec![Aov::Depth, Aov::ObjectId]);
+    assert_eq!(
+        aov_path("renders/out.png", Aov::Depth),
+        "renders/out.depth.png"
+    );
+    assert_eq!(aov_path("out", Aov::Normal), "out.normal");
+
+    assert!(Options::try_parse_from(["rtracer", "--headless", "--aov", "beauty"]).is_err());
+    // The passes are only rendered without a window
+    assert!(Options::try_parse_from(["rtracer", "--aov", "depth"]).is_err());
+    assert!(Options::try_parse_from(["rtracer", "--batch", "jobs.yml", "--aov", "depth"]).is_ok());
+}
@@ -10,9 +10,11 @@ const PI_2: f64 = std::f64::consts::FRAC_PI_2;
 fn left_wall() -> Shape {
     let transform = Matrix4x4::rotation_z(PI_2).translate(-3.0, 0.0, 0.0);
 
-    let mut material = Material::default();
-    material.color = Color::red();
-    material.specular = 0.0;
+    let material = Material {
+        color: Color::red(),
+        specular: 0.0,
+        ..Material::default()
+    };
 
     Shape::plane(transform, material)
 }
@@ -20,25 +22,29 @@ fn left_wall() -> Shape {
 fn right_wall() -> Shape {
     let transform = Matrix4x4::rotation_z(PI_2).translate(3.0, 0.0, 0.0);
 
-    let mut material = Material::default();
-    material.color = Color::blue();
-    material.specular = 0.0;
+    let material = Material {
+        color: Color::blue(),
+        specular: 0.0,
+        ..Material::default()
+    };
 
     Shape::plane(transform, material)
 }
 
 pub fn create_cornell_box() -> World {
     // Create floor
-    let mut floor_mat = Material::default();
-    floor_mat.pattern = Some(StripePattern::new(Color::white(), Color::green()));
-    floor_mat.specular = 0.0;
-    let floor = Shape::plane(Matrix4x4::identity(), floor_mat);
+    let floor_mat = Material {
+        pattern: Some(StripePattern::new(Color::white(), Color::green())),
+        specular: 0.0,
+        ..Material::default()
+    };
+    let floor = Shape::plane(Matrix4x4::identity(), floor_mat.clone());
 
     let left_wall = left_wall();
     let right_wall = right_wall();
     let back_wall = Shape::plane(
         Matrix4x4::rotation_x(PI_2).translate(0.0, 0.0, 6.0),
-        floor_mat,
+        floor_mat.clone(),
     );
 
     let ceiling = Shape::plane(Matrix4x4::translation(0.0, 5.0, 0.0), floor_mat);
@@ -47,30 +53,36 @@ pub fn create_cornell_box() -> World {
 
     // Create middle
     let middle_transform = Matrix4x4::translation(-0.5, 1.0, 0.5);
-    let mut middle_mat = Material::default();
-    // middle_mat.color = Color::new(0.1, 1.0, 0.5);
-    middle_mat.color = orange;
-    middle_mat.diffuse = 0.7;
-    middle_mat.specular = 0.3;
-    middle_mat.pattern = Some(StripePattern::new(Color::white(), Color::blue()));
+    let middle_mat = Material {
+        // color: Color::new(0.1, 1.0, 0.5),
+        color: orange,
+        diffuse: 0.7,
+        specular: 0.3,
+        pattern: Some(StripePattern::new(Color::white(), Color::blue())),
+        ..Material::default()
+    };
     let middle = Shape::sphere(middle_transform, middle_mat);
 
     // Create right
     let right_transform = Matrix4x4::scaling(0.5, 0.5, 0.5).translate(1.5, 0.5, -0.5);
-    let mut right_mat = Material::default();
-    //right_mat.color = Color::new(0.5, 1.0, 0.1);
-    right_mat.color = orange;
-    right_mat.diffuse = 0.7;
-    right_mat.specular = 0.3;
+    let right_mat = Material {
+        // color: Color::new(0.5, 1.0, 0.1),
+        color: orange,
+        diffuse: 0.7,
+        specular: 0.3,
+        ..Material::default()
+    };
     let right = Shape::sphere(right_transform, right_mat);
 
     // Create left
     let left_translation = Matrix4x4::scaling(0.33, 0.33, 0.33).translate(-1.5, 0.33, -0.75);
-    let mut left_mat = Material::default();
-    // left_mat.color = Color::new(1.0, 0.8, 0.1);
-    left_mat.color = orange;
-    left_mat.diffuse = 0.7;
-    left_mat.specular = 0.3;
+    let left_mat = Material {
+        // color: Color::new(1.0, 0.8, 0.1),
+        color: orange,
+        diffuse: 0.7,
+        specular: 0.3,
+        ..Material::default()
+    };
     let left = Shape::sphere(left_translation, left_mat);
 
     // Create world
@@ -0,0 +1,112 @@
+use crate::{
+    image::load_png, load_scene, save_canvas, Camera, Canvas, Integrator, Matrix4x4,
+    OutputTransform, Point, Vector, World,
+};
+use std::path::PathBuf;
+
+/*
+    Golden image tests render small scenes & check they still look like the reference
+    PNGs in `tests/golden`. After a change which is meant to alter how scenes look, run
+    `UPDATE_GOLDEN=1 cargo test golden` to save new references & check them in.
+*/
+
+/// How far a channel can drift before a pixel counts as different, allowing for rounding
+const THRESHOLD: f64 = 2.0 / 255.0;
+/// How many pixels can differ before the test fails
+const MAX_PERCENT_DIFFERENT: f64 = 0.5;
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.png", name))
+}
+
+/// Fail unless the render looks like the reference image called `name`
+fn assert_matches_golden(name: &str, render: &Canvas) {
+    let transform = OutputTransform::default();
+    let path = golden_path(name).to_string_lossy().into_owned();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        save_canvas(render, path, &transform).unwrap();
+        return;
+    }
+
+    let expected = load_png(path.clone()).unwrap_or_else(|error| {
+        panic!(
+            "can't load `{}`: {}. Run with UPDATE_GOLDEN=1 to create it.",
+            path, error
+        )
+    });
+    let actual = transform.apply_to_canvas(render);
+    let diff = actual.compare(&expected, THRESHOLD).unwrap();
+
+    if diff.percent_over_threshold() > MAX_PERCENT_DIFFERENT {
+        // Save what was rendered next to the differences so they can be looked at
+        let output = std::env::temp_dir().join(format!("rtracer_golden_{}", name));
+        let actual_path = output.with_extension("png").to_string_lossy().into_owned();
+        let diff_path = output
+            .with_extension("diff.png")
+            .to_string_lossy()
+            .into_owned();
+        save_canvas(render, actual_path.clone(), &transform).unwrap();
+        save_canvas(
+            diff.heat_map(),
+            diff_path.clone(),
+            &OutputTransform::linear(),
+        )
+        .unwrap();
+
+        panic!(
+            "`{}` doesn't match its golden image: {:.2}% of pixels differ, max error {:.4}, \
+             RMSE {:.4}, PSNR {:.1}dB, SSIM {:.4}. See `{}` & `{}`.",
+            name,
+            diff.percent_over_threshold(),
+            diff.max_error(),
+            diff.rmse(),
+            diff.psnr(),
+            diff.ssim(),
+            actual_path,
+            diff_path
+        );
+    }
+}
+
+fn book_camera(width: i32, height: i32) -> Camera {
+    Camera::new(
+        width,
+        height,
+        std::f64::consts::FRAC_PI_3,
+        Matrix4x4::view(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::up(),
+        ),
+    )
+}
+
+#[test]
+fn golden_default_world() {
+    let world = World::default().generate();
+    assert_matches_golden("default_world", &book_camera(48, 48).render(&world));
+}
+
+#[test]
+fn golden_default_world_path_traced() {
+    let world = World::default()
+        .set_integrator(Integrator::path_tracer(4, 4).with_seed(1))
+        .generate();
+    assert_matches_golden(
+        "default_world_path_traced",
+        &book_camera(48, 48).render(&world),
+    );
+}
+
+#[test]
+fn golden_cornell_box_scene() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/scenes/cornell_box.yml");
+    let scene = load_scene(path).unwrap();
+    let world = scene.world.generate();
+    let camera = scene.camera.with_size(64, 64);
+    assert_matches_golden("cornell_box", &camera.render(&world));
+}
@@ -0,0 +1,113 @@
+use super::{Canvas, Color};
+use std::io::{self, Read, Write};
+
+/*
+    Builds up an image from many samples per pixel, keeping a running average so the
+    image can be shown at any point. Pixels without any samples yet can be filled with
+    a nearby sample so a coarse version of the image appears straight away.
+*/
+#[derive(Clone, Debug)]
+pub struct Accumulator {
+    sums: Vec<Color>,
+    counts: Vec<u32>,
+    image: Canvas,
+}
+
+impl Accumulator {
+    pub fn new(width: i32, height: i32) -> Self {
+        let size = (width * height) as usize;
+        Self {
+            sums: vec![Color::black(); size],
+            counts: vec![0; size],
+            image: Canvas::new(width, height),
+        }
+    }
+
+    /// Add a sample of a pixel's color & update its average
+    pub fn add_sample(&mut self, x: i32, y: i32, color: Color) {
+        let index = (x + y * self.image.width()) as usize;
+        self.sums[index] = self.sums[index] + color;
+        self.counts[index] += 1;
+        self.image
+            .write_pixel(x, y, self.sums[index] * (1.0 / self.counts[index] as f64));
+    }
+
+    /// Show the color in a block of pixels which haven't had any samples yet
+    pub fn fill_block(&mut self, x: i32, y: i32, size: i32, color: Color) {
+        for by in y..(y + size).min(self.image.height()) {
+            for bx in x..(x + size).min(self.image.width()) {
+                if self.samples_at(bx, by) == 0 {
+                    self.image.write_pixel(bx, by, color);
+                }
+            }
+        }
+    }
+
+    pub fn samples_at(&self, x: i32, y: i32) -> u32 {
+        self.counts[(x + y * self.image.width()) as usize]
+    }
+
+    /// The fewest samples any pixel has
+    pub fn min_samples(&self) -> u32 {
+        self.counts.iter().copied().min().unwrap_or(0)
+    }
+
+    /// The average of each pixel's samples
+    pub fn image(&self) -> &Canvas {
+        &self.image
+    }
+
+    /// Write the size then every pixel's sum & sample count, which is all it takes to carry on later
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.image.width() as u32).to_le_bytes())?;
+        writer.write_all(&(self.image.height() as u32).to_le_bytes())?;
+        for (sum, count) in self.sums.iter().zip(&self.counts) {
+            for channel in [sum.r(), sum.g(), sum.b()] {
+                writer.write_all(&channel.to_le_bytes())?;
+            }
+            writer.write_all(&count.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read an accumulator written by `write_to`, exactly as it was
+    pub fn read_from<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let width = read_u32(reader)? as i32;
+        let height = read_u32(reader)? as i32;
+        if width <= 0 || height <= 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("an accumulator can't be {} by {}", width, height),
+            ));
+        }
+
+        let mut accumulator = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let sum = Color::new(read_f64(reader)?, read_f64(reader)?, read_f64(reader)?);
+                let count = read_u32(reader)?;
+                let index = (x + y * width) as usize;
+                accumulator.sums[index] = sum;
+                accumulator.counts[index] = count;
+                if count > 0 {
+                    accumulator
+                        .image
+                        .write_pixel(x, y, sum * (1.0 / count as f64));
+                }
+            }
+        }
+        Ok(accumulator)
+    }
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f64<R: Read>(reader: &mut R) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
@@ -0,0 +1,72 @@
+use super::{Accumulator, Canvas, Color};
+
+/// The exact bits of every pixel, as infinite colors don't compare equal
+fn bits(canvas: &Canvas) -> Vec<u64> {
+    (0..canvas.height())
+        .flat_map(|y| (0..canvas.width()).map(move |x| canvas.pixel_at(x, y)))
+        .flat_map(|c| [c.r().to_bits(), c.g().to_bits(), c.b().to_bits()])
+        .collect()
+}
+
+#[test]
+fn pixels_are_the_average_of_their_samples() {
+    let mut acc = Accumulator::new(2, 2);
+
+    acc.add_sample(1, 0, Color::new(1.0, 0.0, 0.0));
+    assert_eq!(acc.image().pixel_at(1, 0), Color::new(1.0, 0.0, 0.0));
+
+    acc.add_sample(1, 0, Color::new(0.0, 0.0, 1.0));
+    assert_eq!(acc.image().pixel_at(1, 0), Color::new(0.5, 0.0, 0.5));
+    assert_eq!(acc.samples_at(1, 0), 2);
+    assert_eq!(acc.samples_at(0, 0), 0);
+    assert_eq!(acc.image().pixel_at(0, 0), Color::black());
+}
+
+#[test]
+fn blocks_only_fill_pixels_without_samples() {
+    let mut acc = Accumulator::new(3, 3);
+    acc.add_sample(1, 1, Color::red());
+
+    acc.fill_block(0, 0, 4, Color::green());
+
+    assert_eq!(acc.image().pixel_at(0, 0), Color::green());
+    assert_eq!(acc.image().pixel_at(2, 2), Color::green());
+    assert_eq!(acc.image().pixel_at(1, 1), Color::red());
+
+    // The first real sample replaces the block's color
+    acc.add_sample(2, 2, Color::blue());
+    assert_eq!(acc.image().pixel_at(2, 2), Color::blue());
+}
+
+#[test]
+fn accumulators_can_be_saved_and_loaded_exactly() {
+    let mut acc = Accumulator::new(3, 2);
+    acc.add_sample(0, 0, Color::new(0.1, 0.2, 0.3));
+    acc.add_sample(0, 0, Color::new(1.0 / 3.0, 7.5, -0.0));
+    acc.add_sample(2, 1, Color::new(f64::INFINITY, 0.0, 1e-300));
+
+    let mut bytes = vec![];
+    acc.write_to(&mut bytes).unwrap();
+    let loaded = Accumulator::read_from(&mut bytes.as_slice()).unwrap();
+
+    assert_eq!(loaded.samples_at(0, 0), 2);
+    assert_eq!(loaded.samples_at(1, 0), 0);
+    assert_eq!(loaded.min_samples(), 0);
+    assert_eq!(bits(loaded.image()), bits(acc.image()));
+
+    // Carrying on gives the same result as the original
+    acc.add_sample(0, 0, Color::red());
+    let mut loaded = loaded;
+    loaded.add_sample(0, 0, Color::red());
+    assert_eq!(bits(loaded.image()), bits(acc.image()));
+}
+
+#[test]
+fn truncated_accumulators_fail_to_load() {
+    let mut bytes = vec![];
+    Accumulator::new(2, 2).write_to(&mut bytes).unwrap();
+    bytes.truncate(bytes.len() - 1);
+
+    assert!(Accumulator::read_from(&mut bytes.as_slice()).is_err());
+    assert!(Accumulator::read_from(&mut [0u8; 8].as_slice()).is_err());
+}
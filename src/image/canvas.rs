@@ -1,5 +1,6 @@
-use super::Color;
+use super::{Color, ImageDiff, OutputTransform};
 
+#[derive(Clone, Debug)]
 pub struct Canvas {
     width: i32,
     height: i32,
@@ -40,15 +41,12 @@ impl Canvas {
         (x + y * self.width()) as usize
     }
 
-    pub fn get_save_buffer(&self) -> Vec<u8> {
-        let mut result = Vec::new();
-        result.reserve((self.width() * self.height() * 4) as usize);
+    pub fn get_save_buffer(&self, transform: &OutputTransform) -> Vec<u8> {
+        let mut result = Vec::with_capacity((self.width() * self.height() * 4) as usize);
 
         // Convert each color into [u8; 4]
         for pixel in self.pixels.iter() {
-            let r = (pixel.r().min(1.0).max(0.0) * 255.0) as u8;
-            let g = (pixel.g().min(1.0).max(0.0) * 255.0) as u8;
-            let b = (pixel.b().min(1.0).max(0.0) * 255.0) as u8;
+            let [r, g, b] = transform.to_rgb8(*pixel);
             result.push(r);
             result.push(g);
             result.push(b);
@@ -58,6 +56,11 @@ impl Canvas {
         result
     }
 
+    /// Compare with the image this one should look like. See `ImageDiff::new`.
+    pub fn compare(&self, expected: &Canvas, threshold: f64) -> Result<ImageDiff, String> {
+        ImageDiff::new(self, expected, threshold)
+    }
+
     pub fn downsample(&self, sample_width: i32, sample_height: i32) -> Canvas {
         let mut new_canvas = Canvas::new(sample_width, sample_height);
         let (half_pixel_width, half_pixel_height) = (
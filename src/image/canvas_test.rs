@@ -1,4 +1,4 @@
-use super::{Canvas, Color};
+use super::{Canvas, Color, OutputTransform};
 
 #[test]
 fn can_create_a_canvas() {
@@ -24,12 +24,27 @@ fn canvas_can_be_written_to_png() {
         c.write_pixel(i, i, Color::red());
     }
 
-    let save_buffer = c.get_save_buffer();
+    let save_buffer = c.get_save_buffer(&OutputTransform::default());
     let expected_buffer = vec![255, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 0, 0, 255];
 
     assert_eq!(save_buffer, expected_buffer);
 }
 
+#[test]
+fn saved_pixels_are_gamma_encoded_and_rounded() {
+    let mut c = Canvas::new(2, 1);
+    c.write_pixel(0, 0, Color::new(0.5, 0.2, -1.0));
+    c.write_pixel(1, 0, Color::new(0.5, 0.2, 3.0));
+
+    let srgb = c.get_save_buffer(&OutputTransform::srgb());
+    let linear = c.get_save_buffer(&OutputTransform::linear());
+    let default = c.get_save_buffer(&OutputTransform::default());
+
+    assert_eq!(default, linear);
+    assert_eq!(srgb, vec![188, 124, 0, 255, 188, 124, 255, 255]);
+    assert_eq!(linear, vec![128, 51, 0, 255, 128, 51, 255, 255]);
+}
+
 fn create_canvas_for_sampling() -> Canvas {
     let mut canvas = Canvas::new(4, 4);
     canvas.write_pixel(0, 0, Color::red());
@@ -1,22 +1,126 @@
-use super::Canvas;
+use super::{Canvas, Color, OutputTransform};
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{BufReader, BufWriter, Read, Write};
 use std::path::Path;
 
-pub fn save_canvas(canvas: &Canvas, path: String) -> Result<(), png::EncodingError> {
-    // Create file & writer
-    let out_path = Path::new(&path);
-    let file = File::create(out_path)?;
-    let buf_writer = BufWriter::new(file);
-    // Create png writer
-    let mut encoder = png::Encoder::new(buf_writer, canvas.width() as u32, canvas.height() as u32);
+/// How many bits each channel of a PNG is saved with
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PngDepth {
+    Eight,
+    Sixteen,
+}
+
+/// Save the canvas as an opaque 8 bit PNG after passing it through the output transform
+pub fn save_canvas(
+    canvas: &Canvas,
+    path: String,
+    transform: &OutputTransform,
+) -> Result<(), png::EncodingError> {
+    save_png(canvas, None, path, transform, PngDepth::Eight)
+}
+
+/*
+    Save the canvas as a PNG. When there's an alpha canvas its red channel is used as
+    the opacity of each pixel, e.g. the coverage from `Aov::Alpha`. It isn't affected
+    by the output transform.
+*/
+pub fn save_png(
+    canvas: &Canvas,
+    alpha: Option<&Canvas>,
+    path: String,
+    transform: &OutputTransform,
+    depth: PngDepth,
+) -> Result<(), png::EncodingError> {
+    let file = File::create(Path::new(&path))?;
+    write_png(BufWriter::new(file), canvas, alpha, transform, depth)
+}
+
+pub fn write_png<W: Write>(
+    writer: W,
+    canvas: &Canvas,
+    alpha: Option<&Canvas>,
+    transform: &OutputTransform,
+    depth: PngDepth,
+) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(writer, canvas.width() as u32, canvas.height() as u32);
     encoder.set_color(png::ColorType::Rgba);
-    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_depth(match depth {
+        PngDepth::Eight => png::BitDepth::Eight,
+        PngDepth::Sixteen => png::BitDepth::Sixteen,
+    });
     let mut writer = encoder.write_header()?;
 
-    // Write image data
-    let image_data = canvas.get_save_buffer();
+    let opacity = |x: i32, y: i32| match alpha {
+        Some(alpha) => alpha.pixel_at(x, y).r().clamp(0.0, 1.0),
+        None => 1.0,
+    };
+
+    let image_data = match depth {
+        PngDepth::Eight if alpha.is_none() => canvas.get_save_buffer(transform),
+        PngDepth::Eight => {
+            let mut buffer = canvas.get_save_buffer(transform);
+            for (i, pixel) in buffer.chunks_mut(4).enumerate() {
+                let (x, y) = (i as i32 % canvas.width(), i as i32 / canvas.width());
+                pixel[3] = (opacity(x, y) * 255.0).round() as u8;
+            }
+            buffer
+        }
+        PngDepth::Sixteen => {
+            // 16 bit PNGs are big endian
+            let mut buffer = Vec::with_capacity((canvas.width() * canvas.height() * 8) as usize);
+            for y in 0..canvas.height() {
+                for x in 0..canvas.width() {
+                    let [r, g, b] = transform.to_rgb16(canvas.pixel_at(x, y));
+                    let a = (opacity(x, y) * 65535.0).round() as u16;
+                    for value in [r, g, b, a] {
+                        buffer.extend_from_slice(&value.to_be_bytes());
+                    }
+                }
+            }
+            buffer
+        }
+    };
     writer.write_image_data(&image_data)?;
 
     Ok(())
 }
+
+/*
+    Load a PNG into a canvas. The values are the ones stored in the file scaled to 0 to 1,
+    so they're still gamma encoded. Any alpha channel is ignored.
+*/
+pub fn load_png(path: String) -> Result<Canvas, png::DecodingError> {
+    let file = File::open(Path::new(&path))?;
+    read_png(BufReader::new(file))
+}
+
+pub fn read_png<R: Read>(reader: R) -> Result<Canvas, png::DecodingError> {
+    let mut decoder = png::Decoder::new(reader);
+    // Expand palettes & grey images with less than 8 bits
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+
+    let samples: Vec<f64> = match info.bit_depth {
+        png::BitDepth::Sixteen => buffer[..info.buffer_size()]
+            .chunks(2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as f64 / 65535.0)
+            .collect(),
+        _ => buffer[..info.buffer_size()]
+            .iter()
+            .map(|&byte| byte as f64 / 255.0)
+            .collect(),
+    };
+    let channels = info.color_type.samples();
+
+    let mut canvas = Canvas::new(info.width as i32, info.height as i32);
+    for (i, pixel) in samples.chunks(channels).enumerate() {
+        let color = match channels {
+            1 | 2 => Color::new(pixel[0], pixel[0], pixel[0]),
+            _ => Color::new(pixel[0], pixel[1], pixel[2]),
+        };
+        canvas.write_pixel(i as i32 % canvas.width(), i as i32 / canvas.width(), color);
+    }
+    Ok(canvas)
+}
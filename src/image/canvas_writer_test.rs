@@ -0,0 +1,88 @@
+use super::{
+    canvas_writer::{read_png, write_png},
+    Canvas, Color, OutputTransform, PngDepth,
+};
+
+fn decode(data: &[u8]) -> (png::BitDepth, Vec<u8>) {
+    let decoder = png::Decoder::new(data);
+    let mut reader = decoder.read_info().unwrap();
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).unwrap();
+    buffer.truncate(info.buffer_size());
+    (info.bit_depth, buffer)
+}
+
+#[test]
+fn pngs_are_opaque_without_an_alpha_canvas() {
+    let mut c = Canvas::new(2, 1);
+    c.write_pixel(0, 0, Color::red());
+
+    let mut data = vec![];
+    write_png(
+        &mut data,
+        &c,
+        None,
+        &OutputTransform::default(),
+        PngDepth::Eight,
+    )
+    .unwrap();
+
+    let (depth, pixels) = decode(&data);
+    assert_eq!(depth, png::BitDepth::Eight);
+    assert_eq!(pixels, vec![255, 0, 0, 255, 0, 0, 0, 255]);
+}
+
+#[test]
+fn the_alpha_canvas_sets_how_opaque_each_pixel_is() {
+    let mut c = Canvas::new(2, 1);
+    c.write_pixel(0, 0, Color::white());
+    c.write_pixel(1, 0, Color::white());
+    let mut alpha = Canvas::new(2, 1);
+    alpha.write_pixel(0, 0, Color::white());
+
+    let mut data = vec![];
+    let transform = OutputTransform::default();
+    write_png(&mut data, &c, Some(&alpha), &transform, PngDepth::Eight).unwrap();
+
+    let (_, pixels) = decode(&data);
+    assert_eq!(pixels, vec![255, 255, 255, 255, 255, 255, 255, 0]);
+}
+
+#[test]
+fn sixteen_bit_pngs_keep_more_precision() {
+    let mut c = Canvas::new(1, 1);
+    c.write_pixel(0, 0, Color::new(0.5, 1.0 / 1024.0, 2.0));
+    let mut alpha = Canvas::new(1, 1);
+    alpha.write_pixel(0, 0, Color::new(0.5, 0.5, 0.5));
+
+    let mut data = vec![];
+    let transform = OutputTransform::linear();
+    write_png(&mut data, &c, Some(&alpha), &transform, PngDepth::Sixteen).unwrap();
+
+    let (depth, pixels) = decode(&data);
+    assert_eq!(depth, png::BitDepth::Sixteen);
+    let values: Vec<u16> = pixels
+        .chunks(2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .collect();
+    // 1/1024 would be lost in 8 bits
+    assert_eq!(values, vec![32768, 64, 65535, 32768]);
+}
+
+#[test]
+fn pngs_can_be_read_back_into_a_canvas() {
+    let mut c = Canvas::new(3, 2);
+    c.write_pixel(0, 0, Color::red());
+    c.write_pixel(2, 1, Color::new(0.2, 0.4, 0.6));
+
+    for depth in [PngDepth::Eight, PngDepth::Sixteen] {
+        let mut data = vec![];
+        write_png(&mut data, &c, None, &OutputTransform::linear(), depth).unwrap();
+        let loaded = read_png(data.as_slice()).unwrap();
+
+        assert_eq!((loaded.width(), loaded.height()), (3, 2));
+        assert_eq!(loaded.pixel_at(0, 0), Color::red());
+        let diff = loaded.compare(&c, 0.5 / 255.0).unwrap();
+        assert_eq!(diff.percent_over_threshold(), 0.0, "{:?}", depth);
+    }
+}
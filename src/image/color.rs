@@ -46,6 +46,20 @@ impl Color {
         Color::new(0.0, 0.0, 0.0)
     }
 
+    /// Convert from the CIE XYZ color space to linear sRGB
+    pub fn from_xyz(x: f64, y: f64, z: f64) -> Color {
+        Color::new(
+            3.2406 * x - 1.5372 * y - 0.4986 * z,
+            -0.9689 * x + 1.8758 * y + 0.0415 * z,
+            0.0557 * x - 0.2040 * y + 1.0570 * z,
+        )
+    }
+
+    /// The perceived brightness of the color using the Rec. 709 weights
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.r() + 0.7152 * self.g() + 0.0722 * self.b()
+    }
+
     pub fn to_u32(&self) -> u32 {
         let r = (255.0 * self.r()) as u32;
         let g = (255.0 * self.g()) as u32;
@@ -0,0 +1,179 @@
+use super::{Canvas, Color};
+
+/// How far apart two images are, with a heat map showing where they differ
+#[derive(Clone, Debug)]
+pub struct ImageDiff {
+    /// The largest difference of any channel for each pixel, row by row
+    errors: Vec<f64>,
+    threshold: f64,
+    mse: f64,
+    ssim: f64,
+    heat_map: Canvas,
+}
+
+// Windows for SSIM are 8x8 pixels, moved 4 pixels at a time
+const SSIM_WINDOW: i32 = 8;
+const SSIM_STEP: i32 = 4;
+
+impl ImageDiff {
+    /*
+        Compare an image against the one it's meant to look like. Colors are expected to
+        be in 0 to 1, like the ones loaded from a PNG or after an output transform. A
+        pixel counts as different when any channel is more than `threshold` away.
+    */
+    pub fn new(actual: &Canvas, expected: &Canvas, threshold: f64) -> Result<Self, String> {
+        if (actual.width(), actual.height()) != (expected.width(), expected.height()) {
+            return Err(format!(
+                "can't compare a {}x{} image with a {}x{} one",
+                actual.width(),
+                actual.height(),
+                expected.width(),
+                expected.height()
+            ));
+        }
+
+        let (width, height) = (actual.width(), actual.height());
+        let mut errors = Vec::with_capacity((width * height) as usize);
+        let mut squared = 0.0;
+        for y in 0..height {
+            for x in 0..width {
+                let (a, e) = (actual.pixel_at(x, y), expected.pixel_at(x, y));
+                let channels = [(a.r(), e.r()), (a.g(), e.g()), (a.b(), e.b())];
+                errors.push(
+                    channels
+                        .iter()
+                        .map(|(a, e)| (a - e).abs())
+                        .fold(0.0, f64::max),
+                );
+                squared += channels.iter().map(|(a, e)| (a - e).powi(2)).sum::<f64>();
+            }
+        }
+        let mse = squared / (errors.len() * 3).max(1) as f64;
+
+        let largest = errors.iter().copied().fold(0.0, f64::max);
+        let mut heat_map = Canvas::new(width, height);
+        for (i, error) in errors.iter().enumerate() {
+            let scaled = if largest > 0.0 { error / largest } else { 0.0 };
+            heat_map.write_pixel(i as i32 % width, i as i32 / width, heat(scaled));
+        }
+
+        Ok(Self {
+            errors,
+            threshold,
+            mse,
+            ssim: ssim(actual, expected),
+            heat_map,
+        })
+    }
+
+    /// The largest difference of any channel at a pixel
+    pub fn pixel_error(&self, x: i32, y: i32) -> f64 {
+        self.errors[(x + y * self.heat_map.width()) as usize]
+    }
+
+    pub fn max_error(&self) -> f64 {
+        self.errors.iter().copied().fold(0.0, f64::max)
+    }
+
+    pub fn mean_error(&self) -> f64 {
+        self.errors.iter().sum::<f64>() / self.errors.len().max(1) as f64
+    }
+
+    /// Root mean squared error over every channel
+    pub fn rmse(&self) -> f64 {
+        self.mse.sqrt()
+    }
+
+    /// Peak signal to noise ratio in decibels. Infinite for identical images.
+    pub fn psnr(&self) -> f64 {
+        if self.mse == 0.0 {
+            f64::INFINITY
+        } else {
+            -10.0 * self.mse.log10()
+        }
+    }
+
+    /// Structural similarity of the luminance, where 1 means the images are the same
+    pub fn ssim(&self) -> f64 {
+        self.ssim
+    }
+
+    /// The percentage of pixels which differ by more than the threshold
+    pub fn percent_over_threshold(&self) -> f64 {
+        let over = self.errors.iter().filter(|&&e| e > self.threshold).count();
+        100.0 * over as f64 / self.errors.len().max(1) as f64
+    }
+
+    /// Black where the images match, going through blue, green & yellow to red at the largest difference
+    pub fn heat_map(&self) -> &Canvas {
+        &self.heat_map
+    }
+}
+
+fn heat(t: f64) -> Color {
+    if t <= 0.0 {
+        return Color::black();
+    }
+    let stops = [
+        Color::blue(),
+        Color::green(),
+        Color::new(1.0, 1.0, 0.0),
+        Color::red(),
+    ];
+    let scaled = t.min(1.0) * (stops.len() - 1) as f64;
+    let i = (scaled as usize).min(stops.len() - 2);
+    let f = scaled - i as f64;
+    stops[i] * (1.0 - f) + stops[i + 1] * f
+}
+
+/*
+    The mean SSIM of windows over the luminance of the images. Images smaller than a
+    window are compared as a whole.
+*/
+fn ssim(a: &Canvas, b: &Canvas) -> f64 {
+    const C1: f64 = 0.01 * 0.01;
+    const C2: f64 = 0.03 * 0.03;
+
+    let (width, height) = (a.width(), a.height());
+    let (window_width, window_height) = (SSIM_WINDOW.min(width), SSIM_WINDOW.min(height));
+    let starts = |size: i32, window: i32| {
+        let mut starts: Vec<i32> = (0..=size - window).step_by(SSIM_STEP as usize).collect();
+        // Make sure the last row & column are covered
+        if starts.last() != Some(&(size - window)) {
+            starts.push(size - window);
+        }
+        starts
+    };
+
+    let mut total = 0.0;
+    let mut windows = 0;
+    for y0 in starts(height, window_height) {
+        for x0 in starts(width, window_width) {
+            let pixels =
+                (y0..y0 + window_height).flat_map(|y| (x0..x0 + window_width).map(move |x| (x, y)));
+            let values: Vec<(f64, f64)> = pixels
+                .map(|(x, y)| (a.pixel_at(x, y).luminance(), b.pixel_at(x, y).luminance()))
+                .collect();
+            let n = values.len() as f64;
+            let mean_a = values.iter().map(|v| v.0).sum::<f64>() / n;
+            let mean_b = values.iter().map(|v| v.1).sum::<f64>() / n;
+            let var_a = values.iter().map(|v| (v.0 - mean_a).powi(2)).sum::<f64>() / n;
+            let var_b = values.iter().map(|v| (v.1 - mean_b).powi(2)).sum::<f64>() / n;
+            let covariance = values
+                .iter()
+                .map(|v| (v.0 - mean_a) * (v.1 - mean_b))
+                .sum::<f64>()
+                / n;
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a.powi(2) + mean_b.powi(2) + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+
+    if windows == 0 {
+        1.0
+    } else {
+        total / windows as f64
+    }
+}
@@ -0,0 +1,95 @@
+use super::{Canvas, Color};
+
+fn gradient(width: i32, height: i32) -> Canvas {
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let v = (x + y) as f64 / (width + height) as f64;
+            canvas.write_pixel(x, y, Color::new(v, 1.0 - v, 0.5));
+        }
+    }
+    canvas
+}
+
+#[test]
+fn identical_images_have_no_differences() {
+    let image = gradient(16, 12);
+
+    let diff = image.compare(&image, 0.0).unwrap();
+
+    assert_eq!(diff.max_error(), 0.0);
+    assert_eq!(diff.mean_error(), 0.0);
+    assert_eq!(diff.rmse(), 0.0);
+    assert_eq!(diff.psnr(), f64::INFINITY);
+    assert!((diff.ssim() - 1.0).abs() < 1e-9);
+    assert_eq!(diff.percent_over_threshold(), 0.0);
+    assert_eq!(diff.heat_map().pixel_at(5, 5), Color::black());
+}
+
+#[test]
+fn the_error_of_each_pixel_is_its_largest_channel_difference() {
+    let expected = Canvas::new(4, 4);
+    let mut actual = Canvas::new(4, 4);
+    actual.write_pixel(1, 2, Color::new(0.1, 0.5, 0.0));
+    actual.write_pixel(3, 3, Color::new(0.0, 0.0, 0.25));
+
+    let diff = actual.compare(&expected, 0.2).unwrap();
+
+    assert_eq!(diff.pixel_error(1, 2), 0.5);
+    assert_eq!(diff.pixel_error(3, 3), 0.25);
+    assert_eq!(diff.pixel_error(0, 0), 0.0);
+    assert_eq!(diff.max_error(), 0.5);
+    assert_eq!(diff.mean_error(), 0.75 / 16.0);
+    assert_eq!(diff.percent_over_threshold(), 2.0 / 16.0 * 100.0);
+
+    // 0.1² + 0.5² + 0.25² spread over every channel of every pixel
+    let mse: f64 = (0.01 + 0.25 + 0.0625) / 48.0;
+    assert!((diff.rmse() - mse.sqrt()).abs() < 1e-12);
+    assert!((diff.psnr() - -10.0 * mse.log10()).abs() < 1e-9);
+}
+
+#[test]
+fn the_heat_map_is_hottest_at_the_largest_difference() {
+    let expected = Canvas::new(3, 1);
+    let mut actual = Canvas::new(3, 1);
+    actual.write_pixel(1, 0, Color::new(0.1, 0.1, 0.1));
+    actual.write_pixel(2, 0, Color::new(0.3, 0.0, 0.0));
+
+    let diff = actual.compare(&expected, 0.0).unwrap();
+    let heat_map = diff.heat_map();
+
+    assert_eq!(heat_map.pixel_at(0, 0), Color::black());
+    // A third of the largest difference
+    assert_eq!(heat_map.pixel_at(1, 0), Color::green());
+    assert_eq!(heat_map.pixel_at(2, 0), Color::red());
+}
+
+#[test]
+fn structural_similarity_drops_as_images_differ() {
+    let expected = gradient(32, 32);
+    let mut noisy = expected.clone();
+    let mut inverted = Canvas::new(32, 32);
+    for y in 0..32 {
+        for x in 0..32 {
+            let c = expected.pixel_at(x, y);
+            let noise = if (x * 7 + y * 13) % 5 == 0 { 0.05 } else { 0.0 };
+            noisy.write_pixel(x, y, c + Color::new(noise, noise, noise));
+            inverted.write_pixel(x, y, Color::white() - c);
+        }
+    }
+
+    let slightly = noisy.compare(&expected, 0.01).unwrap().ssim();
+    let very = inverted.compare(&expected, 0.01).unwrap().ssim();
+
+    assert!(slightly < 1.0);
+    assert!(slightly > 0.5);
+    assert!(very < slightly);
+}
+
+#[test]
+fn images_of_different_sizes_cannot_be_compared() {
+    let error = Canvas::new(2, 2)
+        .compare(&Canvas::new(2, 3), 0.0)
+        .unwrap_err();
+    assert_eq!(error, "can't compare a 2x2 image with a 2x3 one");
+}
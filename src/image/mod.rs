@@ -1,18 +1,41 @@
-// This is a library to the code won't be used yet
-#![allow(dead_code)]
-
 // Code files
+mod accumulator;
 mod canvas;
 mod canvas_writer;
 mod color;
+mod compare;
+mod openexr;
+mod output_transform;
+mod ppm;
+mod radiance_hdr;
 
 // Exports
+pub use accumulator::Accumulator;
 pub use canvas::Canvas;
-pub use canvas_writer::save_canvas;
+pub use canvas_writer::{load_png, save_canvas, save_png, PngDepth};
 pub use color::Color;
+pub(crate) use compare::ImageDiff;
+pub use openexr::{load_exr, save_exr, ExrPrecision};
+pub use output_transform::{srgb_encode, OutputTransform, ToneMap};
+pub use ppm::{save_ppm, PpmEncoding};
+pub use radiance_hdr::{load_hdr, save_hdr};
 
 // Tests
 #[cfg(test)]
+mod accumulator_test;
+#[cfg(test)]
 mod canvas_test;
 #[cfg(test)]
+mod canvas_writer_test;
+#[cfg(test)]
 mod color_test;
+#[cfg(test)]
+mod compare_test;
+#[cfg(test)]
+mod openexr_test;
+#[cfg(test)]
+mod output_transform_test;
+#[cfg(test)]
+mod ppm_test;
+#[cfg(test)]
+mod radiance_hdr_test;
@@ -0,0 +1,103 @@
+use super::{Canvas, Color};
+use exr::prelude::*;
+
+/// How many bits each channel is stored with in an OpenEXR file
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExrPrecision {
+    Half,
+    Float,
+}
+
+/*
+    Save canvases as the layers of one OpenEXR image without clamping any values.
+    Layers are stored as channels named `<layer>.R`, `<layer>.G` & `<layer>.B` so
+    compositing tools can find them. A layer with an empty name gets plain `R`, `G` & `B`.
+    All of the canvases must be the same size.
+*/
+pub fn save_exr(layers: &[(&str, &Canvas)], path: &str, precision: ExrPrecision) -> Result<()> {
+    let (width, height) = match layers.first() {
+        Some((_, canvas)) => (canvas.width(), canvas.height()),
+        None => return Err(Error::Invalid("an image needs at least one layer".into())),
+    };
+    if layers
+        .iter()
+        .any(|(_, canvas)| canvas.width() != width || canvas.height() != height)
+    {
+        return Err(Error::Invalid("every layer must be the same size".into()));
+    }
+
+    let mut channels = vec![];
+    for (name, canvas) in layers {
+        for (component, value) in [
+            ("R", Color::r as fn(&Color) -> f64),
+            ("G", Color::g),
+            ("B", Color::b),
+        ] {
+            let values = (0..height)
+                .flat_map(|y| (0..width).map(move |x| value(&canvas.pixel_at(x, y)) as f32));
+            let samples = match precision {
+                ExrPrecision::Half => FlatSamples::F16(values.map(f16::from_f32).collect()),
+                ExrPrecision::Float => FlatSamples::F32(values.collect()),
+            };
+            let channel_name = match *name {
+                "" => component.to_owned(),
+                name => format!("{}.{}", name, component),
+            };
+            channels.push(AnyChannel::new(channel_name.as_str(), samples));
+        }
+    }
+
+    let layer = Layer::new(
+        (width as usize, height as usize),
+        LayerAttributes::default(),
+        Encoding::FAST_LOSSLESS,
+        AnyChannels::sort(channels.into()),
+    );
+    Image::from_layer(layer).write().to_file(path)
+}
+
+/// Load each layer of an OpenEXR image saved by `save_exr`, sorted by name
+pub fn load_exr(path: &str) -> Result<Vec<(String, Canvas)>> {
+    let image = read()
+        .no_deep_data()
+        .largest_resolution_level()
+        .all_channels()
+        .first_valid_layer()
+        .all_attributes()
+        .from_file(path)?;
+
+    let layer = image.layer_data;
+    let (width, height) = (layer.size.width() as i32, layer.size.height() as i32);
+
+    let mut canvases: Vec<(String, Canvas)> = vec![];
+    for channel in &layer.channel_data.list {
+        let name = channel.name.to_string();
+        let (layer_name, component) = match name.rsplit_once('.') {
+            Some((layer_name, component)) => (layer_name.to_owned(), component.to_owned()),
+            None => (String::new(), name),
+        };
+        let index = match canvases.iter().position(|(name, _)| *name == layer_name) {
+            Some(index) => index,
+            None => {
+                canvases.push((layer_name, Canvas::new(width, height)));
+                canvases.len() - 1
+            }
+        };
+
+        let canvas = &mut canvases[index].1;
+        for (i, value) in channel.sample_data.values_as_f32().enumerate() {
+            let (x, y) = (i as i32 % width, i as i32 / width);
+            let old = canvas.pixel_at(x, y);
+            let value = value as f64;
+            let color = match component.as_str() {
+                "R" => Color::new(value, old.g(), old.b()),
+                "G" => Color::new(old.r(), value, old.b()),
+                "B" => Color::new(old.r(), old.g(), value),
+                _ => old,
+            };
+            canvas.write_pixel(x, y, color);
+        }
+    }
+
+    Ok(canvases)
+}
@@ -0,0 +1,98 @@
+use super::{load_exr, save_exr, Canvas, Color, ExrPrecision};
+
+fn bright_canvas(width: i32, height: i32) -> Canvas {
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let value = (x + y * width) as f64;
+            canvas.write_pixel(x, y, Color::new(value * 10.0, -value, 0.125));
+        }
+    }
+    canvas
+}
+
+fn temp_path(name: &str) -> String {
+    std::env::temp_dir()
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn float_exr_images_round_trip_exactly() {
+    let canvas = bright_canvas(5, 3);
+    let path = temp_path("rtracer_float_test.exr");
+
+    save_exr(&[("", &canvas)], &path, ExrPrecision::Float).unwrap();
+    let layers = load_exr(&path).unwrap();
+
+    assert_eq!(layers.len(), 1);
+    let (name, loaded) = &layers[0];
+    assert_eq!(name, "");
+    assert_eq!((loaded.width(), loaded.height()), (5, 3));
+    for y in 0..3 {
+        for x in 0..5 {
+            assert_eq!(loaded.pixel_at(x, y), canvas.pixel_at(x, y));
+        }
+    }
+    // Nothing is clamped to 1
+    assert_eq!(loaded.pixel_at(4, 2).r(), 140.0);
+}
+
+#[test]
+fn half_exr_images_keep_3_significant_figures() {
+    let canvas = bright_canvas(4, 4);
+    let path = temp_path("rtracer_half_test.exr");
+
+    save_exr(&[("", &canvas)], &path, ExrPrecision::Half).unwrap();
+    let (_, loaded) = &load_exr(&path).unwrap()[0];
+
+    for y in 0..4 {
+        for x in 0..4 {
+            let (expected, actual) = (canvas.pixel_at(x, y), loaded.pixel_at(x, y));
+            for (e, a) in [
+                (expected.r(), actual.r()),
+                (expected.g(), actual.g()),
+                (expected.b(), actual.b()),
+            ] {
+                assert!((e - a).abs() <= e.abs() * 1e-3, "{} != {}", e, a);
+            }
+        }
+    }
+}
+
+#[test]
+fn several_layers_can_be_saved_in_one_exr_image() {
+    let beauty = bright_canvas(3, 2);
+    let mut depth = Canvas::new(3, 2);
+    depth.write_pixel(1, 1, Color::new(42.0, 42.0, 42.0));
+    let path = temp_path("rtracer_layers_test.exr");
+
+    save_exr(
+        &[("", &beauty), ("depth", &depth)],
+        &path,
+        ExrPrecision::Float,
+    )
+    .unwrap();
+    let layers = load_exr(&path).unwrap();
+
+    let names: Vec<&str> = layers.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["", "depth"]);
+    assert_eq!(layers[0].1.pixel_at(2, 1), beauty.pixel_at(2, 1));
+    assert_eq!(layers[1].1.pixel_at(1, 1), Color::new(42.0, 42.0, 42.0));
+    assert_eq!(layers[1].1.pixel_at(0, 0), Color::black());
+}
+
+#[test]
+fn exr_layers_must_be_the_same_size() {
+    let path = temp_path("rtracer_bad_layers_test.exr");
+    let (small, large) = (Canvas::new(2, 2), Canvas::new(3, 3));
+
+    assert!(save_exr(&[], &path, ExrPrecision::Float).is_err());
+    assert!(save_exr(
+        &[("", &small), ("depth", &large)],
+        &path,
+        ExrPrecision::Float
+    )
+    .is_err());
+}
@@ -0,0 +1,180 @@
+use super::{Canvas, Color};
+use std::str::FromStr;
+
+/// How colors brighter than white are squeezed into the range a display can show
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ToneMap {
+    /// Cut everything off at 1
+    Clamp,
+    /// `c / (1 + c)` for each channel
+    Reinhard,
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve
+    Aces,
+    /// John Hable's filmic curve from Uncharted 2
+    Hable,
+}
+
+impl ToneMap {
+    pub const ALL: [ToneMap; 4] = [
+        ToneMap::Clamp,
+        ToneMap::Reinhard,
+        ToneMap::Aces,
+        ToneMap::Hable,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ToneMap::Clamp => "clamp",
+            ToneMap::Reinhard => "reinhard",
+            ToneMap::Aces => "aces",
+            ToneMap::Hable => "hable",
+        }
+    }
+
+    /// Map a single channel into 0 to 1
+    pub fn apply(&self, value: f64) -> f64 {
+        // NaNs & negative values are treated as black
+        let value = value.max(0.0);
+        if value.is_infinite() {
+            return 1.0;
+        }
+        let mapped = match self {
+            ToneMap::Clamp => value,
+            ToneMap::Reinhard => value / (1.0 + value),
+            ToneMap::Aces => {
+                let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+                (value * (a * value + b)) / (value * (c * value + d) + e)
+            }
+            ToneMap::Hable => {
+                // The curve is scaled so the white point of 11.2 ends up at 1
+                const EXPOSURE_BIAS: f64 = 2.0;
+                hable(value * EXPOSURE_BIAS) / hable(11.2)
+            }
+        };
+        mapped.clamp(0.0, 1.0)
+    }
+}
+
+fn hable(x: f64) -> f64 {
+    let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+    ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f
+}
+
+impl FromStr for ToneMap {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        ToneMap::ALL
+            .iter()
+            .find(|tone_map| tone_map.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = ToneMap::ALL
+                    .iter()
+                    .map(|tone_map| tone_map.name())
+                    .collect();
+                format!(
+                    "unknown tone map `{}`. Expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/*
+    Turns the linear light a render produces into what gets shown on screen or saved
+    into an 8 bit image. The exposure is applied first, then the tone map & finally,
+    when asked for, the sRGB transfer function so the image isn't too dark on a normal
+    display. By default values are saved linearly, as the book does.
+*/
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OutputTransform {
+    /// Brightness adjustment in stops. +1 doubles the light, -1 halves it.
+    pub exposure: f64,
+    pub tone_map: ToneMap,
+    /// Whether to gamma encode with the sRGB curve
+    pub srgb: bool,
+}
+
+impl Default for OutputTransform {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            tone_map: ToneMap::Clamp,
+            srgb: false,
+        }
+    }
+}
+
+impl OutputTransform {
+    /// Clamp the values & nothing else. For images which hold data rather than light.
+    pub fn linear() -> Self {
+        Self {
+            srgb: false,
+            ..Self::default()
+        }
+    }
+
+    /// Gamma encode for an sRGB display, with no exposure change or tone map
+    pub fn srgb() -> Self {
+        Self {
+            srgb: true,
+            ..Self::default()
+        }
+    }
+
+    /// The color as it should be displayed, with every channel in 0 to 1
+    pub fn apply(&self, color: Color) -> Color {
+        let scale = 2f64.powf(self.exposure);
+        let channel = |value: f64| {
+            let value = self.tone_map.apply(value * scale);
+            if self.srgb {
+                srgb_encode(value)
+            } else {
+                value
+            }
+        };
+        Color::new(channel(color.r()), channel(color.g()), channel(color.b()))
+    }
+
+    /// A copy of the canvas as it would be displayed
+    pub fn apply_to_canvas(&self, canvas: &Canvas) -> Canvas {
+        let mut image = Canvas::new(canvas.width(), canvas.height());
+        for y in 0..canvas.height() {
+            for x in 0..canvas.width() {
+                image.write_pixel(x, y, self.apply(canvas.pixel_at(x, y)));
+            }
+        }
+        image
+    }
+
+    /// The color as rounded 8 bit red, green & blue values
+    pub fn to_rgb8(&self, color: Color) -> [u8; 3] {
+        let color = self.apply(color);
+        let byte = |value: f64| (value * 255.0).round() as u8;
+        [byte(color.r()), byte(color.g()), byte(color.b())]
+    }
+
+    /// The color as rounded 16 bit red, green & blue values
+    pub fn to_rgb16(&self, color: Color) -> [u16; 3] {
+        let color = self.apply(color);
+        let word = |value: f64| (value * 65535.0).round() as u16;
+        [word(color.r()), word(color.g()), word(color.b())]
+    }
+
+    /// The color packed as `0RGB`, the format the preview window uses
+    pub fn to_u32(&self, color: Color) -> u32 {
+        let [r, g, b] = self.to_rgb8(color);
+        ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+    }
+}
+
+/// The sRGB opto-electronic transfer function for a value in 0 to 1
+pub fn srgb_encode(value: f64) -> f64 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
@@ -0,0 +1,82 @@
+use super::{srgb_encode, Color, OutputTransform, ToneMap};
+use std::str::FromStr;
+
+fn round(v: f64) -> f64 {
+    (v * 10000.0).round() / 10000.0
+}
+
+#[test]
+fn the_srgb_curve_is_linear_near_black() {
+    assert_eq!(srgb_encode(0.0), 0.0);
+    assert_eq!(round(srgb_encode(0.002)), round(0.002 * 12.92));
+    assert_eq!(round(srgb_encode(0.5)), 0.7354);
+    assert_eq!(round(srgb_encode(1.0)), 1.0);
+}
+
+#[test]
+fn every_tone_map_keeps_values_in_range() {
+    for tone_map in ToneMap::ALL {
+        assert_eq!(tone_map.apply(0.0), 0.0, "{:?}", tone_map);
+        assert_eq!(tone_map.apply(-2.0), 0.0, "{:?}", tone_map);
+        assert_eq!(tone_map.apply(f64::NAN), 0.0, "{:?}", tone_map);
+
+        let mut previous = 0.0;
+        for value in [0.1, 0.5, 1.0, 2.0, 10.0, 1000.0, f64::INFINITY] {
+            let mapped = tone_map.apply(value);
+            assert!((0.0..=1.0).contains(&mapped), "{:?} {}", tone_map, value);
+            assert!(mapped >= previous, "{:?} isn't increasing", tone_map);
+            previous = mapped;
+        }
+    }
+}
+
+#[test]
+fn tone_maps_compress_highlights() {
+    assert_eq!(ToneMap::Clamp.apply(4.0), 1.0);
+    assert_eq!(ToneMap::Reinhard.apply(1.0), 0.5);
+    assert_eq!(ToneMap::Reinhard.apply(3.0), 0.75);
+    assert!(ToneMap::Aces.apply(4.0) < 1.0);
+    assert!(ToneMap::Aces.apply(4.0) > ToneMap::Aces.apply(2.0));
+    assert_eq!(round(ToneMap::Hable.apply(5.6)), 1.0);
+    assert!(ToneMap::Hable.apply(2.0) < 1.0);
+}
+
+#[test]
+fn exposure_is_measured_in_stops() {
+    let transform = OutputTransform {
+        exposure: 1.0,
+        ..OutputTransform::linear()
+    };
+    assert_eq!(
+        transform.apply(Color::new(0.25, 0.1, 1.0)),
+        Color::new(0.5, 0.2, 1.0)
+    );
+
+    let darker = OutputTransform {
+        exposure: -2.0,
+        ..OutputTransform::linear()
+    };
+    assert_eq!(
+        darker.apply(Color::new(2.0, 1.0, 0.0)),
+        Color::new(0.5, 0.25, 0.0)
+    );
+}
+
+#[test]
+fn colors_are_packed_for_the_preview_window() {
+    let transform = OutputTransform::srgb();
+
+    assert_eq!(transform.to_u32(Color::white()), 0x00ff_ffff);
+    assert_eq!(transform.to_u32(Color::new(1.0, 0.5, 0.0)), 0x00ff_bc00);
+    assert_eq!(
+        OutputTransform::linear().to_rgb8(Color::new(0.5, 0.0, 0.0)),
+        [128, 0, 0]
+    );
+}
+
+#[test]
+fn tone_maps_can_be_parsed_by_name() {
+    assert_eq!(ToneMap::from_str("aces"), Ok(ToneMap::Aces));
+    assert_eq!(ToneMap::from_str("hable"), Ok(ToneMap::Hable));
+    assert!(ToneMap::from_str("filmic").is_err());
+}
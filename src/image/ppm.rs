@@ -0,0 +1,70 @@
+use super::{Canvas, OutputTransform};
+use std::fs::File;
+use std::io::{BufWriter, Error, Write};
+use std::path::Path;
+
+/// The two flavours of PPM file
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PpmEncoding {
+    /// `P3`, the text format the book uses
+    Plain,
+    /// `P6`, one byte per channel
+    Binary,
+}
+
+/// Save the canvas as an 8 bit PPM after passing it through the output transform
+pub fn save_ppm(
+    canvas: &Canvas,
+    path: String,
+    transform: &OutputTransform,
+    encoding: PpmEncoding,
+) -> Result<(), Error> {
+    let file = File::create(Path::new(&path))?;
+    let mut writer = BufWriter::new(file);
+    write_ppm(&mut writer, canvas, transform, encoding)?;
+    writer.flush()
+}
+
+pub fn write_ppm<W: Write>(
+    writer: &mut W,
+    canvas: &Canvas,
+    transform: &OutputTransform,
+    encoding: PpmEncoding,
+) -> Result<(), Error> {
+    let magic = match encoding {
+        PpmEncoding::Plain => "P3",
+        PpmEncoding::Binary => "P6",
+    };
+    write!(
+        writer,
+        "{}\n{} {}\n255\n",
+        magic,
+        canvas.width(),
+        canvas.height()
+    )?;
+
+    for y in 0..canvas.height() {
+        let row = (0..canvas.width()).flat_map(|x| transform.to_rgb8(canvas.pixel_at(x, y)));
+        match encoding {
+            PpmEncoding::Binary => writer.write_all(&row.collect::<Vec<u8>>())?,
+            PpmEncoding::Plain => {
+                // Lines in a plain PPM shouldn't be longer than 70 characters
+                let mut line = String::new();
+                for value in row {
+                    let value = value.to_string();
+                    if !line.is_empty() && line.len() + 1 + value.len() > 70 {
+                        writeln!(writer, "{}", line)?;
+                        line.clear();
+                    }
+                    if !line.is_empty() {
+                        line.push(' ');
+                    }
+                    line.push_str(&value);
+                }
+                writeln!(writer, "{}", line)?;
+            }
+        }
+    }
+
+    Ok(())
+}
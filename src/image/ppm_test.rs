@@ -0,0 +1,87 @@
+use super::{
+    ppm::{write_ppm, PpmEncoding},
+    Canvas, Color, OutputTransform,
+};
+
+fn ppm_text(canvas: &Canvas) -> String {
+    let mut data = vec![];
+    write_ppm(
+        &mut data,
+        canvas,
+        &OutputTransform::linear(),
+        PpmEncoding::Plain,
+    )
+    .unwrap();
+    String::from_utf8(data).unwrap()
+}
+
+#[test]
+fn constructing_the_ppm_header() {
+    let c = Canvas::new(5, 3);
+    let ppm = ppm_text(&c);
+    let lines: Vec<&str> = ppm.lines().take(3).collect();
+
+    assert_eq!(lines, vec!["P3", "5 3", "255"]);
+}
+
+#[test]
+fn constructing_the_ppm_pixel_data() {
+    let mut c = Canvas::new(5, 3);
+    c.write_pixel(0, 0, Color::new(1.5, 0.0, 0.0));
+    c.write_pixel(2, 1, Color::new(0.0, 0.5, 0.0));
+    c.write_pixel(4, 2, Color::new(-0.5, 0.0, 1.0));
+
+    let ppm = ppm_text(&c);
+    let lines: Vec<&str> = ppm.lines().skip(3).collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            "255 0 0 0 0 0 0 0 0 0 0 0 0 0 0",
+            "0 0 0 0 0 0 0 128 0 0 0 0 0 0 0",
+            "0 0 0 0 0 0 0 0 0 0 0 0 0 0 255",
+        ]
+    );
+}
+
+#[test]
+fn splitting_long_lines_in_ppm_files() {
+    let mut c = Canvas::new(10, 2);
+    for y in 0..2 {
+        for x in 0..10 {
+            c.write_pixel(x, y, Color::new(1.0, 0.8, 0.6));
+        }
+    }
+
+    let ppm = ppm_text(&c);
+    let lines: Vec<&str> = ppm.lines().skip(3).collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+            "153 255 204 153 255 204 153 255 204 153 255 204 153",
+            "255 204 153 255 204 153 255 204 153 255 204 153 255 204 153 255 204",
+            "153 255 204 153 255 204 153 255 204 153 255 204 153",
+        ]
+    );
+}
+
+#[test]
+fn ppm_files_are_terminated_by_a_newline() {
+    let c = Canvas::new(5, 3);
+    assert!(ppm_text(&c).ends_with('\n'));
+}
+
+#[test]
+fn binary_ppm_files_have_a_byte_per_channel() {
+    let mut c = Canvas::new(2, 1);
+    c.write_pixel(1, 0, Color::new(1.0, 0.5, 0.0));
+
+    let mut data = vec![];
+    write_ppm(&mut data, &c, &OutputTransform::srgb(), PpmEncoding::Binary).unwrap();
+
+    let header = b"P6\n2 1\n255\n";
+    assert_eq!(&data[..header.len()], header);
+    assert_eq!(&data[header.len()..], &[0, 0, 0, 255, 188, 0]);
+}
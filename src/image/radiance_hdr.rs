@@ -0,0 +1,237 @@
+use super::{Canvas, Color};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Error, ErrorKind, Read, Write};
+use std::path::Path;
+
+fn invalid_data(message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, message.to_owned())
+}
+
+/// Load a Radiance RGBE (.hdr) image into a canvas without clamping any values
+pub fn load_hdr(path: String) -> Result<Canvas, Error> {
+    let file = File::open(Path::new(&path))?;
+    read_hdr(&mut BufReader::new(file))
+}
+
+pub fn read_hdr<R: BufRead>(reader: &mut R) -> Result<Canvas, Error> {
+    // The header is a list of lines which ends with an empty line
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if !line.starts_with("#?") {
+        return Err(invalid_data("Missing Radiance HDR signature"));
+    }
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(invalid_data("Unexpected end of HDR header"));
+        }
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            break;
+        }
+        if let Some(format) = trimmed.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(invalid_data(
+                    "Only 32-bit_rle_rgbe HDR images are supported",
+                ));
+            }
+        }
+    }
+
+    // Only the standard orientation of top to bottom & left to right is supported
+    line.clear();
+    reader.read_line(&mut line)?;
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    let (height, width) = match parts.as_slice() {
+        ["-Y", height, "+X", width] => (
+            height
+                .parse::<i32>()
+                .map_err(|_| invalid_data("Bad height"))?,
+            width
+                .parse::<i32>()
+                .map_err(|_| invalid_data("Bad width"))?,
+        ),
+        _ => return Err(invalid_data("Unsupported HDR resolution line")),
+    };
+    if height < 1 || width < 1 {
+        return Err(invalid_data("HDR image sizes must be at least 1"));
+    }
+
+    let mut canvas = Canvas::new(width, height);
+    for y in 0..height {
+        let scanline = read_scanline(reader, width as usize)?;
+        for (x, rgbe) in scanline.iter().enumerate() {
+            canvas.write_pixel(x as i32, y, rgbe_to_color(*rgbe));
+        }
+    }
+
+    Ok(canvas)
+}
+
+fn read_scanline<R: Read>(reader: &mut R, width: usize) -> Result<Vec<[u8; 4]>, Error> {
+    let mut first = [0u8; 4];
+    reader.read_exact(&mut first)?;
+
+    // Scanlines outside this width range or without the marker are stored flat
+    let is_rle = (8..0x8000).contains(&width)
+        && first[0] == 2
+        && first[1] == 2
+        && ((first[2] as usize) << 8 | first[3] as usize) == width;
+
+    if !is_rle {
+        let mut scanline = vec![first];
+        for _ in 1..width {
+            let mut pixel = [0u8; 4];
+            reader.read_exact(&mut pixel)?;
+            scanline.push(pixel);
+        }
+        return Ok(scanline);
+    }
+
+    // Each of the 4 components is run length encoded separately
+    let mut scanline = vec![[0u8; 4]; width];
+    for component in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let mut count = [0u8; 1];
+            reader.read_exact(&mut count)?;
+            let count = count[0] as usize;
+
+            if count > 128 {
+                // A run of the same value
+                let run = count - 128;
+                if x + run > width {
+                    return Err(invalid_data("HDR run overflows the scanline"));
+                }
+                let mut value = [0u8; 1];
+                reader.read_exact(&mut value)?;
+                for pixel in scanline.iter_mut().skip(x).take(run) {
+                    pixel[component] = value[0];
+                }
+                x += run;
+            } else {
+                // A sequence of different values
+                if count == 0 || x + count > width {
+                    return Err(invalid_data("Bad HDR scanline data"));
+                }
+                let mut values = vec![0u8; count];
+                reader.read_exact(&mut values)?;
+                for (pixel, value) in scanline.iter_mut().skip(x).zip(values) {
+                    pixel[component] = value;
+                }
+                x += count;
+            }
+        }
+    }
+
+    Ok(scanline)
+}
+
+fn rgbe_to_color(rgbe: [u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::black();
+    }
+    // The mantissas are stored as fractions of 256 sharing the exponent
+    let scale = 2f64.powi(rgbe[3] as i32 - 136);
+    Color::new(
+        (rgbe[0] as f64 + 0.5) * scale,
+        (rgbe[1] as f64 + 0.5) * scale,
+        (rgbe[2] as f64 + 0.5) * scale,
+    )
+}
+
+/// Save a canvas as a Radiance RGBE (.hdr) image, keeping values above 1. Negative values become 0.
+pub fn save_hdr(canvas: &Canvas, path: String) -> Result<(), Error> {
+    let file = File::create(Path::new(&path))?;
+    let mut writer = BufWriter::new(file);
+    write_hdr(&mut writer, canvas)?;
+    writer.flush()
+}
+
+pub fn write_hdr<W: Write>(writer: &mut W, canvas: &Canvas) -> Result<(), Error> {
+    writer.write_all(b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n")?;
+    writeln!(writer, "-Y {} +X {}", canvas.height(), canvas.width())?;
+
+    let width = canvas.width() as usize;
+    for y in 0..canvas.height() {
+        let scanline: Vec<[u8; 4]> = (0..canvas.width())
+            .map(|x| color_to_rgbe(canvas.pixel_at(x, y)))
+            .collect();
+
+        // Readers only expect run length encoding for widths in this range
+        if !(8..0x8000).contains(&width) {
+            for pixel in &scanline {
+                writer.write_all(pixel)?;
+            }
+            continue;
+        }
+
+        writer.write_all(&[2, 2, (width >> 8) as u8, (width & 0xff) as u8])?;
+        for component in 0..4 {
+            let values: Vec<u8> = scanline.iter().map(|pixel| pixel[component]).collect();
+            write_rle(writer, &values)?;
+        }
+    }
+    Ok(())
+}
+
+/// Encode one component of a scanline as runs of the same value & sequences of different ones
+fn write_rle<W: Write>(writer: &mut W, values: &[u8]) -> Result<(), Error> {
+    // Runs shorter than this take more space than storing the values
+    const MIN_RUN: usize = 4;
+
+    let run_at = |start: usize| {
+        values[start..]
+            .iter()
+            .take(127)
+            .take_while(|&&value| value == values[start])
+            .count()
+    };
+
+    let mut x = 0;
+    while x < values.len() {
+        let run = run_at(x);
+        if run >= MIN_RUN {
+            writer.write_all(&[128 + run as u8, values[x]])?;
+            x += run;
+            continue;
+        }
+
+        // Collect values up to the next worthwhile run
+        let start = x;
+        while x < values.len() && x - start < 128 && run_at(x) < MIN_RUN {
+            x += 1;
+        }
+        writer.write_all(&[(x - start) as u8])?;
+        writer.write_all(&values[start..x])?;
+    }
+    Ok(())
+}
+
+fn color_to_rgbe(color: Color) -> [u8; 4] {
+    let channel = |value: f64| {
+        if value.is_finite() {
+            value.max(0.0)
+        } else {
+            0.0
+        }
+    };
+    let (r, g, b) = (channel(color.r()), channel(color.g()), channel(color.b()));
+
+    let largest = r.max(g).max(b);
+    if largest < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    // Share the exponent which puts the largest component's mantissa in [0.5, 1)
+    let exponent = (largest.log2().floor() as i32 + 1).min(127);
+    let scale = 256.0 / 2f64.powi(exponent);
+    let mantissa = |value: f64| (value * scale).min(255.0) as u8;
+    [
+        mantissa(r),
+        mantissa(g),
+        mantissa(b),
+        (exponent + 128) as u8,
+    ]
+}
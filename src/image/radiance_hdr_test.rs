@@ -0,0 +1,140 @@
+use super::{
+    load_hdr,
+    radiance_hdr::{read_hdr, write_hdr},
+    save_hdr, Canvas, Color,
+};
+use crate::maths::round;
+use std::io::Cursor;
+
+const HEADER: &[u8] = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n";
+
+#[test]
+fn reading_a_flat_hdr_image() {
+    let mut data = HEADER.to_vec();
+    data.extend_from_slice(b"-Y 1 +X 2\n");
+    data.extend_from_slice(&[128, 64, 0, 129, 0, 0, 0, 0]);
+
+    let canvas = read_hdr(&mut Cursor::new(data)).unwrap();
+
+    assert_eq!(canvas.width(), 2);
+    assert_eq!(canvas.height(), 1);
+    let pixel = canvas.pixel_at(0, 0);
+    assert_eq!(round(pixel.r()), 1.00391);
+    assert_eq!(round(pixel.g()), 0.50391);
+    assert_eq!(canvas.pixel_at(1, 0), Color::black());
+}
+
+#[test]
+fn reading_a_run_length_encoded_hdr_image() {
+    let mut data = HEADER.to_vec();
+    data.extend_from_slice(b"-Y 1 +X 8\n");
+    data.extend_from_slice(&[2, 2, 0, 8]);
+    // Red is a run of 8 values
+    data.extend_from_slice(&[128 + 8, 128]);
+    // Green is 8 separate values
+    data.extend_from_slice(&[8, 0, 16, 32, 48, 64, 80, 96, 112]);
+    // Blue is 2 runs of 4
+    data.extend_from_slice(&[128 + 4, 0, 128 + 4, 255]);
+    // The exponent is shared
+    data.extend_from_slice(&[128 + 8, 129]);
+
+    let canvas = read_hdr(&mut Cursor::new(data)).unwrap();
+
+    assert_eq!(canvas.width(), 8);
+    assert_eq!(round(canvas.pixel_at(0, 0).r()), 1.00391);
+    assert_eq!(round(canvas.pixel_at(7, 0).r()), 1.00391);
+    assert_eq!(round(canvas.pixel_at(2, 0).g()), 0.25391);
+    assert_eq!(round(canvas.pixel_at(3, 0).b()), 0.00391);
+    assert_eq!(round(canvas.pixel_at(4, 0).b()), 1.99609);
+}
+
+#[test]
+fn reading_an_image_without_the_signature_fails() {
+    let data = b"P3\n1 1\n255\n".to_vec();
+    assert!(read_hdr(&mut Cursor::new(data)).is_err());
+}
+
+#[test]
+fn reading_an_image_with_a_bad_size_fails() {
+    for resolution in ["-Y -1 +X 2", "-Y 1 +X 0"] {
+        let data = format!("#?RADIANCE\n\n{}\n", resolution).into_bytes();
+        let error = read_hdr(&mut Cursor::new(data)).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn loading_an_hdr_image_from_disk() {
+    let mut data = HEADER.to_vec();
+    data.extend_from_slice(b"-Y 1 +X 1\n");
+    data.extend_from_slice(&[128, 128, 128, 130]);
+
+    let path = std::env::temp_dir().join("rtracer_load_hdr_test.hdr");
+    std::fs::write(&path, data).unwrap();
+
+    let canvas = load_hdr(path.to_string_lossy().into_owned()).unwrap();
+    assert_eq!(round(canvas.pixel_at(0, 0).r()), 2.00781);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+fn assert_close(expected: Color, actual: Color) {
+    // RGBE keeps 8 bits of mantissa shared between the components
+    let tolerance = expected.r().max(expected.g()).max(expected.b()) / 128.0;
+    for (e, a) in [
+        (expected.r(), actual.r()),
+        (expected.g(), actual.g()),
+        (expected.b(), actual.b()),
+    ] {
+        assert!((e - a).abs() <= tolerance, "{:?} != {:?}", expected, actual);
+    }
+}
+
+#[test]
+fn hdr_images_round_trip() {
+    // Wide enough to be run length encoded, with runs & varying values
+    let mut canvas = Canvas::new(300, 2);
+    for x in 0..300 {
+        let value = if x < 100 { 1000.0 } else { x as f64 / 7.0 };
+        canvas.write_pixel(x, 0, Color::new(value, value * 0.5, 0.25));
+        canvas.write_pixel(x, 1, Color::new(0.0, 0.0, 0.0));
+    }
+
+    let mut data = vec![];
+    write_hdr(&mut data, &canvas).unwrap();
+    let loaded = read_hdr(&mut Cursor::new(data)).unwrap();
+
+    assert_eq!((loaded.width(), loaded.height()), (300, 2));
+    for x in 0..300 {
+        assert_close(canvas.pixel_at(x, 0), loaded.pixel_at(x, 0));
+        assert_eq!(loaded.pixel_at(x, 1), Color::black());
+    }
+}
+
+#[test]
+fn narrow_hdr_images_are_saved_flat() {
+    let mut canvas = Canvas::new(3, 1);
+    canvas.write_pixel(0, 0, Color::new(2.0, 4.0, 8.0));
+    canvas.write_pixel(1, 0, Color::new(-1.0, 0.5, f64::INFINITY));
+
+    let mut data = vec![];
+    write_hdr(&mut data, &canvas).unwrap();
+    let loaded = read_hdr(&mut Cursor::new(data)).unwrap();
+
+    assert_close(canvas.pixel_at(0, 0), loaded.pixel_at(0, 0));
+    // Values which can't be stored become 0
+    assert_close(Color::new(0.0, 0.5, 0.0), loaded.pixel_at(1, 0));
+}
+
+#[test]
+fn saving_an_hdr_file() {
+    let mut canvas = Canvas::new(10, 10);
+    canvas.write_pixel(3, 4, Color::new(12.0, 1.0, 0.1));
+    let path = std::env::temp_dir().join("rtracer_save_test.hdr");
+    let path = path.to_string_lossy().into_owned();
+
+    save_hdr(&canvas, path.clone()).unwrap();
+    let loaded = load_hdr(path).unwrap();
+
+    assert_close(canvas.pixel_at(3, 4), loaded.pixel_at(3, 4));
+}
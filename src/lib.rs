@@ -0,0 +1,25 @@
+//! A ray tracer built by following The Ray Tracer Challenge.
+//!
+//! Build a scene with [`World`], look at it through a [`Camera`] & save the result with
+//! [`save_canvas`]. Scenes can also be read from YAML files with [`load_scene`].
+//! The [`render`] module saves renders in each format, runs batches & renders with checkpoints.
+
+mod cornell_box;
+pub mod image;
+pub mod maths;
+pub mod primitives;
+pub mod render;
+pub mod scene;
+
+pub use cornell_box::create_cornell_box;
+pub use image::{save_canvas, Canvas, Color, OutputTransform};
+pub use maths::{Matrix4x4, Point, Vector};
+pub use primitives::{
+    Bounds, Camera, Geometry, Integrator, IntersectionStats, Material, PointLight, Ray, Shape,
+    SurfaceShader, Tracer, World, WorldImpl,
+};
+pub use scene::{load_scene, Scene, SceneError};
+
+// Tests
+#[cfg(test)]
+mod golden_test;
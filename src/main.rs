@@ -1,31 +1,24 @@
-mod cornell_box;
-mod image;
-mod maths;
-mod primitives;
-mod worker;
-
-use cornell_box::create_cornell_box;
-use image::{save_canvas, Canvas};
-use maths::{Matrix4x4, Point, Vector};
-use minifb::{Key, Window, WindowOptions};
-use primitives::Camera;
-use rand::{seq::SliceRandom, thread_rng};
-use worker::{Worker, WorkerState};
-
-fn resample_buffer(buffer: &mut Vec<u32>, canvas: &Canvas, screen_width: i32, screen_height: i32) {
-    let downsample = canvas.downsample(screen_width, screen_height);
-
-    for y in 0..downsample.height() {
-        for x in 0..downsample.width() {
-            let color = downsample.pixel_at(x, y);
-            buffer[(x + y * downsample.width()) as usize] = color.to_u32();
-        }
-    }
-}
-
-fn main() {
-    let world = create_cornell_box().generate();
-
+mod cli;
+mod preview;
+mod progress_bar;
+
+#[cfg(test)]
+mod cli_test;
+
+use clap::Parser;
+use cli::Options;
+use preview::render_in_window;
+use progress_bar::ProgressBar;
+use rtracer::primitives::Aov;
+use rtracer::render::{
+    render_with_checkpoints, run_batch, save_render, CheckpointEvent, RenderSettings,
+};
+use rtracer::scene::{hash_world, load_manifest};
+use rtracer::{create_cornell_box, load_scene, Camera, Canvas, Matrix4x4, Point, Vector};
+use std::process::ExitCode;
+use std::time::Duration;
+
+fn cornell_box_camera() -> Camera {
     // quality 1 == 128 * 128
     // quality 4 == 1024 * 1024
     let quality_multiplier = 3;
@@ -34,77 +27,141 @@ fn main() {
         128 * 2i32.pow(quality_multiplier),
     );
 
-    let (screen_width, screen_height) = (512, 512);
-
-    // Create camera
     let view_transform = Matrix4x4::view(
         Point::new(0.0, 2.8, -10.0),
         Point::new(0.0, 2.0, 0.0),
         Vector::up(),
     );
-    let camera = Camera::new(width, height, std::f64::consts::FRAC_PI_4, view_transform);
-
-    // Generate all the points to render
-    let mut points: Vec<(i32, i32)> = (0..height)
-        .map(|row| (0..width).map(move |col| (row, col)))
-        .flatten()
-        .collect();
-
-    // Shuffle all the points
-    let mut rng = thread_rng();
-    points.shuffle(&mut rng);
-
-    // Create the window
-    let mut window = Window::new(
-        "RTracer - ESC to exit",
-        screen_width as usize,
-        screen_height as usize,
-        WindowOptions::default(),
-    )
-    .expect("Failed to create window");
-    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+    Camera::new(width, height, std::f64::consts::FRAC_PI_4, view_transform)
+}
 
-    // Start rendering all the colors in another thread
-    let worker = Worker::new(
-        points,
-        move |(row, col)| {
-            let ray = camera.ray_for_pixel(col, row);
-            ((row, col), world.color_at(ray))
-        },
-        width as usize,
-    );
+fn main() -> ExitCode {
+    let options = Options::parse();
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    // Create the buffers where the pixels will go once rendered
-    let mut buffer = vec![0u32; (screen_width * screen_height) as usize];
-    let mut canvas = Canvas::new(width, height);
+fn run(options: &Options) -> Result<(), String> {
+    if let Some(threads) = options.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads as usize)
+            .build_global()
+            .map_err(|error| error.to_string())?;
+    }
 
-    let mut saved = false; // Make sure we only save once
+    if let Some(manifest) = &options.batch {
+        return render_batch(manifest, options);
+    }
 
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        // Pull the new pixels fr
-        match worker.fetch() {
-            WorkerState::Values(vals) => vals.into_iter().for_each(|((row, col), color)| {
-                canvas.write_pixel(col, row, color);
-            }),
-            WorkerState::Complete => {
-                if !saved {
-                    // Update the window
-                    window.set_title("RTracer - ESC to exit -- Finished");
+    let save = options.save_options(options.output_format()?)?;
+    if options.alpha && !options.headless {
+        return Err("--alpha can only be used with --headless or --batch".to_owned());
+    }
 
-                    // Save the buffer to a canvas
-                    save_canvas(&canvas, "out.png".to_owned()).unwrap();
-                    saved = true;
-                }
-            }
+    // Render a scene file when one is given, otherwise the built-in Cornell box
+    let (world, camera, source_hash) = match &options.scene {
+        Some(path) => {
+            let scene = load_scene(path).map_err(|error| error.to_string())?;
+            (scene.world, scene.camera, scene.source_hash)
         }
+        None => {
+            let world = create_cornell_box();
+            let hash = hash_world(&world);
+            (world, cornell_box_camera(), hash)
+        }
+    };
+    let world = world.set_integrator(options.integrator()).generate();
+    let camera = camera.with_size(
+        options.width.unwrap_or(camera.width()),
+        options.height.unwrap_or(camera.height()),
+    );
 
-        // Resample the buffer in the location which has now changed
-        resample_buffer(&mut buffer, &canvas, screen_width, screen_height);
-
-        window
-            .update_with_buffer(&buffer, screen_width as usize, screen_height as usize)
-            .expect("Failed to update the window with buffer");
+    if options.headless {
+        // The alpha channel comes from an extra pass on the end
+        let mut aovs = options.aovs.clone();
+        if options.alpha {
+            aovs.push(Aov::Alpha);
+        }
+        let (canvas, mut passes) = match &options.checkpoint {
+            Some(path) => {
+                let settings = RenderSettings::new(
+                    options.scene.clone(),
+                    source_hash,
+                    &camera,
+                    world.integrator(),
+                );
+                let bar = ProgressBar::new();
+                let canvas = render_with_checkpoints(
+                    &world,
+                    &camera,
+                    &settings,
+                    path,
+                    Duration::from_secs(options.checkpoint_interval),
+                    options.resume,
+                    |event| match event {
+                        CheckpointEvent::Resumed { samples } => println!(
+                            "Resuming from `{}` after {} of {} samples",
+                            path, samples, settings.samples
+                        ),
+                        CheckpointEvent::Progress(progress) => bar.update(progress),
+                        CheckpointEvent::SaveFailed(error) => eprintln!("\nwarning: {}", error),
+                    },
+                );
+                bar.finish();
+                (canvas?, camera.render_aovs(&world, &aovs))
+            }
+            None => {
+                let bar = ProgressBar::new();
+                let rendered = camera
+                    .render_passes_with_progress(&world, &aovs, |progress| bar.update(progress));
+                bar.finish();
+                rendered
+            }
+        };
+        let alpha = if options.alpha { passes.pop() } else { None };
+        let passes: Vec<(Aov, Canvas)> = options.aovs.iter().copied().zip(passes).collect();
+        save_render(&canvas, alpha.as_ref(), &passes, &options.output, &save)
+    } else {
+        render_in_window(
+            world,
+            camera,
+            &options.output,
+            save,
+            options.preview_options(),
+        )
     }
+}
 
-    worker.finish();
+/// Render every job in a manifest & write a summary of how they went
+fn render_batch(manifest: &str, options: &Options) -> Result<(), String> {
+    let jobs = load_manifest(manifest).map_err(|error| error.to_string())?;
+    let bar = ProgressBar::new();
+    let summary = run_batch(
+        &jobs,
+        &options.batch_options()?,
+        |progress| bar.update(progress),
+        |job| {
+            bar.finish();
+            match &job.error {
+                None => println!("rendered {} in {:.1}s", job.output, job.seconds),
+                Some(error) => eprintln!("error: {}", error),
+            }
+        },
+    );
+    summary.save(&options.summary)?;
+
+    if summary.failures > 0 {
+        return Err(format!(
+            "{} of {} jobs failed. See `{}` for details.",
+            summary.failures,
+            summary.jobs.len(),
+            options.summary
+        ));
+    }
+    Ok(())
 }
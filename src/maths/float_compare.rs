@@ -12,8 +12,8 @@ pub fn round(v: f64) -> f64 {
 
 #[test]
 fn test_is_same() {
-    assert_eq!(is_same(0.0, 0.0), true);
-    assert_eq!(is_same(1.0, 1.0), true);
-    assert_eq!(is_same(1.0, 0.0), false);
-    assert_eq!(is_same(1.0, 1.1), false);
+    assert!(is_same(0.0, 0.0));
+    assert!(is_same(1.0, 1.0));
+    assert!(!is_same(1.0, 0.0));
+    assert!(!is_same(1.0, 1.1));
 }
@@ -30,7 +30,7 @@ impl Matrix2x2 {
         self.methods().at(row, col)
     }
 
-    fn methods(&self) -> MatrixMethods {
+    fn methods(&self) -> MatrixMethods<'_> {
         MatrixMethods::new(&self.data, 2)
     }
 
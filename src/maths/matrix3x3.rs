@@ -38,7 +38,7 @@ impl Matrix3x3 {
         self.methods().at(row, col)
     }
 
-    fn methods(&self) -> MatrixMethods {
+    fn methods(&self) -> MatrixMethods<'_> {
         MatrixMethods::new(&self.data, 3)
     }
 
@@ -57,7 +57,7 @@ impl Matrix3x3 {
         if row + col % 2 == 0 {
             minor
         } else {
-            minor * -1.0
+            -minor
         }
     }
 
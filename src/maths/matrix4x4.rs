@@ -176,7 +176,7 @@ impl Matrix4x4 {
         self.methods().at(row, col)
     }
 
-    fn methods(&self) -> MatrixMethods {
+    fn methods(&self) -> MatrixMethods<'_> {
         MatrixMethods::new(&self.data, 4)
     }
 
@@ -212,10 +212,10 @@ impl Matrix4x4 {
 
     pub fn cofactor(&self, row: usize, col: usize) -> f64 {
         let minor = self.minor(row, col);
-        if (row + col) % 2 == 0 {
+        if (row + col).is_multiple_of(2) {
             minor
         } else {
-            minor * -1.0
+            -minor
         }
     }
 
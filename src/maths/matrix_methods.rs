@@ -7,7 +7,7 @@ pub struct MatrixMethods<'a> {
 }
 
 impl<'a> MatrixMethods<'a> {
-    pub fn new(data: &'a [f64], size: usize) -> MatrixMethods {
+    pub fn new(data: &'a [f64], size: usize) -> MatrixMethods<'a> {
         MatrixMethods { data, size }
     }
 
@@ -19,8 +19,7 @@ impl<'a> MatrixMethods<'a> {
     }
 
     pub fn submatrix_data(&self, row: usize, col: usize) -> Vec<f64> {
-        let mut data = Vec::new();
-        data.reserve(self.size * self.size);
+        let mut data = Vec::with_capacity(self.size * self.size);
 
         for current_row in 0..self.size {
             for current_col in 0..self.size {
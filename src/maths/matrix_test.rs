@@ -167,14 +167,14 @@ fn testing_matrix_invertability() {
     );
 
     assert_eq!(invertable.determinant(), -2120.0);
-    assert_eq!(invertable.is_invertable(), true);
+    assert!(invertable.is_invertable());
 
     let not_invertable = Matrix4x4::new(
         -4.0, 2.0, -2.0, -3.0, 9.0, 6.0, 2.0, 6.0, 0.0, -5.0, 1.0, -5.0, 0.0, 0.0, 0.0, 0.0,
     );
 
     assert_eq!(not_invertable.determinant(), 0.0);
-    assert_eq!(not_invertable.is_invertable(), false);
+    assert!(!not_invertable.is_invertable());
 }
 
 #[test]
@@ -14,15 +14,15 @@ fn can_create_a_tuple_and_get_its_values() {
 #[test]
 fn tuple_with_w_1_is_a_vector() {
     let t = Tuple::new(1.0, 2.0, 3.0, 0.0);
-    assert_eq!(t.is_vector(), true);
-    assert_eq!(t.is_point(), false);
+    assert!(t.is_vector());
+    assert!(!t.is_point());
 }
 
 #[test]
 fn tuple_with_w_0_is_a_point() {
     let t = Tuple::new(1.0, 2.0, 3.0, 1.0);
-    assert_eq!(t.is_point(), true);
-    assert_eq!(t.is_vector(), false);
+    assert!(t.is_point());
+    assert!(!t.is_vector());
 }
 
 #[test]
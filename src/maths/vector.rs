@@ -1,5 +1,5 @@
 use super::Tuple;
-use std::ops::{Mul, Neg, Sub};
+use std::ops::{Add, Mul, Neg, Sub};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Vector {
@@ -85,6 +85,13 @@ impl Vector {
     }
 }
 
+impl Add for Vector {
+    type Output = Vector;
+    fn add(self, rhs: Vector) -> Vector {
+        Vector::new_from_tuple(self.data + rhs.data)
+    }
+}
+
 impl Sub for Vector {
     type Output = Vector;
     fn sub(self, rhs: Vector) -> Vector {
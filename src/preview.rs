@@ -0,0 +1,430 @@
+use minifb::{Key, MouseButton, MouseMode, Window, WindowOptions};
+use rtracer::image::{Accumulator, OutputTransform};
+use rtracer::primitives::{
+    PixelInspection, Progress, ProgressTracker, Tile, TileOrder, TileScheduler, Viewpoint,
+};
+use rtracer::render::{coarse_to_fine, save_image, SaveOptions, Worker, WorkerState};
+use rtracer::{Camera, Canvas, Color, Point, WorldImpl};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const SCREEN_WIDTH: i32 = 512;
+const SCREEN_HEIGHT: i32 = 512;
+
+/// Radians to orbit for each pixel the mouse is dragged
+const ORBIT_SPEED: f64 = 0.01;
+/// How much of the distance to the target to fly each frame a key is held
+const FLY_SPEED: f64 = 0.02;
+/// How much each step of the scroll wheel zooms
+const ZOOM_STEP: f64 = 0.9;
+/// How often the progress in the title bar changes
+const TITLE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How the window renders
+#[derive(Copy, Clone, Debug)]
+pub struct PreviewOptions {
+    /// Show the whole image straight away & add a sample to every pixel each pass
+    pub progressive: bool,
+    pub tile_size: i32,
+    pub tile_order: TileOrder,
+}
+
+/// Some of the image to render in one go
+enum Piece {
+    /// Pixels along with the block each one covers until it's rendered at full size
+    Pixels(Vec<(i32, i32, i32)>),
+    Tile(Tile),
+}
+
+/// Which pass a piece is for, the piece & the colors of its pixels
+type Sample = (u32, Piece, Vec<Color>);
+
+/// A render of one view running in the background
+struct Render {
+    worker: Worker<Sample>,
+    world: Arc<WorldImpl>,
+    image: Accumulator,
+    passes: u32,
+    pixel_count: u64,
+    progress: ProgressTracker,
+}
+
+impl Render {
+    /*
+        Start rendering a view. Tiles are rendered in the order the options ask for & sent
+        back as they're completed, starting nearest `cursor` for the cursor order. With
+        `coarse_first` the first pass is a blocky version of the whole image instead & in
+        progressive mode each pass adds one sample to every pixel.
+    */
+    fn start(
+        world: &Arc<WorldImpl>,
+        camera: &Camera,
+        options: PreviewOptions,
+        coarse_first: bool,
+        cursor: Option<(f64, f64)>,
+    ) -> Self {
+        let (width, height) = (camera.width(), camera.height());
+        let scheduler =
+            TileScheduler::new(width, height, options.tile_size).with_order(options.tile_order);
+        let tiles = match (options.tile_order, cursor) {
+            (TileOrder::Cursor, Some((x, y))) => scheduler.tiles_nearest(x, y),
+            _ => scheduler.tiles(),
+        };
+
+        let first_pass: Vec<Piece> = if coarse_first {
+            coarse_to_fine(width, height)
+                .chunks(width as usize)
+                .map(|points| Piece::Pixels(points.to_vec()))
+                .collect()
+        } else {
+            tiles.iter().map(|&tile| Piece::Tile(tile)).collect()
+        };
+        let passes = if options.progressive {
+            world.integrator().samples().max(1)
+        } else {
+            1
+        };
+        let work = first_pass
+            .into_iter()
+            .map(|piece| (0, piece))
+            .chain((1..passes).flat_map(move |sample| {
+                let tiles = tiles.clone();
+                tiles
+                    .into_iter()
+                    .map(move |tile| (sample, Piece::Tile(tile)))
+            }));
+
+        // Render the pieces on the thread pool, each one on a single thread
+        let pixel_count = (width * height) as u64;
+        let progress = ProgressTracker::new(pixel_count * passes as u64, world.rays_cast());
+        let render_world = world.clone();
+        let camera = camera.with_size(width, height);
+        let progressive = options.progressive;
+        let worker = Worker::cancellable(
+            work,
+            move |(sample, piece), cancelled| {
+                let color = |x, y| {
+                    // A view which has been moved away from is left unfinished
+                    if cancelled.load(Ordering::Relaxed) {
+                        Color::black()
+                    } else if progressive {
+                        camera.pixel_sample(&render_world, x, y, sample)
+                    } else {
+                        camera.pixel_color(&render_world, x, y)
+                    }
+                };
+                let colors = match &piece {
+                    Piece::Pixels(points) => points.iter().map(|&(x, y, _)| color(x, y)).collect(),
+                    Piece::Tile(tile) => tile.pixels().map(|(x, y)| color(x, y)).collect(),
+                };
+                render_world.count_rays();
+                (sample, piece, colors)
+            },
+            rayon::current_num_threads() * 2,
+        );
+
+        Self {
+            worker,
+            world: world.clone(),
+            image: Accumulator::new(width, height),
+            passes,
+            pixel_count,
+            progress,
+        }
+    }
+
+    fn passes_done(&self) -> u64 {
+        self.progress.completed() / self.pixel_count
+    }
+
+    /// How far through the view the render is, counting a pixel for each pass
+    fn progress(&self) -> Progress {
+        self.progress.progress(self.world.rays_cast())
+    }
+
+    /// Add the colors which have been rendered since the last update, returning whether it's complete
+    fn update(&mut self) -> bool {
+        match self.worker.fetch() {
+            WorkerState::Values(vals) => {
+                for (sample, piece, colors) in vals {
+                    self.progress.add(colors.len() as u64);
+                    match piece {
+                        Piece::Pixels(points) => {
+                            for ((x, y, block), color) in points.into_iter().zip(colors) {
+                                if sample == 0 && block > 1 {
+                                    self.image.fill_block(x, y, block, color);
+                                }
+                                self.image.add_sample(x, y, color);
+                            }
+                        }
+                        Piece::Tile(tile) => {
+                            for ((x, y), color) in tile.pixels().zip(colors) {
+                                self.image.add_sample(x, y, color);
+                            }
+                        }
+                    }
+                }
+                false
+            }
+            WorkerState::Complete => true,
+        }
+    }
+}
+
+fn resample_buffer(buffer: &mut [u32], canvas: &Canvas, transform: &OutputTransform) {
+    let downsample = canvas.downsample(SCREEN_WIDTH, SCREEN_HEIGHT);
+
+    for y in 0..downsample.height() {
+        for x in 0..downsample.width() {
+            let color = downsample.pixel_at(x, y);
+            buffer[(x + y * downsample.width()) as usize] = transform.to_u32(color);
+        }
+    }
+}
+
+/// What the mouse was doing last frame
+#[derive(Default)]
+struct Mouse {
+    position: Option<(f32, f32)>,
+    left_down: bool,
+    /// Whether it's moved since the left button was pressed
+    dragged: bool,
+}
+
+/*
+    Move the view with the mouse & keyboard, returning whether it moved. Dragging with
+    the left button orbits, the right button pans & the scroll wheel zooms. W, A, S & D
+    fly forwards, left, back & right while Q & E fly down & up.
+*/
+fn navigate(window: &Window, view: &mut Viewpoint, mouse: &mut Mouse, pan_scale: f64) -> bool {
+    let before = *view;
+
+    let position = window.get_mouse_pos(MouseMode::Pass);
+    if let (Some((x, y)), Some((last_x, last_y))) = (position, mouse.position) {
+        let (dx, dy) = ((x - last_x) as f64, (y - last_y) as f64);
+        if window.get_mouse_down(MouseButton::Left) && (dx != 0.0 || dy != 0.0) {
+            view.orbit(-dx * ORBIT_SPEED, dy * ORBIT_SPEED);
+            mouse.dragged = true;
+        } else if window.get_mouse_down(MouseButton::Right) {
+            // The scene follows the mouse
+            let scale = pan_scale * view.distance();
+            view.pan(-dx * scale, dy * scale);
+        }
+    }
+    mouse.position = position;
+
+    if let Some((_, scroll)) = window.get_scroll_wheel().filter(|&(_, y)| y != 0.0) {
+        view.zoom(ZOOM_STEP.powf(scroll.signum() as f64));
+    }
+
+    let speed = FLY_SPEED * view.distance();
+    let axis = |positive: Key, negative: Key| {
+        let held = |key| if window.is_key_down(key) { 1.0 } else { 0.0 };
+        (held(positive) - held(negative)) * speed
+    };
+    let (forward, right, up) = (
+        axis(Key::W, Key::S),
+        axis(Key::D, Key::A),
+        axis(Key::E, Key::Q),
+    );
+    if forward != 0.0 || right != 0.0 || up != 0.0 {
+        view.fly(forward, right, up);
+    }
+
+    *view != before
+}
+
+/// Where the left button was clicked without dragging, in window coordinates
+fn clicked(window: &Window, mouse: &mut Mouse) -> Option<(f32, f32)> {
+    let down = window.get_mouse_down(MouseButton::Left);
+    let click = if mouse.left_down && !down && !mouse.dragged {
+        mouse.position
+    } else {
+        None
+    };
+    if !down {
+        mouse.dragged = false;
+    }
+    mouse.left_down = down;
+    click
+}
+
+/// The pixel of the image under the mouse, if it's over the window
+fn cursor_pixel(window: &Window, camera: &Camera) -> Option<(f64, f64)> {
+    let (x, y) = window.get_mouse_pos(MouseMode::Discard)?;
+    Some((
+        x as f64 * camera.width() as f64 / SCREEN_WIDTH as f64,
+        y as f64 * camera.height() as f64 / SCREEN_HEIGHT as f64,
+    ))
+}
+
+/*
+    Show the image in a window as it renders & save it once it's complete. The view can
+    be moved around, which starts the render again from a blocky version of the image.
+    In progressive mode closing the window early saves the passes which are done.
+*/
+pub fn render_in_window(
+    world: WorldImpl,
+    camera: Camera,
+    output: &str,
+    save: SaveOptions,
+    options: PreviewOptions,
+) -> Result<(), String> {
+    let progressive = options.progressive;
+    let world = Arc::new(world);
+    let mut camera = camera;
+
+    // Orbit around whatever is in the middle of the view
+    let center = camera.ray_for_pixel(camera.width() / 2, camera.height() / 2);
+    let depth = world.surface_info_at(center).depth;
+    let mut view = Viewpoint::from_camera(&camera, if depth.is_finite() { depth } else { 10.0 });
+    let pan_scale = camera.pixel_size() * camera.width() as f64 / SCREEN_WIDTH as f64;
+    let mut mouse = Mouse::default();
+    let mut moved = false;
+
+    // Create the window
+    let mut window = Window::new(
+        "RTracer - ESC to exit",
+        SCREEN_WIDTH as usize,
+        SCREEN_HEIGHT as usize,
+        WindowOptions::default(),
+    )
+    .map_err(|error| format!("failed to create a window: {}", error))?;
+    window.limit_update_rate(Some(std::time::Duration::from_micros(16600)));
+
+    // Create the buffers where the pixels will go once rendered
+    let mut buffer = vec![0u32; (SCREEN_WIDTH * SCREEN_HEIGHT) as usize];
+    let mut render = Render::start(
+        &world,
+        &camera,
+        options,
+        progressive,
+        cursor_pixel(&window, &camera),
+    );
+
+    let mut saved = false; // Make sure we only save once per view
+    let mut inspecting = false; // Leave a pixel's details in the title until the view moves
+    let mut title_changed = Instant::now();
+    let mut result = Ok(());
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        if navigate(&window, &mut view, &mut mouse, pan_scale) {
+            /*
+                Throw away the old view & start again with a rough version of the new one.
+                The old render stops at the next pixel without the window waiting for it.
+            */
+            camera = camera.with_transform(view.transform());
+            render.worker.cancel();
+            render = Render::start(
+                &world,
+                &camera,
+                options,
+                true,
+                cursor_pixel(&window, &camera),
+            );
+            window.set_title("RTracer - ESC to exit");
+            saved = false;
+            inspecting = false;
+            moved = true;
+        }
+
+        // Show what's under the mouse when the image is clicked
+        if let Some((x, y)) = clicked(&window, &mut mouse) {
+            let x = (x as f64 * camera.width() as f64 / SCREEN_WIDTH as f64) as i32;
+            let y = (y as f64 * camera.height() as f64 / SCREEN_HEIGHT as f64) as i32;
+            if (0..camera.width()).contains(&x) && (0..camera.height()).contains(&y) {
+                let pixel = camera.inspect_pixel(&world, x, y);
+                print!("{}", pixel);
+                window.set_title(&inspection_title(&pixel));
+                inspecting = true;
+            }
+        }
+
+        // Pull the new pixels from the worker
+        if render.update() {
+            if !saved {
+                // Update the window
+                window.set_title(&format!(
+                    "RTracer - ESC to exit -- Finished, {}",
+                    render.progress()
+                ));
+
+                // Save the buffer to a canvas
+                result = save_image(render.image.image(), None, output, &save);
+                match &result {
+                    Err(error) => eprintln!("error: {}", error),
+                    Ok(()) if moved => println!("Saved `{}` {}", output, describe(&view)),
+                    Ok(()) => {}
+                }
+                saved = true;
+            }
+        } else if !inspecting && title_changed.elapsed() >= TITLE_INTERVAL {
+            window.set_title(&progress_title(&render));
+            title_changed = Instant::now();
+        }
+
+        // Resample the buffer in the location which has now changed
+        resample_buffer(&mut buffer, render.image.image(), &save.transform);
+
+        window
+            .update_with_buffer(&buffer, SCREEN_WIDTH as usize, SCREEN_HEIGHT as usize)
+            .expect("Failed to update the window with buffer");
+    }
+
+    let passes_done = render.passes_done();
+    if progressive && !saved && passes_done > 0 {
+        println!("Stopped after {} of {} passes", passes_done, render.passes);
+        result = save_image(render.image.image(), None, output, &save);
+    }
+
+    render.worker.finish();
+    result
+}
+
+/// The title bar while a view is rendering, e.g. `45% 1.2M rays/s 0:12 elapsed, 0:15 left`
+fn progress_title(render: &Render) -> String {
+    let passes = if render.passes > 1 {
+        format!("{} of {} passes, ", render.passes_done(), render.passes)
+    } else {
+        String::new()
+    };
+    format!("RTracer - ESC to exit -- {}{}", passes, render.progress())
+}
+
+/// A one line summary of a pixel for the title bar
+fn inspection_title(pixel: &PixelInspection) -> String {
+    let c = pixel.color;
+    let color = format!("[{:.3}, {:.3}, {:.3}]", c.r(), c.g(), c.b());
+    match &pixel.hit {
+        Some(hit) => {
+            let blocked = hit.lights.iter().filter(|(_, blocked)| *blocked).count();
+            format!(
+                "RTracer - ({}, {}) {} {} {} t = {:.3}{}, {} of {} lights blocked",
+                pixel.x,
+                pixel.y,
+                color,
+                hit.shape_name(),
+                hit.object_id,
+                hit.t,
+                if hit.inside { " inside" } else { "" },
+                blocked,
+                hit.lights.len()
+            )
+        }
+        None => format!("RTracer - ({}, {}) {} nothing hit", pixel.x, pixel.y, color),
+    }
+}
+
+/// Where the view is, in the form used by scene files
+fn describe(view: &Viewpoint) -> String {
+    let triple = |x: f64, y: f64, z: f64| format!("[{:.3}, {:.3}, {:.3}]", x, y, z);
+    let point = |p: Point| triple(p.x(), p.y(), p.z());
+    let (from, to, up) = (point(view.from), point(view.to), view.up);
+    format!(
+        "from: {} to: {} up: {}",
+        from,
+        to,
+        triple(up.x(), up.y(), up.z())
+    )
+}
@@ -0,0 +1,162 @@
+use crate::{
+    image::{Canvas, Color},
+    maths::Vector,
+};
+use std::str::FromStr;
+
+/// Arbitrary output variables. Extra images rendered alongside the final one for compositing & debugging.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Aov {
+    /// Distance from the camera to the first surface
+    Depth,
+    /// The world space normal of the first surface, facing the camera
+    Normal,
+    /// The surface's color before any lighting
+    Albedo,
+    /// The fraction of the point lights which reach the surface
+    Shadow,
+    /// Which object was hit
+    ObjectId,
+    /// Which material was hit
+    MaterialId,
+    /// 1 where there's a surface & 0 where the background shows through
+    Alpha,
+}
+
+impl Aov {
+    pub const ALL: [Aov; 7] = [
+        Aov::Depth,
+        Aov::Normal,
+        Aov::Albedo,
+        Aov::Shadow,
+        Aov::ObjectId,
+        Aov::MaterialId,
+        Aov::Alpha,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Aov::Depth => "depth",
+            Aov::Normal => "normal",
+            Aov::Albedo => "albedo",
+            Aov::Shadow => "shadow",
+            Aov::ObjectId => "object-id",
+            Aov::MaterialId => "material-id",
+            Aov::Alpha => "alpha",
+        }
+    }
+
+    /*
+        Turn the raw values in a pass into something which can be looked at. Depth is
+        scaled so the farthest surface is white, normals are moved into 0 to 1 & each ID
+        gets its own color.
+    */
+    pub fn display(&self, pass: &Canvas) -> Canvas {
+        let pixels = (0..pass.height()).flat_map(|y| (0..pass.width()).map(move |x| (x, y)));
+        let farthest = pixels
+            .clone()
+            .map(|(x, y)| pass.pixel_at(x, y).r())
+            .filter(|depth| depth.is_finite())
+            .fold(0.0, f64::max);
+
+        let mut image = Canvas::new(pass.width(), pass.height());
+        for (x, y) in pixels {
+            let value = pass.pixel_at(x, y);
+            let color = match self {
+                Aov::Depth if value.r().is_infinite() => Color::black(),
+                Aov::Depth if farthest > 0.0 => value * (1.0 / farthest),
+                Aov::Normal => (value + Color::white()) * 0.5,
+                Aov::ObjectId | Aov::MaterialId => id_color(value.r() as usize),
+                _ => value,
+            };
+            image.write_pixel(x, y, color);
+        }
+        image
+    }
+}
+
+impl FromStr for Aov {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Aov::ALL
+            .iter()
+            .find(|aov| aov.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Aov::ALL.iter().map(|aov| aov.name()).collect();
+                format!(
+                    "unknown AOV `{}`. Expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// What a camera ray hits first, used to fill in the AOVs for a pixel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SurfaceInfo {
+    pub depth: f64,
+    pub normal: Vector,
+    pub albedo: Color,
+    pub shadow: f64,
+    /// The index of the object in the world
+    pub object_id: Option<usize>,
+    /// The index of the first object in the world with the same material
+    pub material_id: Option<usize>,
+}
+
+impl SurfaceInfo {
+    /// The values for a ray which doesn't hit anything
+    pub fn miss() -> Self {
+        Self {
+            depth: f64::INFINITY,
+            normal: Vector::new(0.0, 0.0, 0.0),
+            albedo: Color::black(),
+            shadow: 1.0,
+            object_id: None,
+            material_id: None,
+        }
+    }
+
+    /// The raw value of an AOV. IDs count up from 1 so 0 means nothing was hit.
+    pub fn value(&self, aov: Aov) -> Color {
+        let id = |id: Option<usize>| id.map(|id| id + 1).unwrap_or(0) as f64;
+        match aov {
+            Aov::Depth => Color::new(self.depth, self.depth, self.depth),
+            Aov::Normal => Color::new(self.normal.x(), self.normal.y(), self.normal.z()),
+            Aov::Albedo => self.albedo,
+            Aov::Shadow => Color::new(self.shadow, self.shadow, self.shadow),
+            Aov::ObjectId => {
+                let id = id(self.object_id);
+                Color::new(id, id, id)
+            }
+            Aov::MaterialId => {
+                let id = id(self.material_id);
+                Color::new(id, id, id)
+            }
+            Aov::Alpha if self.object_id.is_some() => Color::white(),
+            Aov::Alpha => Color::black(),
+        }
+    }
+}
+
+/// A distinct, stable color for each ID. 0 is black.
+fn id_color(id: usize) -> Color {
+    if id == 0 {
+        return Color::black();
+    }
+
+    // Spread neighbouring IDs around the hue circle using the golden ratio
+    let hue = (id as f64 * 0.618_033_988_75).fract() * 6.0;
+    let x = 1.0 - (hue % 2.0 - 1.0).abs();
+    match hue as u32 {
+        0 => Color::new(1.0, x, 0.0),
+        1 => Color::new(x, 1.0, 0.0),
+        2 => Color::new(0.0, 1.0, x),
+        3 => Color::new(0.0, x, 1.0),
+        4 => Color::new(x, 0.0, 1.0),
+        _ => Color::new(1.0, 0.0, x),
+    }
+}
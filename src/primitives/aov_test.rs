@@ -0,0 +1,148 @@
+use super::{Aov, Camera, Material, PointLight, Ray, Shape, ShapeIds, SurfaceInfo, World};
+use crate::{
+    image::{Canvas, Color},
+    maths::{Matrix4x4, Point, Vector},
+};
+
+#[test]
+fn aovs_can_be_named() {
+    for aov in Aov::ALL {
+        assert_eq!(aov.name().parse::<Aov>(), Ok(aov));
+    }
+    assert_eq!("object-id".parse::<Aov>(), Ok(Aov::ObjectId));
+    assert!("beauty".parse::<Aov>().is_err());
+}
+
+#[test]
+fn the_surface_info_of_the_first_hit() {
+    let w = World::default().generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    let info = w.surface_info_at(ray);
+
+    assert_eq!(info.depth, 4.0);
+    assert_eq!(info.normal, Vector::new(0.0, 0.0, -1.0));
+    assert_eq!(info.albedo, Color::new(0.8, 1.0, 0.6));
+    assert_eq!(info.shadow, 1.0);
+    assert_eq!(info.object_id, Some(0));
+    assert_eq!(info.material_id, Some(0));
+    assert_eq!(info.value(Aov::Alpha), Color::white());
+}
+
+#[test]
+fn a_ray_which_misses_has_no_surface() {
+    let w = World::default().generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+    let info = w.surface_info_at(ray);
+
+    assert_eq!(info, SurfaceInfo::miss());
+    assert_eq!(info.value(Aov::ObjectId), Color::black());
+    assert_eq!(info.value(Aov::Alpha), Color::black());
+    assert!(info.value(Aov::Depth).r().is_infinite());
+}
+
+#[test]
+fn objects_sharing_a_material_share_a_material_id() {
+    let mut red = Material::default();
+    red.color = Color::red();
+    let w = World::new()
+        .add_light(PointLight::new(
+            Point::new(0.0, 10.0, -10.0),
+            Color::white(),
+        ))
+        .add_object(Shape::sphere(
+            Matrix4x4::translation(-2.0, 0.0, 0.0),
+            red.clone(),
+        ))
+        .add_object(Shape::sphere(Matrix4x4::identity(), Material::default()))
+        .add_object(Shape::sphere(Matrix4x4::translation(2.0, 0.0, 0.0), red))
+        .generate();
+
+    let info = w.surface_info_at(Ray::new(
+        Point::new(2.0, 0.0, -5.0),
+        Vector::new(0.0, 0.0, 1.0),
+    ));
+
+    assert_eq!(info.object_id, Some(2));
+    assert_eq!(info.material_id, Some(0));
+    assert_eq!(info.value(Aov::ObjectId), Color::new(3.0, 3.0, 3.0));
+}
+
+#[test]
+fn identical_objects_get_their_own_ids() {
+    let sphere = Shape::sphere(Matrix4x4::identity(), Material::default());
+    let w = World::new()
+        .add_object(sphere.clone())
+        .add_object(sphere.clone())
+        .generate();
+
+    assert_eq!(sphere.ids(), None);
+    assert_eq!(
+        w.objects()[1].ids(),
+        Some(ShapeIds {
+            object: 1,
+            material: 0
+        })
+    );
+}
+
+#[test]
+fn the_shadow_mask_counts_the_lights_which_reach_a_point() {
+    let floor = Shape::plane(Matrix4x4::identity(), Material::default());
+    let blocker = Shape::sphere(Matrix4x4::translation(0.0, 5.0, 0.0), Material::default());
+    let w = World::new()
+        .add_light(PointLight::new(Point::new(0.0, 10.0, 0.0), Color::white()))
+        .add_light(PointLight::new(Point::new(10.0, 10.0, 0.0), Color::white()))
+        .add_object(floor)
+        .add_object(blocker)
+        .generate();
+
+    let info = w.surface_info_at(Ray::new(
+        Point::new(0.0, 1.0, -1.0),
+        Vector::new(0.0, -1.0, 1.0),
+    ));
+
+    assert_eq!(info.shadow, 0.5);
+}
+
+#[test]
+fn rendering_passes_alongside_the_image() {
+    let w = World::default().generate();
+    let c = Camera::new(
+        11,
+        11,
+        std::f64::consts::FRAC_PI_2,
+        Matrix4x4::view(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::up(),
+        ),
+    );
+
+    let (image, passes) = c.render_passes(&w, &[Aov::Depth, Aov::ObjectId]);
+
+    assert_eq!(image.pixel_at(5, 5), c.render(&w).pixel_at(5, 5));
+    assert_eq!(passes.len(), 2);
+    assert_eq!(passes[0].pixel_at(5, 5).r(), 4.0);
+    assert_eq!(passes[1].pixel_at(5, 5), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(passes[1].pixel_at(0, 0), Color::black());
+}
+
+#[test]
+fn displaying_a_depth_pass_scales_it_by_the_farthest_surface() {
+    let mut pass = Canvas::new(3, 1);
+    pass.write_pixel(0, 0, Color::new(2.0, 2.0, 2.0));
+    pass.write_pixel(1, 0, Color::new(4.0, 4.0, 4.0));
+    pass.write_pixel(
+        2,
+        0,
+        Color::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+    );
+
+    let image = Aov::Depth.display(&pass);
+
+    assert_eq!(image.pixel_at(0, 0), Color::new(0.5, 0.5, 0.5));
+    assert_eq!(image.pixel_at(1, 0), Color::white());
+    assert_eq!(image.pixel_at(2, 0), Color::black());
+}
@@ -0,0 +1,56 @@
+use super::{EnvironmentMap, PreethamSky};
+use crate::{image::Color, maths::Vector};
+use std::sync::Arc;
+
+/// What a ray sees when it doesn't hit anything
+#[derive(Clone, Debug)]
+pub enum Background {
+    Solid(Color),
+    /// Blends from the horizon color straight ahead to the zenith color straight up.
+    /// Everything below the horizon uses the ground color.
+    Gradient {
+        zenith: Color,
+        horizon: Color,
+        ground: Color,
+    },
+    Environment(Arc<EnvironmentMap>),
+    Sky(PreethamSky),
+}
+
+impl Background {
+    pub fn black() -> Self {
+        Background::Solid(Color::black())
+    }
+
+    pub fn environment(map: EnvironmentMap) -> Self {
+        Background::Environment(Arc::new(map))
+    }
+
+    pub fn color_in_direction(&self, direction: Vector) -> Color {
+        match self {
+            Background::Solid(color) => *color,
+            Background::Gradient {
+                zenith,
+                horizon,
+                ground,
+            } => {
+                let height = direction.normalize().y();
+                if height < 0.0 {
+                    *ground
+                } else {
+                    *horizon * (1.0 - height) + *zenith * height
+                }
+            }
+            Background::Environment(map) => map.color_in_direction(direction),
+            Background::Sky(sky) => sky.color_in_direction(direction),
+        }
+    }
+
+    /// The environment map which can be importance sampled as a light, if there is one
+    pub fn environment_map(&self) -> Option<&EnvironmentMap> {
+        match self {
+            Background::Environment(map) => Some(map),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use super::{sampling::sample_rng, Background, EnvironmentMap};
+use crate::{
+    image::{Canvas, Color},
+    maths::Vector,
+};
+use rand::Rng;
+use std::f64::consts::PI;
+
+fn uniform_sphere_direction<R: Rng>(rng: &mut R) -> Vector {
+    let z = 1.0 - 2.0 * rng.gen::<f64>();
+    let radius = (1.0 - z * z).sqrt();
+    let phi = 2.0 * PI * rng.gen::<f64>();
+    Vector::new(radius * phi.cos(), radius * phi.sin(), z)
+}
+
+fn striped_map() -> EnvironmentMap {
+    // Top row is red & bottom row is blue with a bright pixel looking along -X
+    let mut canvas = Canvas::new(4, 2);
+    for x in 0..4 {
+        canvas.write_pixel(x, 0, Color::red());
+        canvas.write_pixel(x, 1, Color::blue());
+    }
+    canvas.write_pixel(1, 1, Color::new(50.0, 50.0, 50.0));
+    EnvironmentMap::new(canvas, 1.0)
+}
+
+#[test]
+fn a_solid_background_is_the_same_in_every_direction() {
+    let background = Background::Solid(Color::green());
+    assert_eq!(
+        background.color_in_direction(Vector::new(0.0, 1.0, 0.0)),
+        Color::green()
+    );
+    assert_eq!(
+        background.color_in_direction(Vector::new(1.0, -1.0, 0.0)),
+        Color::green()
+    );
+}
+
+#[test]
+fn a_gradient_background_blends_from_the_horizon_to_the_zenith() {
+    let background = Background::Gradient {
+        zenith: Color::blue(),
+        horizon: Color::white(),
+        ground: Color::black(),
+    };
+
+    assert_eq!(
+        background.color_in_direction(Vector::new(0.0, 1.0, 0.0)),
+        Color::blue()
+    );
+    assert_eq!(
+        background.color_in_direction(Vector::new(0.0, 0.0, 1.0)),
+        Color::white()
+    );
+    assert_eq!(
+        background.color_in_direction(Vector::new(0.0, 1.0, 1.0)),
+        Color::new(1.0 - 2f64.sqrt() / 2.0, 1.0 - 2f64.sqrt() / 2.0, 1.0)
+    );
+    assert_eq!(
+        background.color_in_direction(Vector::new(0.0, -0.1, 1.0)),
+        Color::black()
+    );
+}
+
+#[test]
+fn an_environment_map_is_looked_up_by_direction() {
+    let background = Background::environment(striped_map());
+
+    assert_eq!(
+        background.color_in_direction(Vector::new(0.0, 1.0, 0.0)),
+        Color::red()
+    );
+    assert_eq!(
+        background.color_in_direction(Vector::new(0.0, -1.0, 0.0)),
+        Color::blue()
+    );
+    // Straight ahead is the middle of the image
+    assert_eq!(
+        background.color_in_direction(Vector::new(0.0, -0.1, 1.0)),
+        Color::blue()
+    );
+    assert_eq!(
+        background.color_in_direction(Vector::new(-1.0, -0.1, 0.0)),
+        Color::new(50.0, 50.0, 50.0)
+    );
+}
+
+#[test]
+fn only_environment_maps_can_be_sampled() {
+    assert!(Background::black().environment_map().is_none());
+    assert!(Background::environment(striped_map())
+        .environment_map()
+        .is_some());
+}
+
+#[test]
+fn the_environment_pdf_integrates_to_one() {
+    let map = striped_map();
+    let (rows, columns) = (200, 400);
+    let (d_theta, d_phi) = (PI / rows as f64, 2.0 * PI / columns as f64);
+
+    // Integrate over a grid of latitudes & longitudes
+    let mut total = 0.0;
+    for row in 0..rows {
+        let theta = (row as f64 + 0.5) * d_theta;
+        for column in 0..columns {
+            let phi = (column as f64 + 0.5) * d_phi;
+            let direction = Vector::new(
+                theta.sin() * phi.sin(),
+                theta.cos(),
+                theta.sin() * phi.cos(),
+            );
+            total += map.pdf(direction) * theta.sin() * d_theta * d_phi;
+        }
+    }
+
+    assert!((total - 1.0).abs() < 0.01, "{}", total);
+}
+
+#[test]
+fn sampling_the_environment_prefers_bright_areas() {
+    let map = striped_map();
+    let mut rng = sample_rng(4, 0, 0, 0);
+
+    let bright = (0..1000)
+        .map(|_| map.sample(rng.gen(), rng.gen()))
+        .filter(|(_, color, _)| color.r() > 1.0)
+        .count();
+
+    assert!(bright > 900);
+}
+
+#[test]
+fn sampling_the_environment_agrees_with_uniform_sampling() {
+    // Both estimate the total light arriving from the environment
+    let map = striped_map();
+    let mut rng = sample_rng(5, 0, 0, 0);
+    let samples = 50000;
+
+    let importance: f64 = (0..samples)
+        .map(|_| {
+            let (_, color, pdf) = map.sample(rng.gen(), rng.gen());
+            color.g() / pdf
+        })
+        .sum::<f64>()
+        / samples as f64;
+
+    let uniform: f64 = (0..samples)
+        .map(|_| {
+            map.color_in_direction(uniform_sphere_direction(&mut rng))
+                .g()
+                * 4.0
+                * PI
+        })
+        .sum::<f64>()
+        / samples as f64;
+
+    assert!((importance - uniform).abs() / uniform < 0.03);
+}
+
+#[test]
+fn an_empty_environment_map_is_black() {
+    let map = EnvironmentMap::new(Canvas::new(0, 0), 1.0);
+
+    assert_eq!(
+        map.color_in_direction(Vector::new(0.0, 1.0, 0.0)),
+        Color::black()
+    );
+    let (_, color, pdf) = map.sample(0.5, 0.5);
+    assert_eq!(color, Color::black());
+    assert!(pdf > 0.0);
+}
@@ -0,0 +1,95 @@
+use super::Ray;
+use crate::maths::{Matrix4x4, Point};
+
+/// An axis aligned box which contains a shape
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bounds {
+    min: Point,
+    max: Point,
+}
+
+impl Bounds {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    /// Bounds for shapes which go on forever, such as planes
+    pub fn infinite() -> Self {
+        Self::new(
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        )
+    }
+
+    pub fn min(&self) -> Point {
+        self.min
+    }
+
+    pub fn max(&self) -> Point {
+        self.max
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .any(|p| [p.x(), p.y(), p.z()].iter().any(|v| v.is_infinite()))
+    }
+
+    /// The bounds of the box after it has been transformed. Boxes with any infinite side stay infinite.
+    pub fn transform(&self, m: Matrix4x4) -> Bounds {
+        if self.is_infinite() {
+            return Bounds::infinite();
+        }
+
+        let corners = [
+            Point::new(self.min.x(), self.min.y(), self.min.z()),
+            Point::new(self.min.x(), self.min.y(), self.max.z()),
+            Point::new(self.min.x(), self.max.y(), self.min.z()),
+            Point::new(self.min.x(), self.max.y(), self.max.z()),
+            Point::new(self.max.x(), self.min.y(), self.min.z()),
+            Point::new(self.max.x(), self.min.y(), self.max.z()),
+            Point::new(self.max.x(), self.max.y(), self.min.z()),
+            Point::new(self.max.x(), self.max.y(), self.max.z()),
+        ];
+
+        let points: Vec<Point> = corners.iter().map(|&corner| m * corner).collect();
+        let smallest =
+            |axis: fn(&Point) -> f64| points.iter().map(axis).fold(f64::INFINITY, f64::min);
+        let largest =
+            |axis: fn(&Point) -> f64| points.iter().map(axis).fold(f64::NEG_INFINITY, f64::max);
+
+        let min = Point::new(smallest(Point::x), smallest(Point::y), smallest(Point::z));
+        let max = Point::new(largest(Point::x), largest(Point::y), largest(Point::z));
+        Bounds::new(min, max)
+    }
+
+    /// Whether the line along the ray passes through the box, in either direction
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        let origin = ray.origin();
+        let direction = ray.direction();
+        let axes = [
+            (origin.x(), direction.x(), self.min.x(), self.max.x()),
+            (origin.y(), direction.y(), self.min.y(), self.max.y()),
+            (origin.z(), direction.z(), self.min.z(), self.max.z()),
+        ];
+
+        let mut tmin = f64::NEG_INFINITY;
+        let mut tmax = f64::INFINITY;
+        for (origin, direction, min, max) in axes {
+            if direction.abs() < f64::EPSILON {
+                // Parallel to this pair of sides so it must start between them
+                if origin < min || origin > max {
+                    return false;
+                }
+                continue;
+            }
+
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
+            tmin = tmin.max(t1.min(t2));
+            tmax = tmax.min(t1.max(t2));
+        }
+
+        tmin <= tmax
+    }
+}
@@ -0,0 +1,40 @@
+use super::{Bounds, Ray, Shape};
+use crate::maths::{Matrix4x4, Point, Vector};
+
+#[test]
+fn a_ray_through_a_box_intersects_it() {
+    let b = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+
+    let through = Ray::new(Point::new(0.5, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let behind = Ray::new(Point::new(0.5, 0.5, 5.0), Vector::new(0.0, 0.0, 1.0));
+    let beside = Ray::new(Point::new(2.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let diagonal = Ray::new(Point::new(-5.0, -5.0, -5.0), Vector::new(1.0, 1.0, 1.0));
+
+    assert!(b.intersects(&through));
+    // The whole line is tested so boxes behind the ray still count
+    assert!(b.intersects(&behind));
+    assert!(!b.intersects(&beside));
+    assert!(b.intersects(&diagonal));
+}
+
+#[test]
+fn transforming_a_box_fits_a_new_box_around_it() {
+    let b = Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+    let moved = b.transform(Matrix4x4::scaling(2.0, 1.0, 1.0).translate(5.0, 0.0, 0.0));
+
+    assert_eq!(moved.min(), Point::new(3.0, -1.0, -1.0));
+    assert_eq!(moved.max(), Point::new(7.0, 1.0, 1.0));
+
+    let rotated = b.transform(Matrix4x4::rotation_y(std::f64::consts::FRAC_PI_4));
+    let half_diagonal = 2f64.sqrt();
+    assert!((rotated.max().x() - half_diagonal).abs() < 1e-9);
+    assert!((rotated.min().z() + half_diagonal).abs() < 1e-9);
+}
+
+#[test]
+fn infinite_shapes_have_infinite_bounds() {
+    let plane = Shape::plane(Matrix4x4::translation(0.0, 2.0, 0.0), Default::default());
+
+    assert!(plane.bounds().is_infinite());
+    assert!(Bounds::infinite().is_infinite());
+}
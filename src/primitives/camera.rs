@@ -1,9 +1,16 @@
-use super::{world::WorldImpl, Ray};
-use crate::image::{Canvas, Color};
+use super::{
+    sampling::sample_rng, world::WorldImpl, Aov, Integrator, PixelInspection, Progress,
+    ProgressTracker, Ray, SurfaceInfo, Tile, TileScheduler,
+};
+use crate::image::{Accumulator, Canvas, Color};
 use crate::maths::{Matrix4x4, Point};
 
+use rand::Rng;
 use rayon::prelude::*;
 
+/// The size of the square tiles images are split into when rendering
+pub const TILE_SIZE: i32 = 32;
+
 pub struct Camera {
     width: i32,
     height: i32,
@@ -30,6 +37,16 @@ impl Camera {
         }
     }
 
+    /// The same view rendered at a different resolution
+    pub fn with_size(&self, width: i32, height: i32) -> Self {
+        Self::new(width, height, self.fov, self.transform)
+    }
+
+    /// The same camera looking from somewhere else
+    pub fn with_transform(&self, transform: Matrix4x4) -> Self {
+        Self::new(self.width, self.height, self.fov, transform)
+    }
+
     pub fn width(&self) -> i32 {
         self.width
     }
@@ -71,9 +88,14 @@ impl Camera {
     }
 
     pub fn ray_for_pixel(&self, x: i32, y: i32) -> Ray {
-        // The offset from the edge of the canvas to the pixels center
-        let xoffset = (x as f64 + 0.5) * self.pixel_size;
-        let yoffset = (y as f64 + 0.5) * self.pixel_size;
+        self.ray_for_pixel_offset(x, y, 0.5, 0.5)
+    }
+
+    /// Create a ray through a point within a pixel. An offset of (0.5, 0.5) is the pixel's center
+    pub fn ray_for_pixel_offset(&self, x: i32, y: i32, offset_x: f64, offset_y: f64) -> Ray {
+        // The offset from the edge of the canvas to the point in the pixel
+        let xoffset = (x as f64 + offset_x) * self.pixel_size;
+        let yoffset = (y as f64 + offset_y) * self.pixel_size;
 
         // The untransformed coordinates of the pixel in world space.
         // (remember that the camera looks towards -z. so +x is to the *left*).
@@ -90,26 +112,246 @@ impl Camera {
         Ray::new(origin, direction)
     }
 
-    pub fn render(&self, world: WorldImpl) -> Canvas {
-        let mut image = Canvas::new(self.width, self.height);
+    /// Find the final color of a pixel, averaging as many samples as the integrator asks for
+    pub fn pixel_color(&self, world: &WorldImpl, x: i32, y: i32) -> Color {
+        match world.integrator() {
+            Integrator::Whitted => world.color_at(self.ray_for_pixel(x, y)),
+            Integrator::PathTracer { samples, .. } => {
+                let total = (0..samples)
+                    .map(|sample| self.pixel_sample(world, x, y, sample))
+                    .fold(Color::black(), |total, col| total + col);
+                total * (1.0 / samples.max(1) as f64)
+            }
+        }
+    }
+
+    /*
+        Find a single sample of a pixel's color. Averaging samples 0 to n - 1 gives the
+        same color as `pixel_color` with n samples, so an image can be built up a pass at
+        a time. Every sample is the same with the Whitted integrator.
+    */
+    pub fn pixel_sample(&self, world: &WorldImpl, x: i32, y: i32, sample: u32) -> Color {
+        match world.integrator() {
+            Integrator::Whitted => world.color_at(self.ray_for_pixel(x, y)),
+            Integrator::PathTracer { seed, .. } => {
+                let mut rng = sample_rng(seed, x, y, sample);
+                let ray = self.ray_for_pixel_offset(x, y, rng.gen(), rng.gen());
+                world.sample_color_at(ray, &mut rng)
+            }
+        }
+    }
+
+    /*
+        Find the final color of a pixel along with what its first sample hits for the AOVs.
+        The AOVs are found while the sample is traced rather than by tracing it again.
+    */
+    fn pixel_color_and_surface(&self, world: &WorldImpl, x: i32, y: i32) -> (Color, SurfaceInfo) {
+        match world.integrator() {
+            Integrator::Whitted => world.color_and_surface_at(self.ray_for_pixel(x, y)),
+            Integrator::PathTracer { seed, samples, .. } => {
+                let mut rng = sample_rng(seed, x, y, 0);
+                let ray = self.ray_for_pixel_offset(x, y, rng.gen(), rng.gen());
+                let (first, surface) = world.sample_color_and_surface_at(ray, &mut rng);
+
+                let total = (1..samples)
+                    .map(|sample| self.pixel_sample(world, x, y, sample))
+                    .fold(first, |total, col| total + col);
+                (total * (1.0 / samples.max(1) as f64), surface)
+            }
+        }
+    }
+
+    /// The ray the AOVs of a pixel are found along, which is the one its first sample follows
+    fn aov_ray(&self, world: &WorldImpl, x: i32, y: i32) -> Ray {
+        match world.integrator() {
+            Integrator::Whitted => self.ray_for_pixel(x, y),
+            Integrator::PathTracer { seed, .. } => {
+                let mut rng = sample_rng(seed, x, y, 0);
+                self.ray_for_pixel_offset(x, y, rng.gen(), rng.gen())
+            }
+        }
+    }
+
+    /// Find the color of a pixel & what the ray through its middle hits
+    pub fn inspect_pixel(&self, world: &WorldImpl, x: i32, y: i32) -> PixelInspection {
+        PixelInspection {
+            x,
+            y,
+            color: self.pixel_color(world, x, y),
+            hit: world.inspect(self.ray_for_pixel(x, y)),
+        }
+    }
 
-        let points: Vec<(i32, i32)> = (0..self.height)
-            .map(|row| (0..self.width).map(move |col| (row, col)))
-            .flatten()
+    /// Render the image a tile at a time, spreading the tiles over the thread pool
+    pub fn render(&self, world: &WorldImpl) -> Canvas {
+        self.render_with_progress(world, |_| {})
+    }
+
+    /*
+        Render the image, calling `on_progress` each time a tile is finished. It's called
+        from the rendering threads, so it should be quick. Sending the progress down a
+        channel lets another thread follow along.
+    */
+    pub fn render_with_progress<F>(&self, world: &WorldImpl, on_progress: F) -> Canvas
+    where
+        F: Fn(Progress) + Sync,
+    {
+        self.render_passes_with_progress(world, &[], on_progress).0
+    }
+
+    /// Render the pixels of one tile into a canvas the size of the tile
+    pub fn render_tile(&self, world: &WorldImpl, tile: Tile) -> Canvas {
+        self.render_tile_passes(world, tile, &[]).0
+    }
+
+    /// Render the image along with a pass for each of the AOVs, in the order they're given
+    pub fn render_passes(&self, world: &WorldImpl, aovs: &[Aov]) -> (Canvas, Vec<Canvas>) {
+        self.render_passes_with_progress(world, aovs, |_| {})
+    }
+
+    /// Render the image & AOVs, calling `on_progress` each time a tile is finished
+    pub fn render_passes_with_progress<F>(
+        &self,
+        world: &WorldImpl,
+        aovs: &[Aov],
+        on_progress: F,
+    ) -> (Canvas, Vec<Canvas>)
+    where
+        F: Fn(Progress) + Sync,
+    {
+        let samples = world.integrator().samples().max(1) as u64;
+        let pixel_count = (self.width * self.height) as u64;
+        let tracker = ProgressTracker::new(pixel_count * samples, world.rays_cast());
+
+        let tiles = TileScheduler::new(self.width, self.height, TILE_SIZE).tiles();
+        let rendered: Vec<(Tile, (Canvas, Vec<Canvas>))> = tiles
+            .into_par_iter()
+            .map(|tile| {
+                let pixels = self.render_tile_passes(world, tile, aovs);
+                tracker.add(tile.pixel_count() as u64 * samples);
+                on_progress(tracker.progress(world.rays_cast()));
+                (tile, pixels)
+            })
             .collect();
 
-        let colors: Vec<(i32, i32, Color)> = points
-            .par_iter()
-            .map(|&(row, col)| {
-                let ray = self.ray_for_pixel(col, row);
-                (row, col, world.color_at(ray))
+        let mut image = Canvas::new(self.width, self.height);
+        let mut passes = vec![Canvas::new(self.width, self.height); aovs.len()];
+        for (tile, (pixels, tile_passes)) in rendered {
+            for (x, y) in tile.pixels() {
+                let (tile_x, tile_y) = (x - tile.x, y - tile.y);
+                image.write_pixel(x, y, pixels.pixel_at(tile_x, tile_y));
+                for (pass, tile_pass) in passes.iter_mut().zip(&tile_passes) {
+                    pass.write_pixel(x, y, tile_pass.pixel_at(tile_x, tile_y));
+                }
+            }
+        }
+
+        (image, passes)
+    }
+
+    /// Render only the AOVs, without the image
+    pub fn render_aovs(&self, world: &WorldImpl, aovs: &[Aov]) -> Vec<Canvas> {
+        let tiles = TileScheduler::new(self.width, self.height, TILE_SIZE).tiles();
+        let rendered: Vec<(Tile, Vec<Color>)> = tiles
+            .into_par_iter()
+            .map(|tile| {
+                let values = tile
+                    .pixels()
+                    .flat_map(|(x, y)| {
+                        let info = world.surface_info_at(self.aov_ray(world, x, y));
+                        aovs.iter().map(move |&aov| info.value(aov))
+                    })
+                    .collect();
+                world.count_rays();
+                (tile, values)
             })
             .collect();
 
-        colors
+        let mut passes = vec![Canvas::new(self.width, self.height); aovs.len()];
+        for (tile, values) in rendered {
+            for ((x, y), values) in tile.pixels().zip(values.chunks(aovs.len().max(1))) {
+                for (pass, &value) in passes.iter_mut().zip(values) {
+                    pass.write_pixel(x, y, value);
+                }
+            }
+        }
+        passes
+    }
+
+    /*
+        Add a sample to every pixel a pass at a time until each one has as many as the
+        integrator asks for, carrying on from the samples the accumulator already has.
+        `after_pass` is given the accumulator after every pass so it can be saved. Samples
+        are the same however the passes are split up, so a render which is saved, loaded
+        & finished gives the same image as one which ran straight through.
+    */
+    pub fn accumulate<F, A>(
+        &self,
+        world: &WorldImpl,
+        accumulator: &mut Accumulator,
+        on_progress: F,
+        mut after_pass: A,
+    ) where
+        F: Fn(Progress) + Sync,
+        A: FnMut(&Accumulator),
+    {
+        let samples = world.integrator().samples().max(1);
+        let tiles = TileScheduler::new(self.width, self.height, TILE_SIZE).tiles();
+        let remaining = tiles
             .iter()
-            .for_each(|&(row, col, color)| image.write_pixel(col, row, color));
+            .flat_map(Tile::pixels)
+            .map(|(x, y)| samples.saturating_sub(accumulator.samples_at(x, y)) as u64)
+            .sum();
+        let tracker = ProgressTracker::new(remaining, world.rays_cast());
+
+        for sample in accumulator.min_samples()..samples {
+            // Only pixels which are behind get this sample, in case some have more than others
+            let behind = &*accumulator;
+            let rendered: Vec<Vec<(i32, i32, Color)>> = tiles
+                .par_iter()
+                .map(|tile| {
+                    let colors: Vec<(i32, i32, Color)> = tile
+                        .pixels()
+                        .filter(|&(x, y)| behind.samples_at(x, y) == sample)
+                        .map(|(x, y)| (x, y, self.pixel_sample(world, x, y, sample)))
+                        .collect();
+                    world.count_rays();
+                    tracker.add(colors.len() as u64);
+                    on_progress(tracker.progress(world.rays_cast()));
+                    colors
+                })
+                .collect();
 
-        image
+            for (x, y, color) in rendered.into_iter().flatten() {
+                accumulator.add_sample(x, y, color);
+            }
+            after_pass(accumulator);
+        }
+    }
+
+    /// Render a tile & its AOVs into canvases the size of the tile
+    fn render_tile_passes(
+        &self,
+        world: &WorldImpl,
+        tile: Tile,
+        aovs: &[Aov],
+    ) -> (Canvas, Vec<Canvas>) {
+        let mut pixels = Canvas::new(tile.width, tile.height);
+        let mut passes = vec![Canvas::new(tile.width, tile.height); aovs.len()];
+        for (x, y) in tile.pixels() {
+            let (tile_x, tile_y) = (x - tile.x, y - tile.y);
+            if aovs.is_empty() {
+                pixels.write_pixel(tile_x, tile_y, self.pixel_color(world, x, y));
+                continue;
+            }
+
+            let (color, info) = self.pixel_color_and_surface(world, x, y);
+            pixels.write_pixel(tile_x, tile_y, color);
+            for (pass, &aov) in passes.iter_mut().zip(aovs) {
+                pass.write_pixel(tile_x, tile_y, info.value(aov));
+            }
+        }
+        world.count_rays();
+        (pixels, passes)
     }
 }
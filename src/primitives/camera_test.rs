@@ -1,4 +1,5 @@
-use super::{Camera, World};
+use super::{Aov, Camera, Integrator, World};
+use crate::image::{Accumulator, Canvas};
 use crate::maths::{is_same, round, Matrix4x4, Point, Vector};
 use std::f64::consts::FRAC_PI_2;
 
@@ -67,10 +68,145 @@ fn rendering_a_world_with_a_camera() {
     );
     let c = Camera::new(11, 11, FRAC_PI_2, view_transform);
 
-    let image = c.render(w);
+    let image = c.render(&w);
     let color = image.pixel_at(5, 5);
 
     assert_eq!(round(color.r()), 0.38066);
     assert_eq!(round(color.g()), 0.47583);
     assert_eq!(round(color.b()), 0.28550);
 }
+
+#[test]
+fn path_traced_pixels_are_repeatable() {
+    let w = World::default()
+        .set_integrator(Integrator::path_tracer(4, 3))
+        .generate();
+    let view_transform = Matrix4x4::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::up(),
+    );
+    let c = Camera::new(11, 11, FRAC_PI_2, view_transform);
+
+    assert_eq!(c.pixel_color(&w, 5, 5), c.pixel_color(&w, 5, 5));
+}
+
+#[test]
+fn averaging_single_samples_gives_the_pixel_color() {
+    let w = World::default()
+        .set_integrator(Integrator::path_tracer(4, 3).with_seed(3))
+        .generate();
+    let view_transform = Matrix4x4::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::up(),
+    );
+    let c = Camera::new(11, 11, FRAC_PI_2, view_transform);
+
+    let total = (0..4)
+        .map(|sample| c.pixel_sample(&w, 5, 5, sample))
+        .fold(crate::image::Color::black(), |total, col| total + col);
+
+    assert_eq!(total * 0.25, c.pixel_color(&w, 5, 5));
+}
+
+/// The exact bits of every pixel, to check images are identical rather than just close
+fn bits(canvas: &Canvas) -> Vec<u64> {
+    (0..canvas.height())
+        .flat_map(|y| (0..canvas.width()).map(move |x| canvas.pixel_at(x, y)))
+        .flat_map(|c| [c.r().to_bits(), c.g().to_bits(), c.b().to_bits()])
+        .collect()
+}
+
+#[test]
+fn accumulating_passes_gives_the_rendered_image() {
+    let w = World::default()
+        .set_integrator(Integrator::path_tracer(3, 3).with_seed(5))
+        .generate();
+    let view_transform = Matrix4x4::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::up(),
+    );
+    let c = Camera::new(11, 11, FRAC_PI_2, view_transform);
+
+    let mut passes = 0;
+    let mut acc = Accumulator::new(11, 11);
+    c.accumulate(&w, &mut acc, |_| {}, |_| passes += 1);
+
+    assert_eq!(passes, 3);
+    assert_eq!(acc.min_samples(), 3);
+    assert_eq!(bits(acc.image()), bits(&c.render(&w)));
+}
+
+#[test]
+fn an_accumulation_can_be_stopped_and_carried_on() {
+    let w = World::default()
+        .set_integrator(Integrator::path_tracer(4, 3))
+        .generate();
+    let view_transform = Matrix4x4::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::up(),
+    );
+    let c = Camera::new(11, 11, FRAC_PI_2, view_transform);
+
+    // Stop after 2 of the 4 passes & save the accumulator, partway through the third
+    let fewer = World::default()
+        .set_integrator(Integrator::path_tracer(2, 3))
+        .generate();
+    let mut acc = Accumulator::new(11, 11);
+    c.accumulate(&fewer, &mut acc, |_| {}, |_| {});
+    acc.add_sample(3, 3, c.pixel_sample(&w, 3, 3, 2));
+    let mut saved = vec![];
+    acc.write_to(&mut saved).unwrap();
+
+    let mut resumed = Accumulator::read_from(&mut saved.as_slice()).unwrap();
+    c.accumulate(&w, &mut resumed, |_| {}, |_| {});
+
+    let mut uninterrupted = Accumulator::new(11, 11);
+    c.accumulate(&w, &mut uninterrupted, |_| {}, |_| {});
+    assert_eq!(bits(resumed.image()), bits(uninterrupted.image()));
+    assert_eq!(resumed.samples_at(3, 3), 4);
+}
+
+#[test]
+fn aovs_can_be_rendered_without_the_image() {
+    let w = World::default().generate();
+    let view_transform = Matrix4x4::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::up(),
+    );
+    let c = Camera::new(11, 11, FRAC_PI_2, view_transform);
+
+    let aovs = [Aov::Depth, Aov::Alpha];
+    let with_image = c.render_passes(&w, &aovs).1;
+    let without_image = c.render_aovs(&w, &aovs);
+    assert_eq!(without_image.len(), 2);
+    for (a, b) in without_image.iter().zip(&with_image) {
+        assert_eq!(bits(a), bits(b));
+    }
+}
+
+#[test]
+fn path_traced_aovs_are_found_along_the_first_sample() {
+    let w = World::default()
+        .set_integrator(Integrator::path_tracer(2, 3).with_seed(7))
+        .generate();
+    let view_transform = Matrix4x4::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::up(),
+    );
+    let c = Camera::new(11, 11, FRAC_PI_2, view_transform);
+
+    let aovs = [Aov::Depth, Aov::Normal, Aov::ObjectId];
+    let (image, with_image) = c.render_passes(&w, &aovs);
+    let without_image = c.render_aovs(&w, &aovs);
+
+    assert_eq!(bits(&image), bits(&c.render(&w)));
+    for (a, b) in without_image.iter().zip(&with_image) {
+        assert_eq!(bits(a), bits(b));
+    }
+}
@@ -0,0 +1,35 @@
+/// How the refractive index of a material changes with the wavelength of light
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Dispersion {
+    /// n = a + b / λ² with λ in micrometres
+    Cauchy { a: f64, b: f64 },
+    /// n² = 1 + Σ bᵢλ² / (λ² - cᵢ) with λ in micrometres
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// Schott BK7, a common crown glass
+    pub fn bk7() -> Self {
+        Dispersion::Sellmeier {
+            b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+            c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+        }
+    }
+
+    /// The refractive index for a wavelength in nanometres
+    pub fn refractive_index(&self, wavelength: f64) -> f64 {
+        let micrometres = wavelength / 1000.0;
+        let squared = micrometres * micrometres;
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / squared,
+            Dispersion::Sellmeier { b, c } => {
+                let sum: f64 = b
+                    .iter()
+                    .zip(c.iter())
+                    .map(|(b, c)| b * squared / (squared - c))
+                    .sum();
+                (1.0 + sum).sqrt()
+            }
+        }
+    }
+}
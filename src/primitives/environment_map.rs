@@ -0,0 +1,158 @@
+use crate::{
+    image::{load_hdr, Canvas, Color},
+    maths::Vector,
+};
+use std::f64::consts::PI;
+
+/// An equirectangular (latitude/longitude) image surrounding the scene
+#[derive(Clone, Debug)]
+pub struct EnvironmentMap {
+    image: Canvas,
+    intensity: f64,
+    // Cumulative distribution of choosing each row, then each pixel within a row
+    row_cdf: Vec<f64>,
+    column_cdfs: Vec<Vec<f64>>,
+    total_weight: f64,
+}
+
+fn build_cdf(weights: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut total = 0.0;
+    weights
+        .map(|weight| {
+            total += weight;
+            total
+        })
+        .collect()
+}
+
+/// Find the index of the bucket `u * total` falls into along with how far through the bucket it is
+fn sample_cdf(cdf: &[f64], u: f64) -> (usize, f64) {
+    let total = cdf[cdf.len() - 1];
+    let target = u * total;
+    let index = cdf
+        .partition_point(|&value| value <= target)
+        .min(cdf.len() - 1);
+    let start = if index == 0 { 0.0 } else { cdf[index - 1] };
+    let width = cdf[index] - start;
+    let fraction = if width > 0.0 {
+        (target - start) / width
+    } else {
+        0.5
+    };
+    (index, fraction.clamp(0.0, 1.0))
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Canvas, intensity: f64) -> Self {
+        // An empty image is a single black pixel so there's always something to look up & sample
+        let image = if image.width() > 0 && image.height() > 0 {
+            image
+        } else {
+            Canvas::new(1, 1)
+        };
+        let (width, height) = (image.width(), image.height());
+
+        /*
+            Weight each pixel by its brightness & the solid angle it covers. Rows near
+            the poles are squashed into a small area so are less likely to be picked.
+            A small floor on the weight means dark areas can still be sampled.
+        */
+        let weight = |x: i32, y: i32| {
+            let sin_theta = (PI * (y as f64 + 0.5) / height as f64).sin();
+            (image.pixel_at(x, y).luminance().max(0.0) + 1e-4) * sin_theta
+        };
+
+        let column_cdfs: Vec<Vec<f64>> = (0..height)
+            .map(|y| build_cdf((0..width).map(|x| weight(x, y))))
+            .collect();
+        let row_cdf = build_cdf(column_cdfs.iter().map(|cdf| cdf[cdf.len() - 1]));
+        let total_weight = row_cdf[row_cdf.len() - 1];
+
+        Self {
+            image,
+            intensity,
+            row_cdf,
+            column_cdfs,
+            total_weight,
+        }
+    }
+
+    /// Load an equirectangular Radiance .hdr image
+    pub fn from_hdr(path: String, intensity: f64) -> Result<Self, std::io::Error> {
+        Ok(Self::new(load_hdr(path)?, intensity))
+    }
+
+    pub fn image(&self) -> &Canvas {
+        &self.image
+    }
+
+    pub fn intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    /// Find the pixel a direction maps to. +Z is the center of the image and +Y is the top.
+    fn pixel_for_direction(&self, direction: Vector) -> (i32, i32) {
+        let direction = direction.normalize();
+        let u = 0.5 + direction.x().atan2(direction.z()) / (2.0 * PI);
+        let v = direction.y().clamp(-1.0, 1.0).acos() / PI;
+
+        let x = ((u * self.image.width() as f64) as i32).clamp(0, self.image.width() - 1);
+        let y = ((v * self.image.height() as f64) as i32).clamp(0, self.image.height() - 1);
+        (x, y)
+    }
+
+    fn direction_for_uv(u: f64, v: f64) -> Vector {
+        let phi = (u - 0.5) * 2.0 * PI;
+        let theta = v * PI;
+        Vector::new(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            theta.sin() * phi.cos(),
+        )
+    }
+
+    pub fn color_in_direction(&self, direction: Vector) -> Color {
+        let (x, y) = self.pixel_for_direction(direction);
+        self.image.pixel_at(x, y) * self.intensity
+    }
+
+    /// The probability density of `sample` picking a direction, per unit solid angle
+    pub fn pdf(&self, direction: Vector) -> f64 {
+        let direction = direction.normalize();
+        let (x, y) = self.pixel_for_direction(direction);
+        let (width, height) = (self.image.width(), self.image.height());
+
+        let column_cdf = &self.column_cdfs[y as usize];
+        let previous = if x == 0 {
+            0.0
+        } else {
+            column_cdf[x as usize - 1]
+        };
+        let pixel_weight = column_cdf[x as usize] - previous;
+
+        // Convert from the density over the image to the density over the sphere
+        let pdf_image = pixel_weight / self.total_weight * (width * height) as f64;
+        let sin_theta = (1.0 - direction.y() * direction.y()).max(1e-8).sqrt();
+        pdf_image / (2.0 * PI * PI * sin_theta)
+    }
+
+    /// Pick a direction with a probability proportional to how much light comes from it.
+    /// Returns the direction, the light arriving from it & the pdf.
+    pub fn sample(&self, u: f64, v: f64) -> (Vector, Color, f64) {
+        let (width, height) = (self.image.width(), self.image.height());
+
+        let (y, row_fraction) = sample_cdf(&self.row_cdf, u);
+        let (x, column_fraction) = sample_cdf(&self.column_cdfs[y], v);
+
+        let direction = Self::direction_for_uv(
+            (x as f64 + column_fraction) / width as f64,
+            (y as f64 + row_fraction) / height as f64,
+        );
+
+        (
+            direction,
+            self.color_in_direction(direction),
+            self.pdf(direction),
+        )
+    }
+}
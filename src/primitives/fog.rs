@@ -0,0 +1,20 @@
+use crate::image::Color;
+
+/// Exponential distance fog which fades everything towards a color
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fog {
+    pub color: Color,
+    pub density: f64,
+}
+
+impl Fog {
+    pub fn new(color: Color, density: f64) -> Self {
+        Self { color, density }
+    }
+
+    /// Blend a color seen at a distance with the fog. Rays which hit nothing are infinitely far away.
+    pub fn apply(&self, color: Color, distance: f64) -> Color {
+        let visible = (-self.density * distance).exp();
+        color * visible + self.color * (1.0 - visible)
+    }
+}
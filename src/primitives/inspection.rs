@@ -0,0 +1,154 @@
+use super::{Dispersion, Material, PointLight, Shape, ShapeType};
+use crate::image::Color;
+use crate::maths::{Point, Vector};
+use std::fmt;
+
+/// Everything about the first thing a ray hits, for working out why a pixel looks wrong
+#[derive(Clone, Debug)]
+pub struct Inspection {
+    pub object: Shape,
+    /// The index of the object in the world
+    pub object_id: usize,
+    pub t: f64,
+    pub point: Point,
+    pub normal: Vector,
+    /// Whether the ray started inside the object
+    pub inside: bool,
+    /// Each point light & whether it's blocked from the point
+    pub lights: Vec<(PointLight, bool)>,
+    /// Each emissive shape which lights the scene & whether the middle of it is blocked from the point
+    pub emitters: Vec<(Shape, bool)>,
+}
+
+impl Inspection {
+    pub fn material(&self) -> &Material {
+        self.object.material()
+    }
+
+    /// What kind of shape was hit
+    pub fn shape_name(&self) -> &'static str {
+        shape_name(&self.object)
+    }
+}
+
+fn shape_name(shape: &Shape) -> &'static str {
+    match shape.shape_type() {
+        ShapeType::Sphere => "sphere",
+        ShapeType::Plane => "plane",
+        ShapeType::Custom(_) => "custom shape",
+    }
+}
+
+/// What's behind one pixel of a render
+#[derive(Clone, Debug)]
+pub struct PixelInspection {
+    pub x: i32,
+    pub y: i32,
+    /// The final color of the pixel
+    pub color: Color,
+    /// What the camera ray through the middle of the pixel hits first
+    pub hit: Option<Inspection>,
+}
+
+fn point(p: Point) -> String {
+    format!("[{:.4}, {:.4}, {:.4}]", p.x(), p.y(), p.z())
+}
+
+fn vector(v: Vector) -> String {
+    format!("[{:.4}, {:.4}, {:.4}]", v.x(), v.y(), v.z())
+}
+
+fn color(c: Color) -> String {
+    format!("[{:.4}, {:.4}, {:.4}]", c.r(), c.g(), c.b())
+}
+
+fn dispersion(dispersion: Option<Dispersion>) -> String {
+    match dispersion {
+        None => "none".to_string(),
+        Some(Dispersion::Cauchy { a, b }) => format!("Cauchy a {}, b {}", a, b),
+        Some(Dispersion::Sellmeier { b, c }) => format!("Sellmeier b {:?}, c {:?}", b, c),
+    }
+}
+
+impl fmt::Display for PixelInspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Pixel ({}, {}) is {}", self.x, self.y, color(self.color))?;
+        let hit = match &self.hit {
+            Some(hit) => hit,
+            None => return writeln!(f, "  The ray doesn't hit anything"),
+        };
+
+        writeln!(
+            f,
+            "  Hits object {} ({}) at t = {:.4}{}",
+            hit.object_id,
+            hit.shape_name(),
+            hit.t,
+            if hit.inside { " from inside" } else { "" }
+        )?;
+        writeln!(f, "  Point {}", point(hit.point))?;
+        writeln!(f, "  Normal {}", vector(hit.normal))?;
+
+        let m = hit.material();
+        writeln!(
+            f,
+            "  Material color {}, ambient {}, diffuse {}, specular {}, shininess {}",
+            color(m.color),
+            m.ambient,
+            m.diffuse,
+            m.specular,
+            m.shininess
+        )?;
+        writeln!(
+            f,
+            "           reflective {}, transparency {}, refractive index {}, emission {}",
+            m.reflective,
+            m.transparency,
+            m.refractive_index,
+            color(m.emission)
+        )?;
+        writeln!(
+            f,
+            "           absorption {}, dispersion {}",
+            color(m.absorption),
+            dispersion(m.dispersion)
+        )?;
+        if let Some(microfacet) = m.microfacet {
+            writeln!(
+                f,
+                "           GGX metallic {}, roughness {}",
+                microfacet.metallic, microfacet.roughness
+            )?;
+        }
+        if let Some(medium) = m.medium {
+            writeln!(
+                f,
+                "           medium color {}, density {}, anisotropy {}",
+                color(medium.color),
+                medium.density,
+                medium.anisotropy
+            )?;
+        }
+
+        for (i, (light, shadowed)) in hit.lights.iter().enumerate() {
+            writeln!(
+                f,
+                "  Light {} at {} is {}",
+                i,
+                point(light.position()),
+                if *shadowed { "blocked" } else { "visible" }
+            )?;
+        }
+        for (emitter, shadowed) in &hit.emitters {
+            writeln!(
+                f,
+                "  Emitter {} ({}) giving off {} is {}",
+                emitter.ids().map(|ids| ids.object).unwrap_or_default(),
+                shape_name(emitter),
+                color(emitter.material().emission),
+                if *shadowed { "blocked" } else { "visible" }
+            )?;
+        }
+        Ok(())
+    }
+}
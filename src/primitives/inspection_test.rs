@@ -0,0 +1,154 @@
+use super::{Camera, Dispersion, Material, Medium, Microfacet, PointLight, Ray, Shape, World};
+use crate::{
+    image::Color,
+    maths::{Matrix4x4, Point, Vector},
+};
+use std::f64::consts::FRAC_PI_2;
+
+#[test]
+fn inspecting_the_first_hit_of_a_ray() {
+    let w = World::default().generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    let hit = w.inspect(ray).unwrap();
+
+    assert_eq!(hit.object_id, 0);
+    assert_eq!(hit.shape_name(), "sphere");
+    assert_eq!(hit.t, 4.0);
+    assert_eq!(hit.point, Point::new(0.0, 0.0, -1.0));
+    assert_eq!(hit.normal, Vector::new(0.0, 0.0, -1.0));
+    assert!(!hit.inside);
+    assert_eq!(hit.material().color, Color::new(0.8, 1.0, 0.6));
+    assert_eq!(hit.lights.len(), 1);
+    assert!(!hit.lights[0].1);
+}
+
+#[test]
+fn inspecting_from_inside_a_shape() {
+    let w = World::default().generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+
+    let hit = w.inspect(ray).unwrap();
+
+    // The inner sphere is hit first
+    assert_eq!(hit.object_id, 1);
+    assert_eq!(hit.t, 0.5);
+    assert!(hit.inside);
+    assert_eq!(hit.normal, Vector::new(0.0, 0.0, -1.0));
+}
+
+#[test]
+fn inspecting_a_ray_which_misses() {
+    let w = World::default().generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+    assert!(w.inspect(ray).is_none());
+}
+
+#[test]
+fn each_light_is_checked_for_shadows() {
+    let w = World::new()
+        .add_light(PointLight::new(Point::new(0.0, 10.0, 0.0), Color::white()))
+        .add_light(PointLight::new(Point::new(10.0, 10.0, 0.0), Color::white()))
+        .add_object(Shape::plane(Matrix4x4::identity(), Material::default()))
+        .add_object(Shape::sphere_from_transformation(Matrix4x4::translation(
+            0.0, 2.0, 0.0,
+        )))
+        .generate();
+    let ray = Ray::new(
+        Point::new(0.0, 5.0, -5.0),
+        Vector::new(0.0, -1.0, 1.0).normalize(),
+    );
+
+    let hit = w.inspect(ray).unwrap();
+
+    assert_eq!(hit.object_id, 0);
+    assert_eq!(hit.shape_name(), "plane");
+    // The sphere is between the point & the light above it
+    assert!(hit.lights[0].1);
+    assert!(!hit.lights[1].1);
+}
+
+#[test]
+fn inspecting_a_pixel_describes_it() {
+    let w = World::default().generate();
+    let c = Camera::new(
+        11,
+        11,
+        FRAC_PI_2,
+        Matrix4x4::view(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::up(),
+        ),
+    );
+
+    let pixel = c.inspect_pixel(&w, 5, 5);
+
+    assert_eq!(pixel.color, c.pixel_color(&w, 5, 5));
+    assert_eq!(pixel.hit.as_ref().unwrap().object_id, 0);
+
+    let text = pixel.to_string();
+    assert!(text.starts_with("Pixel (5, 5) is [0.3807, 0.4758, 0.2855]"));
+    assert!(text.contains("Hits object 0 (sphere) at t = 4.0000"));
+    assert!(text.contains("Light 0 at [-10.0000, 10.0000, -10.0000] is visible"));
+
+    let miss = c.inspect_pixel(&w, 0, 0);
+    assert!(miss.hit.is_none());
+    assert!(miss.to_string().contains("doesn't hit anything"));
+}
+
+#[test]
+fn inspecting_describes_the_physically_based_material_and_emitters() {
+    let floor = Material {
+        microfacet: Some(Microfacet::new(1.0, 0.25)),
+        absorption: Color::new(0.5, 0.0, 0.0),
+        dispersion: Some(Dispersion::Cauchy { a: 1.5, b: 0.004 }),
+        ..Material::default()
+    };
+    let lamp = Material {
+        emission: Color::new(4.0, 4.0, 4.0),
+        ..Material::default()
+    };
+    let smoke = Material {
+        medium: Some(Medium::new(Color::white(), 0.5)),
+        ..Material::default()
+    };
+
+    let w = World::new()
+        .add_object(Shape::plane(Matrix4x4::identity(), floor))
+        .add_object(Shape::sphere(Matrix4x4::translation(0.0, 5.0, 0.0), lamp))
+        .add_object(Shape::sphere(Matrix4x4::translation(10.0, 0.0, 0.0), smoke))
+        .generate();
+    let c = Camera::new(
+        11,
+        11,
+        FRAC_PI_2,
+        Matrix4x4::view(
+            Point::new(0.0, 1.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::up(),
+        ),
+    );
+
+    let pixel = c.inspect_pixel(&w, 5, 5);
+    let hit = pixel.hit.as_ref().unwrap();
+    assert_eq!(hit.emitters.len(), 1);
+    assert!(!hit.emitters[0].1);
+
+    let text = pixel.to_string();
+    assert!(text.contains("absorption [0.5000, 0.0000, 0.0000], dispersion Cauchy a 1.5, b 0.004"));
+    assert!(text.contains("GGX metallic 1, roughness 0.25"));
+    assert!(text.contains("Emitter 1 (sphere) giving off [4.0000, 4.0000, 4.0000] is visible"));
+
+    // The medium is described when a volume is hit
+    let text = c
+        .with_transform(Matrix4x4::view(
+            Point::new(10.0, 0.0, -5.0),
+            Point::new(10.0, 0.0, 0.0),
+            Vector::up(),
+        ))
+        .inspect_pixel(&w, 5, 5)
+        .to_string();
+    assert!(text.contains("medium color [1.0000, 1.0000, 1.0000], density 0.5, anisotropy 0"));
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Selects how the world turns a camera ray into a color
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Integrator {
+    /// Direct lighting from the point lights with an ambient term (the book's renderer)
+    Whitted,
+    /// Monte Carlo path tracing. Emissive shapes are sampled as area lights at every bounce.
+    PathTracer {
+        samples: u32,
+        max_depth: u32,
+        seed: u64,
+        /// Trace a single wavelength per sample so materials can disperse light
+        spectral: bool,
+    },
+}
+
+impl Integrator {
+    pub fn path_tracer(samples: u32, max_depth: u32) -> Self {
+        Integrator::PathTracer {
+            samples,
+            max_depth,
+            seed: 0,
+            spectral: false,
+        }
+    }
+
+    pub fn spectral_path_tracer(samples: u32, max_depth: u32) -> Self {
+        Integrator::PathTracer {
+            samples,
+            max_depth,
+            seed: 0,
+            spectral: true,
+        }
+    }
+
+    /// Change the seed of the random numbers used by the path tracers
+    pub fn with_seed(self, seed: u64) -> Self {
+        match self {
+            Integrator::Whitted => Integrator::Whitted,
+            Integrator::PathTracer {
+                samples,
+                max_depth,
+                spectral,
+                ..
+            } => Integrator::PathTracer {
+                samples,
+                max_depth,
+                seed,
+                spectral,
+            },
+        }
+    }
+
+    pub fn samples(&self) -> u32 {
+        match self {
+            Integrator::Whitted => 1,
+            Integrator::PathTracer { samples, .. } => *samples,
+        }
+    }
+
+    /// Which kind of integrator this is, without its settings
+    pub fn kind(&self) -> IntegratorKind {
+        match self {
+            Integrator::Whitted => IntegratorKind::Whitted,
+            Integrator::PathTracer {
+                spectral: false, ..
+            } => IntegratorKind::Path,
+            Integrator::PathTracer { spectral: true, .. } => IntegratorKind::Spectral,
+        }
+    }
+}
+
+/// The kinds of integrator, for choosing one by name
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntegratorKind {
+    /// Direct lighting from point lights (the book's renderer)
+    Whitted,
+    /// Monte Carlo path tracing
+    Path,
+    /// Path tracing one wavelength at a time so glass can disperse light
+    Spectral,
+}
+
+impl IntegratorKind {
+    pub const ALL: [IntegratorKind; 3] = [
+        IntegratorKind::Whitted,
+        IntegratorKind::Path,
+        IntegratorKind::Spectral,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            IntegratorKind::Whitted => "whitted",
+            IntegratorKind::Path => "path",
+            IntegratorKind::Spectral => "spectral",
+        }
+    }
+}
+
+impl FromStr for IntegratorKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        IntegratorKind::ALL
+            .iter()
+            .find(|kind| kind.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = IntegratorKind::ALL.iter().map(|kind| kind.name()).collect();
+                format!(
+                    "unknown integrator `{}`. Expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
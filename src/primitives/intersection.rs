@@ -2,7 +2,7 @@ use super::{IntersectionStats, Ray, Shape};
 use crate::maths::Vector;
 use std::cmp::{Ord, Ordering};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct Intersection {
     t: f64,
     shape: Shape,
@@ -20,8 +20,8 @@ impl Intersection {
         self.t
     }
 
-    pub fn object(&self) -> Shape {
-        self.shape
+    pub fn object(&self) -> &Shape {
+        &self.shape
     }
 
     pub fn hit(mut intersections: Vec<Intersection>) -> Option<Intersection> {
@@ -33,6 +33,23 @@ impl Intersection {
     }
 
     pub fn prepare_computations(&self, ray: Ray) -> IntersectionStats {
+        self.prepare_computations_in(ray, std::slice::from_ref(self))
+    }
+
+    /// Prepare the computations using all the intersections along the ray to find
+    /// which materials the ray is passing between
+    pub fn prepare_computations_in(&self, ray: Ray, xs: &[Intersection]) -> IntersectionStats {
+        self.prepare_computations_for_wavelength(ray, xs, None)
+    }
+
+    /// Prepare the computations for light of a single wavelength in nanometres
+    pub fn prepare_computations_for_wavelength(
+        &self,
+        ray: Ray,
+        xs: &[Intersection],
+        wavelength: Option<f64>,
+    ) -> IntersectionStats {
+        let (n1, n2) = self.refractive_indices(xs, wavelength);
         let point = ray.position(self.t);
         let eyev = -ray.direction();
         let mut normalv = self.object().normal_at(point);
@@ -47,7 +64,46 @@ impl Intersection {
 
         let over_point = point + normalv * f32::EPSILON as f64;
 
-        IntersectionStats::new(self.t, self.shape, point, eyev, normalv, inside, over_point)
+        IntersectionStats::new(
+            self.t,
+            self.shape.clone(),
+            point,
+            eyev,
+            normalv,
+            inside,
+            over_point,
+        )
+        .with_refractive_indices(n1, n2)
+    }
+
+    fn refractive_indices(&self, xs: &[Intersection], wavelength: Option<f64>) -> (f64, f64) {
+        let index_of = |containers: &Vec<Shape>| {
+            containers
+                .last()
+                .map(|shape| shape.material().refractive_index_at(wavelength))
+                .unwrap_or(1.0)
+        };
+
+        // Track which objects the ray is inside of up to this intersection
+        let mut containers: Vec<Shape> = vec![];
+        let mut n1 = 1.0;
+        for intersection in xs {
+            if intersection == self {
+                n1 = index_of(&containers);
+            }
+
+            match containers.iter().position(|s| s == intersection.object()) {
+                Some(position) => {
+                    containers.remove(position);
+                }
+                None => containers.push(intersection.object().clone()),
+            }
+
+            if intersection == self {
+                return (n1, index_of(&containers));
+            }
+        }
+        (n1, 1.0)
     }
 }
 
@@ -60,6 +116,8 @@ impl PartialEq for Intersection {
 
 impl Eq for Intersection {}
 
+// Comparing a NaN t gives None here rather than panicking like `cmp`
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for Intersection {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         self.t.partial_cmp(&other.t)
@@ -1,7 +1,7 @@
 use super::Shape;
 use crate::maths::{Point, Vector};
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct IntersectionStats {
     t: f64,
     object: Shape,
@@ -10,6 +10,9 @@ pub struct IntersectionStats {
     normalv: Vector,
     inside: bool,
     over_point: Point,
+    // Refractive indices of the materials on the eye's side & the far side of the surface
+    n1: f64,
+    n2: f64,
 }
 
 impl IntersectionStats {
@@ -30,14 +33,22 @@ impl IntersectionStats {
             normalv,
             inside,
             over_point,
+            n1: 1.0,
+            n2: 1.0,
         }
     }
 
+    pub fn with_refractive_indices(mut self, n1: f64, n2: f64) -> Self {
+        self.n1 = n1;
+        self.n2 = n2;
+        self
+    }
+
     pub fn t(&self) -> f64 {
         self.t
     }
-    pub fn object(&self) -> Shape {
-        self.object
+    pub fn object(&self) -> &Shape {
+        &self.object
     }
 
     pub fn point(&self) -> Point {
@@ -59,4 +70,52 @@ impl IntersectionStats {
     pub fn over_point(&self) -> Point {
         self.over_point
     }
+
+    /// A point just below the surface, for rays which carry on through it
+    pub fn under_point(&self) -> Point {
+        self.point - self.normalv * f32::EPSILON as f64
+    }
+
+    pub fn n1(&self) -> f64 {
+        self.n1
+    }
+
+    pub fn n2(&self) -> f64 {
+        self.n2
+    }
+
+    pub fn reflectv(&self) -> Vector {
+        (-self.eyev).reflect(self.normalv)
+    }
+
+    /// The direction light passing through the surface is bent into.
+    /// None when all the light is reflected.
+    pub fn refracted_direction(&self) -> Option<Vector> {
+        let n_ratio = self.n1 / self.n2;
+        let cos_i = Vector::dot(self.eyev, self.normalv);
+        let sin2_t = n_ratio * n_ratio * (1.0 - cos_i * cos_i);
+        if sin2_t > 1.0 {
+            return None;
+        }
+        let cos_t = (1.0 - sin2_t).sqrt();
+        Some(self.normalv * (n_ratio * cos_i - cos_t) - self.eyev * n_ratio)
+    }
+
+    /// Schlick's approximation of the fraction of light reflected by the surface
+    pub fn schlick(&self) -> f64 {
+        let mut cos = Vector::dot(self.eyev, self.normalv);
+
+        // Total internal reflection can only happen leaving the denser material
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n * n * (1.0 - cos * cos);
+            if sin2_t > 1.0 {
+                return 1.0;
+            }
+            cos = (1.0 - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+    }
 }
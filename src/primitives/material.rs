@@ -1,10 +1,15 @@
-use super::{pattern::StripePattern, PointLight};
+use super::{
+    pattern::StripePattern, sampling::cosine_sample_hemisphere, shader::PhongShader, Dispersion,
+    Medium, Microfacet, PointLight, SurfaceShader,
+};
 use crate::{
     image::Color,
     maths::{Point, Vector},
 };
+use std::f64::consts::PI;
+use std::sync::Arc;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Material {
     pub color: Color,
     pub ambient: f64,
@@ -12,10 +17,24 @@ pub struct Material {
     pub specular: f64,
     pub shininess: f64,
     pub pattern: Option<StripePattern>,
+    pub emission: Color,
+    /// Use physically based reflection instead of Phong's diffuse, specular & shininess
+    pub microfacet: Option<Microfacet>,
+    /// Fill the shape with a participating medium. The surface itself becomes invisible.
+    pub medium: Option<Medium>,
+    pub reflective: f64,
+    pub transparency: f64,
+    pub refractive_index: f64,
+    /// Make the refractive index depend on wavelength when rendering spectrally
+    pub dispersion: Option<Dispersion>,
+    /// How much of each color is absorbed per unit of distance travelled inside a transparent material
+    pub absorption: Color,
+    /// Replace the Phong lighting with a shader of your own
+    pub shader: Option<Arc<dyn SurfaceShader>>,
 }
 
-impl Material {
-    pub fn default() -> Self {
+impl Default for Material {
+    fn default() -> Self {
         Self {
             color: Color::white(),
             ambient: 0.1,
@@ -23,9 +42,20 @@ impl Material {
             specular: 0.9,
             shininess: 200.0,
             pattern: None,
+            emission: Color::black(),
+            microfacet: None,
+            medium: None,
+            reflective: 0.0,
+            transparency: 0.0,
+            refractive_index: 1.0,
+            dispersion: None,
+            absorption: Color::black(),
+            shader: None,
         }
     }
+}
 
+impl Material {
     pub fn lighting(
         &self,
         lights: &[PointLight],
@@ -34,7 +64,8 @@ impl Material {
         normalv: Vector,
         in_shadow: bool,
     ) -> Color {
-        let ambient_color = self.color_at(&position) * self.ambient;
+        // Emitted light doesn't depend on the lights so it's added with the ambient
+        let ambient_color = self.color_at(&position) * self.ambient + self.emission;
         lights
             .iter()
             .map(|light| self.internal_lighting(light, position, eyev, normalv, in_shadow))
@@ -63,6 +94,20 @@ impl Material {
             the other side of the surface
         */
         let light_dot_normal = Vector::dot(lightv, normalv);
+
+        if let Some(microfacet) = self.microfacet {
+            if in_shadow || light_dot_normal < 0.0 {
+                return Color::black();
+            }
+
+            /*
+                The factor of PI makes a point light deliver the same amount of light as
+                the Phong model does for a white, fully diffuse surface
+            */
+            let brdf = microfacet.evaluate(color, normalv, eyev, lightv);
+            return brdf * light.intensity() * (light_dot_normal * PI);
+        }
+
         let diffuse: Color;
         let specular: Color;
 
@@ -99,7 +144,77 @@ impl Material {
         }
     }
 
-    fn color_at(&self, point: &Point) -> Color {
+    /*
+        The BRDF used when path tracing. Phong materials are treated as Lambertian
+        using the diffuse component.
+    */
+    pub fn brdf(&self, position: Point, normalv: Vector, eyev: Vector, lightv: Vector) -> Color {
+        let color = self.color_at(&position);
+        match self.microfacet {
+            Some(microfacet) => microfacet.evaluate(color, normalv, eyev, lightv),
+            None if Vector::dot(normalv, lightv) > 0.0 => color * (self.diffuse / PI),
+            None => Color::black(),
+        }
+    }
+
+    /// The probability density of `sample_brdf` picking lightv, per unit solid angle
+    pub fn brdf_pdf(&self, normalv: Vector, eyev: Vector, lightv: Vector) -> f64 {
+        match self.microfacet {
+            Some(microfacet) => microfacet.pdf(normalv, eyev, lightv),
+            None => Vector::dot(normalv, lightv).max(0.0) / PI,
+        }
+    }
+
+    /// Pick the direction to continue a path in & the amount to scale the path's throughput by
+    pub fn sample_brdf(
+        &self,
+        position: Point,
+        normalv: Vector,
+        eyev: Vector,
+        random: (f64, f64, f64),
+    ) -> Option<(Vector, Color)> {
+        let color = self.color_at(&position);
+        match self.microfacet {
+            Some(microfacet) => microfacet.sample(color, normalv, eyev, random),
+            None => {
+                // The cosine & pdf cancel out leaving only the albedo
+                let (_, u, v) = random;
+                let direction = cosine_sample_hemisphere(normalv, u, v);
+                Some((direction, color * self.diffuse))
+            }
+        }
+    }
+
+    /// The shader which lights the surface, Phong unless the material has its own
+    pub fn shader(&self) -> &dyn SurfaceShader {
+        match &self.shader {
+            Some(shader) => shader.as_ref(),
+            None => &PhongShader,
+        }
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        self.emission != Color::black()
+    }
+
+    /// The refractive index for a wavelength in nanometres. Without a wavelength it's `refractive_index`.
+    pub fn refractive_index_at(&self, wavelength: Option<f64>) -> f64 {
+        match (self.dispersion, wavelength) {
+            (Some(dispersion), Some(wavelength)) => dispersion.refractive_index(wavelength),
+            _ => self.refractive_index,
+        }
+    }
+
+    /// The fraction of each color left after travelling a distance through the material (Beer-Lambert)
+    pub fn transmittance(&self, distance: f64) -> Color {
+        Color::new(
+            (-self.absorption.r() * distance).exp(),
+            (-self.absorption.g() * distance).exp(),
+            (-self.absorption.b() * distance).exp(),
+        )
+    }
+
+    pub fn color_at(&self, point: &Point) -> Color {
         self.pattern
             .map(|pattern| pattern.color_at(*point))
             .unwrap_or(self.color)
@@ -114,5 +229,21 @@ impl PartialEq for Material {
             && is_same(self.diffuse, other.diffuse)
             && is_same(self.specular, other.specular)
             && is_same(self.shininess, other.shininess)
+            && self.emission == other.emission
+            && self.microfacet == other.microfacet
+            && self.medium == other.medium
+            && is_same(self.reflective, other.reflective)
+            && is_same(self.transparency, other.transparency)
+            && is_same(self.refractive_index, other.refractive_index)
+            && self.dispersion == other.dispersion
+            && self.absorption == other.absorption
+            && match (&self.shader, &other.shader) {
+                (None, None) => true,
+                // Shaders are only equal when the materials share the same one
+                (Some(a), Some(b)) => {
+                    std::ptr::eq(Arc::as_ptr(a) as *const u8, Arc::as_ptr(b) as *const u8)
+                }
+                _ => false,
+            }
     }
 }
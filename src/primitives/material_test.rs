@@ -1,6 +1,6 @@
 use crate::{image::Color, primitives::pattern::StripePattern, Point, Vector};
 
-use super::{Material, PointLight};
+use super::{Material, Microfacet, PointLight};
 
 fn round(v: f64) -> f64 {
     const SIG_FIGS: f64 = 100000.0;
@@ -17,7 +17,7 @@ fn lighting_with_the_eye_between_the_light_and_the_surface() {
 
     let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
 
-    let result = m.lighting(&vec![light], position, eyev, normalv, false);
+    let result = m.lighting(&[light], position, eyev, normalv, false);
     assert_eq!(result, Color::new(1.9, 1.9, 1.9));
 }
 
@@ -29,7 +29,7 @@ fn lighting_with_the_eye_between_light_and_surface_eye_offset_45() {
     let normalv = Vector::new(0.0, 0.0, -1.0);
     let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
 
-    let result = m.lighting(&vec![light], position, eyev, normalv, false);
+    let result = m.lighting(&[light], position, eyev, normalv, false);
     assert_eq!(result, Color::new(1.0, 1.0, 1.0));
 }
 
@@ -42,7 +42,7 @@ fn lighting_with_eye_opposite_surface_lighting_offset_45() {
     let normalv = Vector::new(0.0, 0.0, -1.0);
     let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
 
-    let result = m.lighting(&vec![light], position, eyev, normalv, false);
+    let result = m.lighting(&[light], position, eyev, normalv, false);
     assert_eq!(round(result.r()), 0.7364);
     assert_eq!(round(result.g()), 0.7364);
     assert_eq!(round(result.b()), 0.7364);
@@ -57,7 +57,7 @@ fn lighting_with_eye_in_the_path_of_the_reflection_vector() {
     let normalv = Vector::new(0.0, 0.0, -1.0);
     let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
 
-    let result = m.lighting(&vec![light], position, eyev, normalv, false);
+    let result = m.lighting(&[light], position, eyev, normalv, false);
     assert_eq!(round(result.r()), 1.6364);
     assert_eq!(round(result.g()), 1.6364);
     assert_eq!(round(result.b()), 1.6364);
@@ -72,7 +72,7 @@ fn lighting_with_the_light_behind_the_surface() {
     let normalv = Vector::new(0.0, 0.0, -1.0);
     let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0));
 
-    let result = m.lighting(&vec![light], position, eyev, normalv, false);
+    let result = m.lighting(&[light], position, eyev, normalv, false);
     assert_eq!(result, Color::new(0.1, 0.1, 0.1));
 }
 
@@ -85,16 +85,18 @@ fn lighting_with_the_surface_in_shadow() {
     let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
     let in_shadow = true;
 
-    let result = m.lighting(&vec![light], position, eyev, normalv, in_shadow);
+    let result = m.lighting(&[light], position, eyev, normalv, in_shadow);
     assert_eq!(result, Color::new(0.1, 0.1, 0.1));
 }
 
 #[test]
 fn lighting_with_a_pattern_applied() {
-    let mut m = Material::default();
-    m.ambient = 1.0;
-    m.diffuse = 0.0;
-    m.specular = 0.0;
+    let mut m = Material {
+        ambient: 1.0,
+        diffuse: 0.0,
+        specular: 0.0,
+        ..Material::default()
+    };
 
     m.pattern = Some(StripePattern::new(Color::white(), Color::black()));
 
@@ -108,3 +110,40 @@ fn lighting_with_a_pattern_applied() {
     assert_eq!(col1, Color::new(1.0, 1.0, 1.0));
     assert_eq!(col2, Color::new(0.0, 0.0, 0.0));
 }
+
+#[test]
+fn lighting_with_an_emissive_material() {
+    let m = Material {
+        emission: Color::new(0.5, 0.5, 0.5),
+        ..Material::default()
+    };
+
+    let position = Point::new(0.0, 0.0, 0.0);
+    let eyev = Vector::new(0.0, 0.0, -1.0);
+    let normalv = Vector::new(0.0, 0.0, -1.0);
+    let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
+
+    // Emission still shows when the surface is in shadow
+    let result = m.lighting(&[light], position, eyev, normalv, true);
+    assert_eq!(result, Color::new(0.6, 0.6, 0.6));
+}
+
+#[test]
+fn lighting_a_rough_white_dielectric_matches_phong_diffuse() {
+    let m = Material {
+        microfacet: Some(Microfacet::new(0.0, 1.0)),
+        ..Material::default()
+    };
+
+    let position = Point::new(0.0, 0.0, 0.0);
+    let eyev = Vector::new(0.0, 0.0, -1.0);
+    let normalv = Vector::new(0.0, 0.0, -1.0);
+    let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white());
+
+    // Ambient of 0.1 plus roughly all of the light, minus some lost to Fresnel & masking
+    let result = m.lighting(&[light], position, eyev, normalv, false);
+    assert!(result.r() > 0.9 && result.r() < 1.1);
+
+    let shadowed = m.lighting(&[light], position, eyev, normalv, true);
+    assert_eq!(shadowed, Color::new(0.1, 0.1, 0.1));
+}
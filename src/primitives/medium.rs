@@ -0,0 +1,65 @@
+use super::sampling::orthonormal_basis;
+use crate::{image::Color, maths::Vector};
+use std::f64::consts::PI;
+
+/// A homogeneous participating medium, such as smoke, filling the inside of a shape
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Medium {
+    /// The fraction of light which is scattered rather than absorbed at each interaction
+    pub color: Color,
+    /// How many interactions happen per unit of distance
+    pub density: f64,
+    /// Henyey-Greenstein asymmetry between -1 & 1. Positive values scatter light forwards.
+    pub anisotropy: f64,
+}
+
+impl Medium {
+    pub fn new(color: Color, density: f64) -> Self {
+        Self {
+            color,
+            density,
+            anisotropy: 0.0,
+        }
+    }
+
+    /// The fraction of light which travels a distance through the medium without interacting
+    pub fn transmittance(&self, distance: f64) -> f64 {
+        (-self.density * distance).exp()
+    }
+
+    /// How much light is scattered between two directions with the given cosine.
+    /// Integrates to 1 over the sphere.
+    pub fn phase(&self, cos_theta: f64) -> f64 {
+        let g = self.anisotropy;
+        let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denom * denom.sqrt())
+    }
+
+    /// Pick how far light travels before interacting with the medium
+    pub fn sample_distance(&self, u: f64) -> f64 {
+        if self.density <= 0.0 {
+            return f64::INFINITY;
+        }
+        -(1.0 - u).ln() / self.density
+    }
+
+    /// Pick the direction light travelling along `direction` is scattered into.
+    /// Directions are picked in proportion to the phase function.
+    pub fn sample_direction(&self, direction: Vector, u: f64, v: f64) -> Vector {
+        let g = self.anisotropy;
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * u
+        } else {
+            let square = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+            (1.0 + g * g - square * square) / (2.0 * g)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * v;
+
+        let (tangent, bitangent) = orthonormal_basis(direction);
+        (tangent * (sin_theta * phi.cos())
+            + bitangent * (sin_theta * phi.sin())
+            + direction * cos_theta)
+            .normalize()
+    }
+}
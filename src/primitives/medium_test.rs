@@ -0,0 +1,62 @@
+use super::{sampling::sample_rng, Medium};
+use crate::{image::Color, maths::Vector};
+use rand::Rng;
+use std::f64::consts::PI;
+
+#[test]
+fn light_fades_exponentially_through_a_medium() {
+    let medium = Medium::new(Color::white(), 0.5);
+
+    assert_eq!(medium.transmittance(0.0), 1.0);
+    assert!((medium.transmittance(2.0) - (-1.0f64).exp()).abs() < 1e-12);
+}
+
+#[test]
+fn an_empty_medium_never_scatters() {
+    let medium = Medium::new(Color::white(), 0.0);
+    assert_eq!(medium.sample_distance(0.99), f64::INFINITY);
+}
+
+#[test]
+fn the_isotropic_phase_function_is_the_same_in_every_direction() {
+    let medium = Medium::new(Color::white(), 1.0);
+
+    assert!((medium.phase(1.0) - 1.0 / (4.0 * PI)).abs() < 1e-12);
+    assert!((medium.phase(-0.3) - 1.0 / (4.0 * PI)).abs() < 1e-12);
+}
+
+#[test]
+fn the_phase_function_integrates_to_one() {
+    let mut medium = Medium::new(Color::white(), 1.0);
+    medium.anisotropy = 0.6;
+
+    let steps = 10000;
+    let d_cos = 2.0 / steps as f64;
+    let total: f64 = (0..steps)
+        .map(|i| medium.phase(-1.0 + (i as f64 + 0.5) * d_cos) * 2.0 * PI * d_cos)
+        .sum();
+
+    assert!((total - 1.0).abs() < 0.01, "{}", total);
+}
+
+#[test]
+fn sampled_directions_follow_the_anisotropy() {
+    // The average cosine of the Henyey-Greenstein phase function is g
+    let mut medium = Medium::new(Color::white(), 1.0);
+    medium.anisotropy = 0.5;
+    let direction = Vector::new(0.0, 0.0, 1.0);
+    let mut rng = sample_rng(7, 0, 0, 0);
+
+    let samples = 20000;
+    let mean: f64 = (0..samples)
+        .map(|_| {
+            Vector::dot(
+                direction,
+                medium.sample_direction(direction, rng.gen(), rng.gen()),
+            )
+        })
+        .sum::<f64>()
+        / samples as f64;
+
+    assert!((mean - 0.5).abs() < 0.02, "{}", mean);
+}
@@ -0,0 +1,151 @@
+use super::sampling::{cosine_sample_hemisphere, orthonormal_basis};
+use crate::{image::Color, maths::Vector};
+use std::f64::consts::PI;
+
+/// Reflectance of a dielectric looking straight at the surface
+const DIELECTRIC_F0: f64 = 0.04;
+
+/// Keep roughness away from 0 so the distribution doesn't become a delta function
+const MIN_ROUGHNESS: f64 = 0.02;
+
+/// Metal/roughness physically based reflection using a GGX microfacet distribution
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Microfacet {
+    pub metallic: f64,
+    pub roughness: f64,
+}
+
+impl Microfacet {
+    pub fn new(metallic: f64, roughness: f64) -> Self {
+        Self {
+            metallic,
+            roughness,
+        }
+    }
+
+    fn alpha(&self) -> f64 {
+        let roughness = self.roughness.clamp(MIN_ROUGHNESS, 1.0);
+        roughness * roughness
+    }
+
+    /// GGX / Trowbridge-Reitz normal distribution
+    fn distribution(&self, n_dot_h: f64) -> f64 {
+        let alpha2 = self.alpha() * self.alpha();
+        let denom = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+        alpha2 / (PI * denom * denom)
+    }
+
+    /// Smith masking for a single direction
+    fn smith_g1(&self, n_dot_x: f64) -> f64 {
+        let alpha2 = self.alpha() * self.alpha();
+        2.0 * n_dot_x / (n_dot_x + (alpha2 + (1.0 - alpha2) * n_dot_x * n_dot_x).sqrt())
+    }
+
+    /// Schlick's approximation of the Fresnel reflectance
+    fn fresnel(&self, base_color: Color, v_dot_h: f64) -> Color {
+        let f0 = Color::new(DIELECTRIC_F0, DIELECTRIC_F0, DIELECTRIC_F0) * (1.0 - self.metallic)
+            + base_color * self.metallic;
+        let weight = (1.0 - v_dot_h).clamp(0.0, 1.0).powi(5);
+        f0 + (Color::white() - f0) * weight
+    }
+
+    /// The probability of choosing the specular lobe when sampling a direction
+    fn specular_probability(&self) -> f64 {
+        0.5 + 0.5 * self.metallic
+    }
+
+    /*
+        Evaluate the BRDF for light arriving along lightv & leaving along eyev. Both
+        vectors point away from the surface. The result doesn't include the cosine term.
+    */
+    pub fn evaluate(
+        &self,
+        base_color: Color,
+        normalv: Vector,
+        eyev: Vector,
+        lightv: Vector,
+    ) -> Color {
+        let n_dot_l = Vector::dot(normalv, lightv);
+        let n_dot_v = Vector::dot(normalv, eyev);
+        if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+            return Color::black();
+        }
+
+        let halfv = (eyev + lightv).normalize();
+        let n_dot_h = Vector::dot(normalv, halfv).max(0.0);
+        let v_dot_h = Vector::dot(eyev, halfv).max(0.0);
+
+        let fresnel = self.fresnel(base_color, v_dot_h);
+        let geometry = self.smith_g1(n_dot_l) * self.smith_g1(n_dot_v);
+        let specular =
+            fresnel * (self.distribution(n_dot_h) * geometry / (4.0 * n_dot_l * n_dot_v));
+
+        /*
+            Light which isn't reflected at the surface on the way in or out is what's
+            left for the diffuse. Using both directions keeps the BRDF reciprocal.
+        */
+        let transmitted = (Color::white() - self.fresnel(base_color, n_dot_l))
+            * (Color::white() - self.fresnel(base_color, n_dot_v));
+        let diffuse = transmitted * base_color * ((1.0 - self.metallic) / PI);
+
+        specular + diffuse
+    }
+
+    /// The probability density of `sample` choosing lightv, per unit solid angle
+    pub fn pdf(&self, normalv: Vector, eyev: Vector, lightv: Vector) -> f64 {
+        let n_dot_l = Vector::dot(normalv, lightv);
+        if n_dot_l <= 0.0 {
+            return 0.0;
+        }
+
+        let halfv = (eyev + lightv).normalize();
+        let n_dot_h = Vector::dot(normalv, halfv).max(0.0);
+        let v_dot_h = Vector::dot(eyev, halfv).abs().max(f64::EPSILON);
+
+        let specular_pdf = self.distribution(n_dot_h) * n_dot_h / (4.0 * v_dot_h);
+        let diffuse_pdf = n_dot_l / PI;
+
+        let p = self.specular_probability();
+        p * specular_pdf + (1.0 - p) * diffuse_pdf
+    }
+
+    /*
+        Pick a direction for light to arrive from. Returns the direction & the BRDF
+        multiplied by the cosine term divided by the pdf, which is the amount the path's
+        throughput should be scaled by.
+    */
+    pub fn sample(
+        &self,
+        base_color: Color,
+        normalv: Vector,
+        eyev: Vector,
+        random: (f64, f64, f64),
+    ) -> Option<(Vector, Color)> {
+        let (choice, u, v) = random;
+
+        let lightv = if choice < self.specular_probability() {
+            // Pick a microfacet normal from the distribution & reflect the eye around it
+            let alpha2 = self.alpha() * self.alpha();
+            let cos_theta = ((1.0 - u) / (1.0 + (alpha2 - 1.0) * u)).sqrt();
+            let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+            let phi = 2.0 * PI * v;
+
+            let (tangent, bitangent) = orthonormal_basis(normalv);
+            let halfv = tangent * (sin_theta * phi.cos())
+                + bitangent * (sin_theta * phi.sin())
+                + normalv * cos_theta;
+            (-eyev).reflect(halfv)
+        } else {
+            cosine_sample_hemisphere(normalv, u, v)
+        };
+
+        let n_dot_l = Vector::dot(normalv, lightv);
+        let pdf = self.pdf(normalv, eyev, lightv);
+        if n_dot_l <= 0.0 || pdf <= 0.0 {
+            return None;
+        }
+
+        let weight = self.evaluate(base_color, normalv, eyev, lightv) * (n_dot_l / pdf);
+        Some((lightv, weight))
+    }
+}
@@ -0,0 +1,117 @@
+use super::{sampling::sample_rng, Microfacet};
+use crate::{image::Color, maths::Vector};
+use rand::Rng;
+use std::f64::consts::PI;
+
+const SAMPLES: u32 = 20000;
+
+fn view_at_angle(radians: f64) -> Vector {
+    Vector::new(radians.sin(), 0.0, radians.cos())
+}
+
+/// Estimate how much of the incoming light is reflected using the importance sampler
+fn sampled_albedo(microfacet: Microfacet, eyev: Vector) -> f64 {
+    let normalv = Vector::new(0.0, 0.0, 1.0);
+    let mut rng = sample_rng(1, 0, 0, 0);
+
+    let total: f64 = (0..SAMPLES)
+        .filter_map(|_| {
+            let random = (rng.gen(), rng.gen(), rng.gen());
+            microfacet.sample(Color::white(), normalv, eyev, random)
+        })
+        .map(|(_, weight)| weight.g())
+        .sum();
+
+    total / SAMPLES as f64
+}
+
+/// Estimate how much of the incoming light is reflected using uniform hemisphere directions
+fn uniform_albedo(microfacet: Microfacet, eyev: Vector) -> f64 {
+    let normalv = Vector::new(0.0, 0.0, 1.0);
+    let mut rng = sample_rng(2, 0, 0, 0);
+
+    let total: f64 = (0..SAMPLES)
+        .map(|_| {
+            let z: f64 = rng.gen();
+            let phi = 2.0 * PI * rng.gen::<f64>();
+            let radius = (1.0 - z * z).sqrt();
+            let lightv = Vector::new(radius * phi.cos(), radius * phi.sin(), z);
+
+            // Divide by the pdf of 1 / 2PI
+            let brdf = microfacet.evaluate(Color::white(), normalv, eyev, lightv);
+            brdf.g() * z * 2.0 * PI
+        })
+        .sum();
+
+    total / SAMPLES as f64
+}
+
+#[test]
+fn the_brdf_is_reciprocal() {
+    let microfacet = Microfacet::new(0.3, 0.4);
+    let normalv = Vector::new(0.0, 0.0, 1.0);
+    let a = view_at_angle(0.3);
+    let b = Vector::new(-0.5, 0.2, 0.8).normalize();
+    let base = Color::new(0.8, 0.5, 0.2);
+
+    assert_eq!(
+        microfacet.evaluate(base, normalv, a, b),
+        microfacet.evaluate(base, normalv, b, a)
+    );
+}
+
+#[test]
+fn no_light_is_reflected_from_below_the_surface() {
+    let microfacet = Microfacet::new(0.0, 0.5);
+    let normalv = Vector::new(0.0, 0.0, 1.0);
+    let lightv = Vector::new(0.0, 0.6, -0.8);
+
+    let brdf = microfacet.evaluate(Color::white(), normalv, view_at_angle(0.0), lightv);
+    assert_eq!(brdf, Color::black());
+}
+
+#[test]
+fn a_white_furnace_does_not_create_energy() {
+    for &metallic in &[0.0, 1.0] {
+        for &roughness in &[0.05, 0.25, 0.5, 1.0] {
+            for &angle in &[0.0, 0.8, 1.4] {
+                let microfacet = Microfacet::new(metallic, roughness);
+                let albedo = sampled_albedo(microfacet, view_at_angle(angle));
+                assert!(
+                    albedo <= 1.01,
+                    "metallic {} roughness {} angle {} reflected {}",
+                    metallic,
+                    roughness,
+                    angle,
+                    albedo
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn the_sampler_agrees_with_the_brdf() {
+    // Both estimates converge on the same integral if the pdf matches the sampler
+    for &metallic in &[0.0, 1.0] {
+        for &roughness in &[0.5, 1.0] {
+            let microfacet = Microfacet::new(metallic, roughness);
+            let eyev = view_at_angle(0.6);
+            let sampled = sampled_albedo(microfacet, eyev);
+            let uniform = uniform_albedo(microfacet, eyev);
+            assert!(
+                (sampled - uniform).abs() < 0.03,
+                "sampled {} uniform {}",
+                sampled,
+                uniform
+            );
+        }
+    }
+}
+
+#[test]
+fn a_smooth_white_metal_reflects_almost_everything() {
+    let microfacet = Microfacet::new(1.0, 0.05);
+    let albedo = sampled_albedo(microfacet, view_at_angle(0.0));
+    assert!(albedo > 0.95 && albedo <= 1.01);
+}
@@ -1,38 +1,91 @@
-// This is a library to the code won't be used yet
-#![allow(dead_code)]
-
 // Code
+mod aov;
+mod background;
+mod bounds;
 mod camera;
+mod dispersion;
+mod environment_map;
+mod fog;
+mod inspection;
+mod integrator;
 mod intersection;
 mod intersection_stats;
 mod material;
+mod medium;
+mod microfacet;
 mod pattern;
 mod point_light;
+mod progress;
 mod ray;
+mod sampling;
+mod shader;
 mod shape;
+mod sky;
+mod spectrum;
+mod tile;
+mod viewpoint;
 mod world;
 
 // Exports
+pub use aov::{Aov, SurfaceInfo};
+pub use background::Background;
+pub use bounds::Bounds;
 pub use camera::Camera;
-pub use intersection::Intersection;
+pub use dispersion::Dispersion;
+pub use environment_map::EnvironmentMap;
+pub use fog::Fog;
+pub use inspection::{Inspection, PixelInspection};
+pub use integrator::{Integrator, IntegratorKind};
+pub(crate) use intersection::Intersection;
 pub use intersection_stats::IntersectionStats;
 pub use material::Material;
+pub use medium::Medium;
+pub(crate) use microfacet::Microfacet;
 pub use pattern::StripePattern;
 pub use point_light::PointLight;
+pub use progress::{Progress, ProgressTracker};
 pub use ray::Ray;
-pub use shape::Shape;
-pub use world::World;
+pub use shader::{PhongShader, SurfaceShader, Tracer};
+pub use shape::{Geometry, Plane, Shape, ShapeIds, ShapeType, Sphere};
+pub use sky::PreethamSky;
+pub use tile::{Tile, TileOrder, TileScheduler};
+pub use viewpoint::Viewpoint;
+pub use world::{World, WorldImpl};
 
 // Tests
 #[cfg(test)]
+mod aov_test;
+#[cfg(test)]
+mod background_test;
+#[cfg(test)]
+mod bounds_test;
+#[cfg(test)]
 mod camera_test;
 #[cfg(test)]
+mod inspection_test;
+#[cfg(test)]
 mod material_test;
 #[cfg(test)]
+mod medium_test;
+#[cfg(test)]
+mod microfacet_test;
+#[cfg(test)]
 mod pattern_test;
 #[cfg(test)]
+mod progress_test;
+#[cfg(test)]
 mod ray_test;
 #[cfg(test)]
+mod shader_test;
+#[cfg(test)]
 mod shape_test;
 #[cfg(test)]
+mod sky_test;
+#[cfg(test)]
+mod spectrum_test;
+#[cfg(test)]
+mod tile_test;
+#[cfg(test)]
+mod viewpoint_test;
+#[cfg(test)]
 mod world_test;
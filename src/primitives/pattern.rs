@@ -13,9 +13,9 @@ impl StripePattern {
 
     pub fn color_at(&self, point: Point) -> Color {
         if (point.x().floor() as i32 % 2) == 0 {
-            return self.col1;
+            self.col1
         } else {
-            return self.col2;
+            self.col2
         }
     }
 }
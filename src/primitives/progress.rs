@@ -0,0 +1,133 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// How far through a render is at one moment
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Progress {
+    /// Pixel samples rendered so far
+    pub completed: u64,
+    /// Pixel samples in the whole render
+    pub total: u64,
+    /// Rays tested against the world since the render started
+    pub rays_cast: u64,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// How much of the render is done, from 0 to 1
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            (self.completed as f64 / self.total as f64).min(1.0)
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.completed >= self.total
+    }
+
+    pub fn rays_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.rays_cast as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// How much longer the render should take at the speed it's gone so far. Unknown until some of it is done.
+    pub fn remaining(&self) -> Option<Duration> {
+        if self.completed == 0 {
+            return None;
+        }
+        let left = self.total.saturating_sub(self.completed) as f64 / self.completed as f64;
+        Some(self.elapsed.mul_f64(left))
+    }
+
+    /// A bar `width` characters wide which fills up as the render goes, e.g. `[#####     ]`
+    pub fn bar(&self, width: usize) -> String {
+        let filled = (self.fraction() * width as f64).floor() as usize;
+        format!("[{}{}]", "#".repeat(filled), " ".repeat(width - filled))
+    }
+}
+
+/// The percentage done, speed, time taken & time left, e.g. `45% 1.2M rays/s 0:12 elapsed, 0:15 left`
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.0}% {} rays/s {} elapsed",
+            self.fraction() * 100.0,
+            rate(self.rays_per_second()),
+            duration(self.elapsed)
+        )?;
+        match self.remaining() {
+            Some(remaining) if !self.is_complete() => write!(f, ", {} left", duration(remaining)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn rate(per_second: f64) -> String {
+    if per_second >= 1e6 {
+        format!("{:.1}M", per_second / 1e6)
+    } else if per_second >= 1e3 {
+        format!("{:.1}k", per_second / 1e3)
+    } else {
+        format!("{:.0}", per_second)
+    }
+}
+
+/// A duration as minutes & seconds, with hours once it's long enough
+fn duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+/*
+    Counts the samples rendered by any number of threads. The rays are counted by the
+    world, so the count when the render started is kept to take off later ones.
+*/
+pub struct ProgressTracker {
+    completed: AtomicU64,
+    total: u64,
+    rays_at_start: u64,
+    start: Instant,
+}
+
+impl ProgressTracker {
+    pub fn new(total: u64, rays_cast: u64) -> Self {
+        Self {
+            completed: AtomicU64::new(0),
+            total,
+            rays_at_start: rays_cast,
+            start: Instant::now(),
+        }
+    }
+
+    /// Record that more samples are done
+    pub fn add(&self, samples: u64) {
+        self.completed.fetch_add(samples, Ordering::Relaxed);
+    }
+
+    pub fn completed(&self) -> u64 {
+        self.completed.load(Ordering::Relaxed)
+    }
+
+    /// How far the render has got, given how many rays the world has cast in total
+    pub fn progress(&self, rays_cast: u64) -> Progress {
+        Progress {
+            completed: self.completed(),
+            total: self.total,
+            rays_cast: rays_cast.saturating_sub(self.rays_at_start),
+            elapsed: self.start.elapsed(),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use super::{Camera, Integrator, Progress, ProgressTracker, World};
+use crate::maths::{Matrix4x4, Point, Vector};
+use std::sync::mpsc;
+use std::time::Duration;
+
+fn progress(completed: u64, total: u64, seconds: u64) -> Progress {
+    Progress {
+        completed,
+        total,
+        rays_cast: 3_000_000,
+        elapsed: Duration::from_secs(seconds),
+    }
+}
+
+#[test]
+fn the_time_left_comes_from_the_speed_so_far() {
+    let p = progress(25, 100, 10);
+
+    assert_eq!(p.fraction(), 0.25);
+    assert_eq!(p.remaining(), Some(Duration::from_secs(30)));
+    assert_eq!(p.rays_per_second(), 300_000.0);
+    assert!(!p.is_complete());
+
+    assert_eq!(progress(0, 100, 10).remaining(), None);
+    assert!(progress(100, 100, 10).is_complete());
+}
+
+#[test]
+fn progress_can_be_shown_as_a_bar() {
+    assert_eq!(progress(25, 100, 10).bar(8), "[##      ]");
+    assert_eq!(progress(100, 100, 10).bar(4), "[####]");
+    assert_eq!(
+        progress(25, 100, 10).to_string(),
+        "25% 300.0k rays/s 0:10 elapsed, 0:30 left"
+    );
+    assert_eq!(
+        progress(100, 100, 3725).to_string(),
+        "100% 805 rays/s 1:02:05 elapsed"
+    );
+}
+
+#[test]
+fn the_tracker_counts_from_when_it_started() {
+    let tracker = ProgressTracker::new(10, 500);
+    tracker.add(4);
+    tracker.add(2);
+
+    let p = tracker.progress(800);
+    assert_eq!((p.completed, p.total, p.rays_cast), (6, 10, 300));
+}
+
+#[test]
+fn progress_can_be_followed_through_a_channel() {
+    let w = World::default()
+        .set_integrator(Integrator::path_tracer(2, 2))
+        .generate();
+    let c = Camera::new(
+        40,
+        20,
+        std::f64::consts::FRAC_PI_2,
+        Matrix4x4::view(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::up(),
+        ),
+    );
+
+    let (sender, receiver) = mpsc::channel();
+    let image = c.render_with_progress(&w, move |progress| sender.send(progress).unwrap());
+    let updates: Vec<Progress> = receiver.iter().collect();
+
+    // One update for each of the two tiles, each counting both samples of its pixels
+    assert_eq!(updates.len(), 2);
+    let last = updates.iter().max_by_key(|p| p.completed).unwrap();
+    assert_eq!((last.completed, last.total), (1600, 1600));
+    assert!(last.rays_cast > 0);
+    assert_eq!(image.pixel_at(20, 10), c.render(&w).pixel_at(20, 10));
+}
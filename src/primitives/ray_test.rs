@@ -1,4 +1,4 @@
-use super::{Intersection, Ray, Shape};
+use super::{Dispersion, Intersection, Material, Ray, Shape};
 use crate::{Matrix4x4, Point, Vector};
 
 #[test]
@@ -27,7 +27,7 @@ fn a_ray_intersects_a_sphere_at_2_locations() {
     let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
     let s = Shape::default();
 
-    let intersections = r.intersects(s);
+    let intersections = r.intersects(&s);
     assert_eq!(intersections[0].t(), 4.0);
     assert_eq!(intersections[1].t(), 6.0);
 }
@@ -36,7 +36,7 @@ fn a_ray_intersects_a_sphere_at_2_locations() {
 fn a_ray_intersects_a_sphere_at_a_tangent() {
     let r = Ray::new(Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0));
     let s = Shape::default();
-    let i = r.intersects(s);
+    let i = r.intersects(&s);
 
     assert_eq!(i[0].t(), 5.0);
     assert_eq!(i[1].t(), 5.0);
@@ -47,7 +47,7 @@ fn a_ray_misses_a_sphere() {
     let r = Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0));
     let s = Shape::default();
 
-    let i = r.intersects(s);
+    let i = r.intersects(&s);
 
     assert_eq!(i.len(), 0);
 }
@@ -57,7 +57,7 @@ fn a_ray_originates_inside_a_sphere() {
     let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
     let s = Shape::default();
 
-    let i = r.intersects(s);
+    let i = r.intersects(&s);
     assert_eq!(i[0].t(), -1.0);
     assert_eq!(i[1].t(), 1.0);
 }
@@ -67,29 +67,29 @@ fn a_sphere_is_behind_a_ray() {
     let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
     let s = Shape::default();
 
-    let i = r.intersects(s);
+    let i = r.intersects(&s);
     assert_eq!(i[0].t(), -6.0);
     assert_eq!(i[1].t(), -4.0);
-    assert_eq!(i[0].object(), s);
-    assert_eq!(i[1].object(), s);
+    assert_eq!(*i[0].object(), s);
+    assert_eq!(*i[1].object(), s);
 }
 
 #[test]
 fn an_intersection_encapsulates_t_and_object() {
     let s = Shape::default();
-    let i = Intersection::new(3.5, s);
+    let i = Intersection::new(3.5, s.clone());
 
     assert_eq!(i.t(), 3.5);
-    assert_eq!(i.object(), s);
+    assert_eq!(*i.object(), s);
 }
 
 #[test]
 fn the_hit_when_all_intersections_have_positive_t() {
     let s = Shape::default();
-    let i1 = Intersection::new(1.0, s);
-    let i2 = Intersection::new(2.0, s);
+    let i1 = Intersection::new(1.0, s.clone());
+    let i2 = Intersection::new(2.0, s.clone());
 
-    let intersections = vec![i1, i2];
+    let intersections = vec![i1.clone(), i2.clone()];
     let hit = Intersection::hit(intersections).unwrap();
 
     assert_eq!(hit, i1);
@@ -98,10 +98,10 @@ fn the_hit_when_all_intersections_have_positive_t() {
 #[test]
 fn the_hit_when_some_intersections_have_negative_t() {
     let s = Shape::default();
-    let i1 = Intersection::new(-1.0, s);
-    let i2 = Intersection::new(1.0, s);
+    let i1 = Intersection::new(-1.0, s.clone());
+    let i2 = Intersection::new(1.0, s.clone());
 
-    let intersections = vec![i1, i2];
+    let intersections = vec![i1.clone(), i2.clone()];
     let hit = Intersection::hit(intersections).unwrap();
 
     assert_eq!(hit, i2);
@@ -110,10 +110,10 @@ fn the_hit_when_some_intersections_have_negative_t() {
 #[test]
 fn the_hit_when_all_intersections_have_negative_t() {
     let s = Shape::default();
-    let i1 = Intersection::new(-2.0, s);
-    let i2 = Intersection::new(-1.0, s);
+    let i1 = Intersection::new(-2.0, s.clone());
+    let i2 = Intersection::new(-1.0, s.clone());
 
-    let intersections = vec![i1, i2];
+    let intersections = vec![i1.clone(), i2.clone()];
     let hit = Intersection::hit(intersections);
 
     assert_eq!(hit, None);
@@ -122,11 +122,11 @@ fn the_hit_when_all_intersections_have_negative_t() {
 #[test]
 fn the_hit_is_always_the_lowest_nonnegative_intersection() {
     let s = Shape::default();
-    let i1 = Intersection::new(5.0, s);
-    let i2 = Intersection::new(7.0, s);
-    let i3 = Intersection::new(-3.0, s);
-    let i4 = Intersection::new(2.0, s);
-    let hit = Intersection::hit(vec![i1, i2, i3, i4]).unwrap();
+    let i1 = Intersection::new(5.0, s.clone());
+    let i2 = Intersection::new(7.0, s.clone());
+    let i3 = Intersection::new(-3.0, s.clone());
+    let i4 = Intersection::new(2.0, s.clone());
+    let hit = Intersection::hit(vec![i1.clone(), i2.clone(), i3.clone(), i4.clone()]).unwrap();
 
     assert_eq!(hit, i4);
 }
@@ -170,7 +170,7 @@ fn intersecting_a_scaled_sphere_with_a_ray() {
     let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
     let s = Shape::sphere_from_transformation(Matrix4x4::scaling(2.0, 2.0, 2.0));
 
-    let intersections = r.intersects(s);
+    let intersections = r.intersects(&s);
 
     assert_eq!(intersections.len(), 2);
     assert_eq!(intersections[0].t(), 3.0);
@@ -182,7 +182,7 @@ fn intersecting_a_translated_sphere_with_a_ray() {
     let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
     let s = Shape::sphere_from_transformation(Matrix4x4::translation(5.0, 0.0, 0.0));
 
-    let intersections = r.intersects(s);
+    let intersections = r.intersects(&s);
 
     assert_eq!(intersections.len(), 0);
 }
@@ -191,7 +191,7 @@ fn intersecting_a_translated_sphere_with_a_ray() {
 fn precomputing_the_state_of_an_intersection() {
     let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
     let shape = Shape::default();
-    let i = Intersection::new(4.0, shape);
+    let i = Intersection::new(4.0, shape.clone());
 
     let comps = i.prepare_computations(r);
     assert_eq!(comps.t(), i.t());
@@ -205,22 +205,22 @@ fn precomputing_the_state_of_an_intersection() {
 fn the_hit_when_an_intersection_occurs_on_the_outside() {
     let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
     let shape = Shape::default();
-    let i = Intersection::new(4.0, shape);
+    let i = Intersection::new(4.0, shape.clone());
 
     let comps = i.prepare_computations(ray);
-    assert_eq!(comps.inside(), false);
+    assert!(!comps.inside());
 }
 
 #[test]
 fn the_hit_when_an_intersection_occurs_on_the_inside() {
     let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
     let shape = Shape::default();
-    let i = Intersection::new(1.0, shape);
+    let i = Intersection::new(1.0, shape.clone());
 
     let comps = i.prepare_computations(ray);
     assert_eq!(comps.point(), Point::new(0.0, 0.0, 1.0));
     assert_eq!(comps.eyev(), Vector::new(0.0, 0.0, -1.0));
-    assert_eq!(comps.inside(), true);
+    assert!(comps.inside());
     assert_eq!(comps.normalv(), Vector::new(0.0, 0.0, -1.0));
 }
 
@@ -229,10 +229,10 @@ fn the_hit_should_offset_the_point() {
     let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
     let shape = Shape::sphere_from_transformation(Matrix4x4::translation(0.0, 0.0, 1.0));
 
-    let i = Intersection::new(5.0, shape);
+    let i = Intersection::new(5.0, shape.clone());
     let comps = i.prepare_computations(ray);
 
-    assert!(comps.over_point().z() < (-std::f32::EPSILON as f64 / 2.0));
+    assert!(comps.over_point().z() < (-f32::EPSILON as f64 / 2.0));
     assert!(comps.point().z() > comps.over_point().z());
 }
 
@@ -240,7 +240,7 @@ fn the_hit_should_offset_the_point() {
 fn intersect_with_a_ray_parallel_to_the_plane() {
     let p = Shape::plane_default();
     let r = Ray::new(Point::new(0.0, 10.0, 0.0), Vector::new(0.0, 0.0, 1.0));
-    let xs = r.intersects(p);
+    let xs = r.intersects(&p);
     assert_eq!(xs.len(), 0);
 }
 
@@ -248,7 +248,7 @@ fn intersect_with_a_ray_parallel_to_the_plane() {
 fn intersect_with_a_coplanar_ray() {
     let p = Shape::plane_default();
     let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
-    let xs = r.intersects(p);
+    let xs = r.intersects(&p);
     assert_eq!(xs.len(), 0);
 }
 
@@ -256,9 +256,138 @@ fn intersect_with_a_coplanar_ray() {
 fn a_ray_intersecting_a_plane_from_above() {
     let p = Shape::plane_default();
     let r = Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0));
-    let xs = r.intersects(p);
+    let xs = r.intersects(&p);
 
     assert_eq!(xs.len(), 1);
     assert_eq!(xs[0].t(), 1.0);
-    assert_eq!(xs[0].object(), p);
+    assert_eq!(*xs[0].object(), p);
+}
+
+fn glass_sphere(transform: Matrix4x4, refractive_index: f64) -> Shape {
+    let material = Material {
+        transparency: 1.0,
+        refractive_index,
+        ..Material::default()
+    };
+    Shape::sphere(transform, material)
+}
+
+#[test]
+fn precomputing_the_reflection_vector() {
+    let shape = Shape::plane_default();
+    let ray = Ray::new(
+        Point::new(0.0, 1.0, -1.0),
+        Vector::new(0.0, -(2f64.sqrt()) / 2.0, 2f64.sqrt() / 2.0),
+    );
+    let i = Intersection::new(2f64.sqrt(), shape.clone());
+    let comps = i.prepare_computations(ray);
+
+    let reflectv = comps.reflectv();
+    assert!((reflectv.y() - 2f64.sqrt() / 2.0).abs() < 1e-9);
+    assert!((reflectv.z() - 2f64.sqrt() / 2.0).abs() < 1e-9);
+}
+
+#[test]
+fn finding_n1_and_n2_at_various_intersections() {
+    let a = glass_sphere(Matrix4x4::scaling(2.0, 2.0, 2.0), 1.5);
+    let b = glass_sphere(Matrix4x4::translation(0.0, 0.0, -0.25), 2.0);
+    let c = glass_sphere(Matrix4x4::translation(0.0, 0.0, 0.25), 2.5);
+    let ray = Ray::new(Point::new(0.0, 0.0, -4.0), Vector::new(0.0, 0.0, 1.0));
+    let xs = vec![
+        Intersection::new(2.0, a.clone()),
+        Intersection::new(2.75, b.clone()),
+        Intersection::new(3.25, c.clone()),
+        Intersection::new(4.75, b.clone()),
+        Intersection::new(5.25, c.clone()),
+        Intersection::new(6.0, a.clone()),
+    ];
+
+    let expected = [
+        (1.0, 1.5),
+        (1.5, 2.0),
+        (2.0, 2.5),
+        (2.5, 2.5),
+        (2.5, 1.5),
+        (1.5, 1.0),
+    ];
+    for (i, (n1, n2)) in xs.iter().zip(expected.iter()) {
+        let comps = i.prepare_computations_in(ray, &xs);
+        assert_eq!(comps.n1(), *n1);
+        assert_eq!(comps.n2(), *n2);
+    }
+}
+
+#[test]
+fn the_under_point_is_offset_below_the_surface() {
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let shape = glass_sphere(Matrix4x4::translation(0.0, 0.0, 1.0), 1.5);
+    let i = Intersection::new(5.0, shape.clone());
+    let comps = i.prepare_computations(ray);
+
+    assert!(comps.under_point().z() > f32::EPSILON as f64 / 2.0);
+    assert!(comps.point().z() < comps.under_point().z());
+}
+
+#[test]
+fn the_schlick_approximation_under_total_internal_reflection() {
+    let shape = glass_sphere(Matrix4x4::identity(), 1.5);
+    let ray = Ray::new(
+        Point::new(0.0, 0.0, 2f64.sqrt() / 2.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    let xs = vec![
+        Intersection::new(-(2f64.sqrt()) / 2.0, shape.clone()),
+        Intersection::new(2f64.sqrt() / 2.0, shape.clone()),
+    ];
+    let comps = xs[1].prepare_computations_in(ray, &xs);
+
+    assert_eq!(comps.schlick(), 1.0);
+    assert!(comps.refracted_direction().is_none());
+}
+
+#[test]
+fn the_schlick_approximation_with_a_perpendicular_viewing_angle() {
+    let shape = glass_sphere(Matrix4x4::identity(), 1.5);
+    let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+    let xs = vec![
+        Intersection::new(-1.0, shape.clone()),
+        Intersection::new(1.0, shape.clone()),
+    ];
+    let comps = xs[1].prepare_computations_in(ray, &xs);
+
+    assert!((comps.schlick() - 0.04).abs() < 1e-9);
+}
+
+#[test]
+fn the_schlick_approximation_with_small_angle_and_n2_greater_than_n1() {
+    let shape = glass_sphere(Matrix4x4::identity(), 1.5);
+    let ray = Ray::new(Point::new(0.0, 0.99, -2.0), Vector::new(0.0, 0.0, 1.0));
+    let xs = vec![Intersection::new(1.8589, shape.clone())];
+    let comps = xs[0].prepare_computations_in(ray, &xs);
+
+    assert!((comps.schlick() - 0.48873).abs() < 1e-4);
+}
+
+#[test]
+fn a_dispersive_material_bends_each_wavelength_differently() {
+    let material = Material {
+        transparency: 1.0,
+        refractive_index: 1.5,
+        dispersion: Some(Dispersion::bk7()),
+        ..Material::default()
+    };
+    let shape = Shape::sphere(Matrix4x4::identity(), material);
+
+    let ray = Ray::new(Point::new(0.0, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let xs = ray.intersects(&shape);
+    let hit = Intersection::hit(xs.clone()).unwrap();
+
+    let blue = hit.prepare_computations_for_wavelength(ray, &xs, Some(450.0));
+    let red = hit.prepare_computations_for_wavelength(ray, &xs, Some(650.0));
+    assert!(blue.n2() > red.n2());
+
+    // Blue light is bent further towards the center
+    let blue = blue.refracted_direction().unwrap();
+    let red = red.refracted_direction().unwrap();
+    assert!(blue.y() < red.y());
 }
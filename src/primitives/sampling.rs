@@ -0,0 +1,42 @@
+use crate::maths::Vector;
+use rand::{rngs::SmallRng, SeedableRng};
+use std::f64::consts::PI;
+
+/// Create the random number generator used for a single sample of a pixel.
+/// Seeding per sample means the image doesn't depend on which thread rendered which pixel.
+pub fn sample_rng(seed: u64, x: i32, y: i32, sample: u32) -> SmallRng {
+    // Mix the inputs together so neighbouring pixels get unrelated streams
+    let mut hash = seed ^ 0x9E37_79B9_7F4A_7C15;
+    for value in [x as u64, y as u64, sample as u64] {
+        hash ^= value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        hash = (hash ^ (hash >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        hash = (hash ^ (hash >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        hash ^= hash >> 31;
+    }
+    SmallRng::seed_from_u64(hash)
+}
+
+/// Build two vectors which together with the normal form an orthonormal basis
+pub fn orthonormal_basis(normal: Vector) -> (Vector, Vector) {
+    let helper = if normal.x().abs() > 0.9 {
+        Vector::new(0.0, 1.0, 0.0)
+    } else {
+        Vector::new(1.0, 0.0, 0.0)
+    };
+    let tangent = Vector::cross(normal, helper).normalize();
+    let bitangent = Vector::cross(normal, tangent);
+    (tangent, bitangent)
+}
+
+/// Pick a direction in the hemisphere around the normal with a probability
+/// proportional to the cosine of the angle with the normal.
+pub fn cosine_sample_hemisphere(normal: Vector, u: f64, v: f64) -> Vector {
+    let (tangent, bitangent) = orthonormal_basis(normal);
+
+    let radius = u.sqrt();
+    let phi = 2.0 * PI * v;
+    let (x, y) = (radius * phi.cos(), radius * phi.sin());
+    let z = (1.0 - u).max(0.0).sqrt();
+
+    (tangent * x + bitangent * y + normal * z).normalize()
+}
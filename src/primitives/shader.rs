@@ -0,0 +1,46 @@
+use super::{IntersectionStats, PointLight, Ray};
+use crate::{image::Color, maths::Point};
+use std::fmt::Debug;
+
+/// Lets a shader look further into the world than the surface it's shading
+pub trait Tracer {
+    /// The color seen along a ray. Black once the limit on recursive rays is reached.
+    fn trace(&self, ray: Ray) -> Color;
+
+    /// Whether every light is hidden from the point
+    fn is_shadowed(&self, point: Point) -> bool;
+
+    /// Whether anything solid lies between a point and a target
+    fn is_occluded(&self, point: Point, target: Point) -> bool;
+}
+
+/*
+    Works out the light leaving a surface towards the eye. Reflection & refraction
+    from the material's `reflective` & `transparency` are still added by the world.
+    Shaders are only used by the Whitted integrator; the path tracers use the BRDF.
+*/
+pub trait SurfaceShader: Debug + Send + Sync {
+    fn shade(&self, comps: &IntersectionStats, lights: &[PointLight], tracer: &dyn Tracer)
+        -> Color;
+}
+
+/// The book's Phong lighting, used by materials without a shader of their own
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PhongShader;
+
+impl SurfaceShader for PhongShader {
+    fn shade(
+        &self,
+        comps: &IntersectionStats,
+        lights: &[PointLight],
+        tracer: &dyn Tracer,
+    ) -> Color {
+        comps.object().material().lighting(
+            lights,
+            comps.over_point(),
+            comps.eyev(),
+            comps.normalv(),
+            tracer.is_shadowed(comps.over_point()),
+        )
+    }
+}
@@ -0,0 +1,126 @@
+use super::{
+    Intersection, IntersectionStats, Material, PointLight, Ray, Shape, SurfaceShader, Tracer, World,
+};
+use crate::{
+    image::Color,
+    maths::{Matrix4x4, Point, Vector},
+};
+use std::sync::Arc;
+
+/// Lights the whole surface with one color
+#[derive(Debug)]
+struct Flat(Color);
+
+impl SurfaceShader for Flat {
+    fn shade(&self, _: &IntersectionStats, _: &[PointLight], _: &dyn Tracer) -> Color {
+        self.0
+    }
+}
+
+/// Shows half of whatever is behind the surface
+#[derive(Debug)]
+struct XRay;
+
+impl SurfaceShader for XRay {
+    fn shade(&self, comps: &IntersectionStats, _: &[PointLight], tracer: &dyn Tracer) -> Color {
+        tracer.trace(Ray::new(comps.under_point(), -comps.eyev())) * 0.5
+    }
+}
+
+/// A mirror which glows a little, so every bounce shows in the color
+#[derive(Debug)]
+struct GlowingMirror;
+
+impl SurfaceShader for GlowingMirror {
+    fn shade(&self, comps: &IntersectionStats, _: &[PointLight], tracer: &dyn Tracer) -> Color {
+        Color::new(0.01, 0.01, 0.01) + tracer.trace(Ray::new(comps.over_point(), comps.reflectv()))
+    }
+}
+
+fn shaded(shader: impl SurfaceShader + 'static) -> Material {
+    Material {
+        shader: Some(Arc::new(shader)),
+        ..Material::default()
+    }
+}
+
+#[test]
+fn materials_use_phong_lighting_by_default() {
+    let w = World::default().generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let comps = Intersection::new(4.0, w.objects()[0].clone()).prepare_computations(ray);
+    let material = comps.object().material();
+
+    let expected = material.lighting(
+        &w.lights(),
+        comps.over_point(),
+        comps.eyev(),
+        comps.normalv(),
+        false,
+    );
+
+    assert!(material.shader.is_none());
+    assert_eq!(w.shade_hit(comps), expected);
+}
+
+#[test]
+fn a_custom_shader_replaces_the_lighting() {
+    let w = World::new()
+        .add_light(PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::white(),
+        ))
+        .add_object(Shape::sphere(
+            Matrix4x4::identity(),
+            shaded(Flat(Color::green())),
+        ))
+        .generate();
+
+    let c = w.color_at(Ray::new(
+        Point::new(0.0, 0.0, -5.0),
+        Vector::new(0.0, 0.0, 1.0),
+    ));
+
+    assert_eq!(c, Color::green());
+}
+
+#[test]
+fn a_shader_can_trace_more_rays() {
+    let w = World::new()
+        .add_object(Shape::sphere(Matrix4x4::identity(), shaded(XRay)))
+        .add_object(Shape::sphere(
+            Matrix4x4::translation(0.0, 0.0, 5.0),
+            shaded(Flat(Color::red())),
+        ))
+        .generate();
+
+    let c = w.color_at(Ray::new(
+        Point::new(0.0, 0.0, -5.0),
+        Vector::new(0.0, 0.0, 1.0),
+    ));
+
+    // Through both sides of the x-ray sphere to the red one
+    assert_eq!(c, Color::red() * 0.25);
+}
+
+#[test]
+fn shaders_which_trace_forever_stop_at_the_recursion_limit() {
+    let w = World::new()
+        .add_object(Shape::plane(
+            Matrix4x4::translation(0.0, -1.0, 0.0),
+            shaded(GlowingMirror),
+        ))
+        .add_object(Shape::plane(
+            Matrix4x4::translation(0.0, 1.0, 0.0),
+            shaded(GlowingMirror),
+        ))
+        .generate();
+
+    let c = w.color_at(Ray::new(
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 1.0, 0.0),
+    ));
+
+    // The first hit plus one for each of the 16 recursive rays
+    assert_eq!(c, Color::new(0.17, 0.17, 0.17));
+}
@@ -1,18 +1,167 @@
-use super::Material;
+use super::{Bounds, Material, Ray};
 use crate::maths::{Matrix4x4, Point, Vector};
+use std::f64::consts::PI;
+use std::fmt::Debug;
+use std::sync::Arc;
 
+/*
+    The geometry of a shape in its own object space. The shape's transform & material
+    are handled by `Shape` so implementations only need to describe the untransformed
+    surface. Implement this to add new kinds of shape to a world.
+*/
+pub trait Geometry: Debug + Send + Sync {
+    /// The distances along the object space ray where it hits the surface, in any order
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64>;
+
+    /// The outward normal at an object space point on the surface. It doesn't need to be normalized.
+    fn local_normal_at(&self, point: Point) -> Vector;
+
+    /// A box in object space which contains the whole surface
+    fn bounds(&self) -> Bounds;
+
+    /*
+        Pick a point on the surface from 2 uniform random numbers for lights to be sampled.
+        Returns the object space point, the outward normal & the probability density of
+        picking the point per unit of object space area. Shapes which can't be sampled,
+        such as infinite ones, return None & can't be used as area lights.
+    */
+    fn sample_local_surface(&self, _u: f64, _v: f64) -> Option<(Point, Vector, f64)> {
+        None
+    }
+}
+
+/// A unit sphere around the origin
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sphere;
+
+impl Geometry for Sphere {
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let sphere_to_ray = ray.origin() - Point::new(0.0, 0.0, 0.0);
+        let a = Vector::dot(ray.direction(), ray.direction());
+        let b = 2.0 * Vector::dot(ray.direction(), sphere_to_ray);
+        let c = Vector::dot(sphere_to_ray, sphere_to_ray) - 1.0;
+
+        let discriminant = b * b - (4.0 * a * c);
+
+        if discriminant < 0.0 {
+            return vec![];
+        }
+
+        let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
+        let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+        vec![t1, t2]
+    }
+
+    fn local_normal_at(&self, point: Point) -> Vector {
+        point - Point::new(0.0, 0.0, 0.0)
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+
+    fn sample_local_surface(&self, u: f64, v: f64) -> Option<(Point, Vector, f64)> {
+        // Pick a uniform point on the unit sphere
+        let z = 1.0 - 2.0 * u;
+        let radius = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * v;
+        let point = Point::new(radius * phi.cos(), radius * phi.sin(), z);
+        Some((point, self.local_normal_at(point), 1.0 / (4.0 * PI)))
+    }
+}
+
+/// The xz plane, facing up the y axis
 #[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane;
+
+impl Geometry for Plane {
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        if ray.direction().y().abs() < (f64::EPSILON * 100.0) {
+            return vec![];
+        }
+        vec![-ray.origin().y() / ray.direction().y()]
+    }
+
+    fn local_normal_at(&self, _point: Point) -> Vector {
+        Vector::new(0.0, 1.0, 0.0)
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
 pub enum ShapeType {
     Sphere,
     Plane,
+    Custom(Arc<dyn Geometry>),
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+impl ShapeType {
+    pub fn geometry(&self) -> &dyn Geometry {
+        match self {
+            ShapeType::Sphere => &Sphere,
+            ShapeType::Plane => &Plane,
+            ShapeType::Custom(geometry) => geometry.as_ref(),
+        }
+    }
+}
+
+impl PartialEq for ShapeType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (ShapeType::Sphere, ShapeType::Sphere) | (ShapeType::Plane, ShapeType::Plane) => true,
+            // Custom shapes are only equal when they share the same geometry
+            (ShapeType::Custom(a), ShapeType::Custom(b)) => {
+                std::ptr::eq(Arc::as_ptr(a) as *const u8, Arc::as_ptr(b) as *const u8)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Where a shape is in its world, given to it when the world is generated
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ShapeIds {
+    /// The index of the shape in the world
+    pub object: usize,
+    /// The index of the first shape in the world with the same material
+    pub material: usize,
+}
+
+#[derive(Clone, Debug)]
 pub struct Shape {
     shape_type: ShapeType,
     transform: Matrix4x4,
     material: Material,
     transformation_inverse: Matrix4x4,
+    ids: Option<ShapeIds>,
+}
+
+// Shapes are the same whether or not they've been put in a world yet
+impl PartialEq for Shape {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape_type == other.shape_type
+            && self.transform == other.transform
+            && self.material == other.material
+            && self.transformation_inverse == other.transformation_inverse
+    }
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Self {
+            shape_type: ShapeType::Sphere,
+            transform: Matrix4x4::identity(),
+            material: Material::default(),
+            transformation_inverse: Matrix4x4::identity().inverse().unwrap(),
+            ids: None,
+        }
+    }
 }
 
 impl Shape {
@@ -22,6 +171,7 @@ impl Shape {
             transform,
             material: Material::default(),
             transformation_inverse: transform.inverse().unwrap(),
+            ids: None,
         }
     }
 
@@ -31,6 +181,7 @@ impl Shape {
             transform,
             material,
             transformation_inverse: transform.inverse().unwrap(),
+            ids: None,
         }
     }
 
@@ -40,6 +191,7 @@ impl Shape {
             transform: Matrix4x4::identity(),
             transformation_inverse: Matrix4x4::identity().inverse().unwrap(),
             material: Material::default(),
+            ids: None,
         }
     }
 
@@ -49,20 +201,49 @@ impl Shape {
             transform,
             transformation_inverse: transform.inverse().unwrap(),
             material,
+            ids: None,
         }
     }
 
-    pub fn default() -> Self {
-        Self {
-            shape_type: ShapeType::Sphere,
-            transform: Matrix4x4::identity(),
-            material: Material::default(),
-            transformation_inverse: Matrix4x4::identity().inverse().unwrap(),
+    /// A shape with user defined geometry. The geometry can be shared between several shapes.
+    pub fn custom(geometry: Arc<dyn Geometry>, transform: Matrix4x4, material: Material) -> Self {
+        Shape {
+            shape_type: ShapeType::Custom(geometry),
+            transform,
+            transformation_inverse: transform.inverse().unwrap(),
+            material,
+            ids: None,
         }
     }
 
-    pub fn shape_type(&self) -> ShapeType {
-        self.shape_type
+    pub fn with_transform(mut self, transform: Matrix4x4) -> Self {
+        self.transform = transform;
+        self.transformation_inverse = transform.inverse().unwrap();
+        self
+    }
+
+    pub fn with_material(mut self, material: Material) -> Self {
+        self.material = material;
+        self
+    }
+
+    /// Give the shape its place in a world
+    pub(crate) fn with_ids(mut self, ids: ShapeIds) -> Self {
+        self.ids = Some(ids);
+        self
+    }
+
+    /// Where the shape is in its world, if it's been put in one
+    pub fn ids(&self) -> Option<ShapeIds> {
+        self.ids
+    }
+
+    pub fn shape_type(&self) -> &ShapeType {
+        &self.shape_type
+    }
+
+    pub fn geometry(&self) -> &dyn Geometry {
+        self.shape_type.geometry()
     }
 
     pub fn transformation(&self) -> Matrix4x4 {
@@ -73,22 +254,79 @@ impl Shape {
         self.transformation_inverse
     }
 
-    pub fn material(&self) -> Material {
-        self.material
+    pub fn material(&self) -> &Material {
+        &self.material
+    }
+
+    /// The distances along a world space ray where it hits the shape
+    pub fn intersect_distances(&self, ray: &Ray) -> Vec<f64> {
+        let local_ray = ray.transform(self.transformation_inverse);
+        let geometry = self.geometry();
+        if !geometry.bounds().intersects(&local_ray) {
+            return vec![];
+        }
+        geometry.local_intersect(&local_ray)
+    }
+
+    /// A box in world space which contains the shape
+    pub fn bounds(&self) -> Bounds {
+        self.geometry().bounds().transform(self.transform)
     }
 
     pub fn normal_at(&self, p: Point) -> Vector {
         let inverse = self.transformation_inverse();
         let object_point = inverse * p;
 
-        // Find the local normal for each shape type
-        let object_normal = match self.shape_type {
-            ShapeType::Sphere => object_point - Point::new(0.0, 0.0, 0.0),
-            ShapeType::Plane => Vector::new(0.0, 1.0, 0.0),
-        };
+        let object_normal = self.geometry().local_normal_at(object_point);
 
         let mut world_normal = inverse.transpose() * object_normal;
         world_normal.set_w(0.0);
         world_normal.normalize()
     }
+
+    /*
+        Pick a point on the surface from 2 uniform random numbers. Returns the point,
+        the outward normal & the probability density of picking that point per unit
+        of world space area. Planes are infinite so can't be sampled.
+    */
+    pub fn sample_surface(&self, u: f64, v: f64) -> Option<(Point, Vector, f64)> {
+        let (object_point, object_normal, pdf) = self.geometry().sample_local_surface(u, v)?;
+
+        let mut world_normal = self.transformation_inverse.transpose() * object_normal;
+        world_normal.set_w(0.0);
+
+        // How much the transform stretches a small patch of area at this point
+        let area_scale =
+            self.transform.determinant().abs() * world_normal.len() / object_normal.len();
+
+        Some((
+            self.transform * object_point,
+            world_normal.normalize(),
+            pdf / area_scale,
+        ))
+    }
+}
+
+impl From<Arc<dyn Geometry>> for Shape {
+    fn from(geometry: Arc<dyn Geometry>) -> Self {
+        Shape::custom(geometry, Matrix4x4::identity(), Material::default())
+    }
+}
+
+impl From<Box<dyn Geometry>> for Shape {
+    fn from(geometry: Box<dyn Geometry>) -> Self {
+        Shape::from(Arc::<dyn Geometry>::from(geometry))
+    }
+}
+
+impl<G: Geometry + 'static> From<Arc<G>> for Shape {
+    fn from(geometry: Arc<G>) -> Self {
+        Shape::from(geometry as Arc<dyn Geometry>)
+    }
+}
+
+impl<G: Geometry + 'static> From<Box<G>> for Shape {
+    fn from(geometry: Box<G>) -> Self {
+        Shape::from(geometry as Box<dyn Geometry>)
+    }
 }
@@ -1,6 +1,7 @@
-use super::{Material, Shape};
-use crate::{Matrix4x4, Point, Vector};
+use super::{Bounds, Geometry, Material, Ray, Shape, World};
+use crate::{image::Color, Matrix4x4, Point, Vector};
 use std::f64::consts::PI;
+use std::sync::Arc;
 
 fn round(v: f64) -> f64 {
     const SIG_FIGS: f64 = 100000.0;
@@ -31,6 +32,8 @@ fn the_normal_is_a_normalized_vector() {
 }
 
 #[test]
+// The book rounds the expected values to 5 places
+#[allow(clippy::approx_constant)]
 fn computing_the_normal_on_a_translated_sphere() {
     let s = Shape::sphere_from_transformation(Matrix4x4::translation(0.0, 1.0, 0.0));
     let n = s.normal_at(Point::new(0.0, 1.70711, -0.70711));
@@ -58,15 +61,17 @@ fn computing_the_normal_on_a_transformed_sphere() {
 #[test]
 fn a_sphere_has_a_default_material() {
     let s = Shape::default();
-    assert_eq!(s.material(), Material::default());
+    assert_eq!(*s.material(), Material::default());
 }
 
 #[test]
 fn a_sphere_may_be_assigned_a_material() {
-    let mut m = Material::default();
-    m.ambient = 1.0;
-    let s = Shape::sphere(Matrix4x4::identity(), m);
-    assert_eq!(s.material(), m);
+    let m = Material {
+        ambient: 1.0,
+        ..Material::default()
+    };
+    let s = Shape::sphere(Matrix4x4::identity(), m.clone());
+    assert_eq!(*s.material(), m);
 }
 
 #[test]
@@ -81,3 +86,117 @@ fn the_normal_of_a_plane_is_constant_everywhere() {
     assert_eq!(n2, Vector::new(0.0, 1.0, 0.0));
     assert_eq!(n3, Vector::new(0.0, 1.0, 0.0));
 }
+
+#[test]
+fn sampling_the_surface_of_a_scaled_sphere() {
+    let s = Shape::sphere_from_transformation(Matrix4x4::scaling(2.0, 2.0, 2.0));
+
+    let (point, normal, pdf) = s.sample_surface(0.25, 0.5).unwrap();
+
+    assert_eq!(round((point - Point::new(0.0, 0.0, 0.0)).len()), 2.0);
+    assert_eq!(normal, (point - Point::new(0.0, 0.0, 0.0)).normalize());
+    // The pdf is 1 / surface area for a uniformly scaled sphere
+    assert_eq!(round(pdf), round(1.0 / (16.0 * PI)));
+}
+
+#[test]
+fn a_plane_cannot_be_sampled() {
+    let p = Shape::plane_default();
+    assert!(p.sample_surface(0.5, 0.5).is_none());
+}
+
+/// A cube from -1 to 1 on every axis, as a shape from outside the crate would be written
+#[derive(Debug)]
+struct Cube;
+
+impl Geometry for Cube {
+    fn local_intersect(&self, ray: &Ray) -> Vec<f64> {
+        let axis = |origin: f64, direction: f64| {
+            let t1 = (-1.0 - origin) / direction;
+            let t2 = (1.0 - origin) / direction;
+            (t1.min(t2), t1.max(t2))
+        };
+        let (o, d) = (ray.origin(), ray.direction());
+        let (xmin, xmax) = axis(o.x(), d.x());
+        let (ymin, ymax) = axis(o.y(), d.y());
+        let (zmin, zmax) = axis(o.z(), d.z());
+
+        let tmin = xmin.max(ymin).max(zmin);
+        let tmax = xmax.min(ymax).min(zmax);
+        if tmin > tmax {
+            vec![]
+        } else {
+            vec![tmin, tmax]
+        }
+    }
+
+    fn local_normal_at(&self, point: Point) -> Vector {
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        let largest = x.abs().max(y.abs()).max(z.abs());
+        if largest == x.abs() {
+            Vector::new(x, 0.0, 0.0)
+        } else if largest == y.abs() {
+            Vector::new(0.0, y, 0.0)
+        } else {
+            Vector::new(0.0, 0.0, z)
+        }
+    }
+
+    fn bounds(&self) -> Bounds {
+        Bounds::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[test]
+fn a_custom_shape_is_transformed_like_the_built_in_ones() {
+    let cube = Shape::custom(
+        Arc::new(Cube),
+        Matrix4x4::translation(0.0, 0.0, 5.0),
+        Material::default(),
+    );
+    let ray = Ray::new(Point::new(0.5, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+
+    let xs = ray.intersects(&cube);
+
+    assert_eq!(xs.len(), 2);
+    assert_eq!(xs[0].t(), 4.0);
+    assert_eq!(xs[1].t(), 6.0);
+    assert_eq!(
+        cube.normal_at(Point::new(0.5, 0.0, 4.0)),
+        Vector::new(0.0, 0.0, -1.0)
+    );
+    assert_eq!(cube.bounds().min(), Point::new(-1.0, -1.0, 4.0));
+    // Custom shapes can't be used as area lights without a way to sample them
+    assert!(cube.sample_surface(0.5, 0.5).is_none());
+}
+
+#[test]
+fn custom_shapes_are_equal_when_they_share_geometry() {
+    let geometry: Arc<dyn Geometry> = Arc::new(Cube);
+    let a = Shape::from(geometry.clone());
+    let b = Shape::from(geometry);
+    let c = Shape::from(Arc::new(Cube));
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_ne!(a, Shape::default());
+}
+
+#[test]
+fn boxed_and_shared_shapes_can_be_added_to_a_world() {
+    let mut red = Material::default();
+    red.color = Color::red();
+
+    let w = World::new()
+        .add_object(Box::new(Cube))
+        .add_object(Shape::from(Arc::new(Cube)).with_material(red))
+        .generate();
+
+    assert_eq!(w.objects().len(), 2);
+    assert_eq!(w.objects()[1].material().color, Color::red());
+
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let xs = w.ray_intersects(ray);
+    assert_eq!(xs.len(), 4);
+    assert_eq!(xs[0].t(), 4.0);
+}
@@ -0,0 +1,179 @@
+use super::PointLight;
+use crate::{
+    image::Color,
+    maths::{Point, Vector},
+};
+use std::f64::consts::PI;
+
+/// How far away the sun light is placed. Far enough that its rays are parallel across a scene.
+const SUN_DISTANCE: f64 = 1.0e6;
+
+/// Converts the model's luminance in kcd/m² into scene units. A clear midday sky is just below 1.
+const DEFAULT_INTENSITY: f64 = 0.02;
+
+/// Coefficients of the Perez sky luminance distribution
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Perez {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    e: f64,
+}
+
+impl Perez {
+    fn distribution(&self, cos_theta: f64, gamma: f64) -> f64 {
+        (1.0 + self.a * (self.b / cos_theta).exp())
+            * (1.0 + self.c * (self.d * gamma).exp() + self.e * gamma.cos() * gamma.cos())
+    }
+}
+
+fn dot3(a: [f64; 4], b: [f64; 4]) -> f64 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+/// Analytic daylight sky from "A Practical Analytic Model for Daylight" by Preetham et al.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PreethamSky {
+    sun_direction: Vector,
+    turbidity: f64,
+    intensity: f64,
+    // Sky values straight up, in the CIE Yxy color space
+    zenith: (f64, f64, f64),
+    perez_y: Perez,
+    perez_x: Perez,
+    perez_chroma_y: Perez,
+}
+
+impl PreethamSky {
+    /// `sun_direction` points towards the sun. Turbidity ranges from 2 (very clear) to 10 (hazy).
+    pub fn new(sun_direction: Vector, turbidity: f64) -> Self {
+        let sun_direction = sun_direction.normalize();
+        let t = turbidity.clamp(2.0, 10.0);
+
+        // Keep the sun just above the horizon as the model breaks down below it
+        let theta_s = sun_direction.y().clamp(0.01, 1.0).acos();
+        let thetas = [theta_s.powi(3), theta_s.powi(2), theta_s, 1.0];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let zenith_x = t * t * dot3([0.00166, -0.00375, 0.00209, 0.0], thetas)
+            + t * dot3([-0.02903, 0.06377, -0.03202, 0.00394], thetas)
+            + dot3([0.11693, -0.21196, 0.06052, 0.25886], thetas);
+        let zenith_y = t * t * dot3([0.00275, -0.00610, 0.00317, 0.0], thetas)
+            + t * dot3([-0.04214, 0.08970, -0.04153, 0.00516], thetas)
+            + dot3([0.15346, -0.26756, 0.06670, 0.26688], thetas);
+
+        Self {
+            sun_direction,
+            turbidity: t,
+            intensity: DEFAULT_INTENSITY,
+            zenith: (zenith_luminance, zenith_x, zenith_y),
+            perez_y: Perez {
+                a: 0.1787 * t - 1.4630,
+                b: -0.3554 * t + 0.4275,
+                c: -0.0227 * t + 5.3251,
+                d: 0.1206 * t - 2.5771,
+                e: -0.0670 * t + 0.3703,
+            },
+            perez_x: Perez {
+                a: -0.0193 * t - 0.2592,
+                b: -0.0665 * t + 0.0008,
+                c: -0.0004 * t + 0.2125,
+                d: -0.0641 * t - 0.8989,
+                e: -0.0033 * t + 0.0452,
+            },
+            perez_chroma_y: Perez {
+                a: -0.0167 * t - 0.2608,
+                b: -0.0950 * t + 0.0092,
+                c: -0.0079 * t + 0.2102,
+                d: -0.0441 * t - 1.6537,
+                e: -0.0109 * t + 0.0529,
+            },
+        }
+    }
+
+    /// Scale the brightness of the sky & sun
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn sun_direction(&self) -> Vector {
+        self.sun_direction
+    }
+
+    pub fn turbidity(&self) -> f64 {
+        self.turbidity
+    }
+
+    pub fn intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    /// The luminance straight up in kcd/m², before the intensity is applied
+    pub fn zenith_luminance(&self) -> f64 {
+        self.zenith.0
+    }
+
+    pub fn color_in_direction(&self, direction: Vector) -> Color {
+        let direction = direction.normalize();
+
+        // The model only covers the upper hemisphere so the ground mirrors the sky
+        let cos_theta = direction.y().abs().max(0.01);
+        let cos_gamma = Vector::dot(direction, self.sun_direction).clamp(-1.0, 1.0);
+        let gamma = cos_gamma.acos();
+
+        let theta_s = self.sun_direction.y().clamp(0.01, 1.0).acos();
+        let relative =
+            |perez: &Perez| perez.distribution(cos_theta, gamma) / perez.distribution(1.0, theta_s);
+
+        let luminance = self.zenith.0 * relative(&self.perez_y) * self.intensity;
+        let x = self.zenith.1 * relative(&self.perez_x);
+        let y = self.zenith.2 * relative(&self.perez_chroma_y);
+
+        xyy_to_color(x, y, luminance)
+    }
+
+    /// A light matching the sun. It is far enough away that it acts as a directional light.
+    pub fn sun_light(&self) -> PointLight {
+        let origin = Point::new(0.0, 0.0, 0.0);
+        PointLight::new(origin + self.sun_direction * SUN_DISTANCE, self.sun_color())
+    }
+
+    /*
+        Sunlight after passing through the atmosphere. Rayleigh & aerosol scattering
+        remove more blue light the further the light travels, turning the sun red at
+        sunset.
+    */
+    fn sun_color(&self) -> Color {
+        let theta_s = self.sun_direction.y().clamp(0.0, 1.0).acos();
+        let relative_air_mass =
+            1.0 / (theta_s.cos() + 0.15 * (93.885 - theta_s.to_degrees()).powf(-1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+
+        let transmittance = |wavelength_um: f64| {
+            let rayleigh = (-0.008735 * wavelength_um.powf(-4.08) * relative_air_mass).exp();
+            let aerosol = (-beta * wavelength_um.powf(-1.3) * relative_air_mass).exp();
+            rayleigh * aerosol
+        };
+
+        // Sample the red, green & blue wavelengths. The sun has an intensity of 1 by default.
+        Color::new(
+            transmittance(0.65),
+            transmittance(0.57),
+            transmittance(0.475),
+        ) * (self.intensity / DEFAULT_INTENSITY)
+    }
+}
+
+/// Convert from the CIE xyY color space to linear sRGB
+fn xyy_to_color(x: f64, y: f64, luminance: f64) -> Color {
+    if y <= 0.0 {
+        return Color::black();
+    }
+    let big_x = x / y * luminance;
+    let big_z = (1.0 - x - y) / y * luminance;
+
+    Color::from_xyz(big_x, luminance, big_z)
+}
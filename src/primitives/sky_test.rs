@@ -0,0 +1,92 @@
+use super::{Background, PreethamSky, Ray, World};
+use crate::{
+    image::Color,
+    maths::{Point, Vector},
+};
+
+fn noon() -> PreethamSky {
+    PreethamSky::new(Vector::new(0.0, 1.0, 0.2), 3.0)
+}
+
+#[test]
+fn the_zenith_has_the_zenith_luminance() {
+    let sky = noon();
+    let up = sky.color_in_direction(Vector::new(0.0, 1.0, 0.0));
+    let expected = sky.zenith_luminance() * sky.intensity();
+    assert!((up.luminance() - expected).abs() / expected < 0.01);
+}
+
+#[test]
+fn the_sky_is_brighter_near_the_sun() {
+    let sky = PreethamSky::new(Vector::new(0.0, 0.5, 1.0), 3.0);
+    let towards = sky.color_in_direction(Vector::new(0.0, 0.4, 1.0));
+    let away = sky.color_in_direction(Vector::new(0.0, 0.4, -1.0));
+    assert!(towards.luminance() > away.luminance());
+}
+
+#[test]
+fn a_clear_sky_is_blue() {
+    let up = noon().color_in_direction(Vector::new(0.0, 1.0, 0.0));
+    assert!(up.b() > up.r());
+}
+
+#[test]
+fn a_hazy_sky_is_less_blue_than_a_clear_sky() {
+    let direction = Vector::new(0.0, 1.0, -0.5);
+    let clear = PreethamSky::new(Vector::new(0.0, 1.0, 0.2), 2.0).color_in_direction(direction);
+    let hazy = PreethamSky::new(Vector::new(0.0, 1.0, 0.2), 9.0).color_in_direction(direction);
+    assert!(hazy.b() / hazy.r() < clear.b() / clear.r());
+}
+
+#[test]
+fn the_sun_light_is_far_away_in_the_sun_direction() {
+    let sky = noon();
+    let light = sky.sun_light();
+    let towards_light = (light.position() - Point::new(0.0, 0.0, 0.0)).normalize();
+
+    assert_eq!(towards_light, sky.sun_direction());
+    assert!(light.position().y() > 1000.0);
+}
+
+#[test]
+fn the_sun_is_redder_at_sunset() {
+    let noon = noon().sun_light().intensity();
+    let sunset = PreethamSky::new(Vector::new(0.0, 0.05, 1.0), 3.0)
+        .sun_light()
+        .intensity();
+
+    assert!(sunset.b() / sunset.r() < noon.b() / noon.r());
+    assert!(sunset.luminance() < noon.luminance());
+}
+
+#[test]
+fn the_intensity_scales_the_sky_and_the_sun() {
+    let sky = noon();
+    let brighter = noon().with_intensity(sky.intensity() * 2.0);
+    let up = Vector::new(0.0, 1.0, 0.0);
+
+    assert_eq!(
+        brighter.color_in_direction(up),
+        sky.color_in_direction(up) * 2.0
+    );
+    assert_eq!(
+        brighter.sun_light().intensity(),
+        sky.sun_light().intensity() * 2.0
+    );
+}
+
+#[test]
+fn rays_which_miss_see_the_sky() {
+    let sky = noon();
+    let w = World::new()
+        .set_background(Background::Sky(sky))
+        .add_light(sky.sun_light())
+        .generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.3, 0.6, 0.2));
+
+    assert_eq!(
+        w.color_at(ray),
+        sky.color_in_direction(Vector::new(0.3, 0.6, 0.2))
+    );
+    assert_ne!(w.color_at(ray), Color::black());
+}
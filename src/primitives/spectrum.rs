@@ -0,0 +1,64 @@
+use crate::image::Color;
+use std::sync::OnceLock;
+
+/// The range of visible wavelengths in nanometres
+pub const MIN_WAVELENGTH: f64 = 380.0;
+pub const MAX_WAVELENGTH: f64 = 780.0;
+
+fn piecewise_gaussian(x: f64, mean: f64, below: f64, above: f64) -> f64 {
+    let spread = if x < mean { below } else { above };
+    let t = (x - mean) / spread;
+    (-0.5 * t * t).exp()
+}
+
+/// The CIE 1931 color matching functions using the multi-lobe fit by Wyman, Sloan & Shirley
+pub fn color_matching(wavelength: f64) -> (f64, f64, f64) {
+    let x = 1.056 * piecewise_gaussian(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * piecewise_gaussian(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * piecewise_gaussian(wavelength, 501.1, 20.4, 26.2);
+    let y = 0.821 * piecewise_gaussian(wavelength, 568.8, 46.9, 40.5)
+        + 0.286 * piecewise_gaussian(wavelength, 530.9, 16.3, 31.1);
+    let z = 1.217 * piecewise_gaussian(wavelength, 437.0, 11.8, 36.0)
+        + 0.681 * piecewise_gaussian(wavelength, 459.0, 26.0, 13.8);
+    (x, y, z)
+}
+
+/// The color of a single wavelength. Colors outside of sRGB are clamped.
+fn wavelength_color(wavelength: f64) -> Color {
+    let (x, y, z) = color_matching(wavelength);
+    let color = Color::from_xyz(x, y, z);
+    Color::new(color.r().max(0.0), color.g().max(0.0), color.b().max(0.0))
+}
+
+/// The average color of all the visible wavelengths
+fn average_color() -> Color {
+    static AVERAGE: OnceLock<Color> = OnceLock::new();
+    *AVERAGE.get_or_init(|| {
+        let steps = 4000;
+        let step = (MAX_WAVELENGTH - MIN_WAVELENGTH) / steps as f64;
+        (0..steps)
+            .map(|i| wavelength_color(MIN_WAVELENGTH + (i as f64 + 0.5) * step))
+            .fold(Color::black(), |total, col| total + col)
+            * (1.0 / steps as f64)
+    })
+}
+
+/// Pick a visible wavelength uniformly
+pub fn sample_wavelength(u: f64) -> f64 {
+    MIN_WAVELENGTH + u * (MAX_WAVELENGTH - MIN_WAVELENGTH)
+}
+
+/*
+    How much a wavelength picked by `sample_wavelength` adds to each channel.
+    This is scaled so that averaging over every wavelength gives white, which
+    keeps scenes without dispersion the same color as the RGB renderer.
+*/
+pub fn wavelength_weight(wavelength: f64) -> Color {
+    let color = wavelength_color(wavelength);
+    let average = average_color();
+    Color::new(
+        color.r() / average.r(),
+        color.g() / average.g(),
+        color.b() / average.b(),
+    )
+}
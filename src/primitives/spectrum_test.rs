@@ -0,0 +1,77 @@
+use super::{
+    spectrum::{color_matching, sample_wavelength, wavelength_weight},
+    Dispersion, Material,
+};
+use crate::image::Color;
+
+#[test]
+fn the_color_matching_functions_peak_at_the_right_wavelengths() {
+    let (_, y, _) = color_matching(555.0);
+    assert!((y - 1.0).abs() < 0.05);
+
+    let (x, _, _) = color_matching(600.0);
+    assert!(x > 1.0);
+
+    let (_, _, z) = color_matching(445.0);
+    assert!(z > 1.7);
+}
+
+#[test]
+fn wavelengths_have_the_colors_of_the_rainbow() {
+    let blue = wavelength_weight(450.0);
+    assert!(blue.b() > blue.g() && blue.b() > blue.r());
+
+    let green = wavelength_weight(530.0);
+    assert!(green.g() > green.r() && green.g() > green.b());
+
+    let red = wavelength_weight(650.0);
+    assert!(red.r() > red.g() && red.r() > red.b());
+}
+
+#[test]
+fn averaging_every_wavelength_gives_white() {
+    let steps = 1000;
+    let average = (0..steps)
+        .map(|i| wavelength_weight(sample_wavelength((i as f64 + 0.5) / steps as f64)))
+        .fold(Color::black(), |total, col| total + col)
+        * (1.0 / steps as f64);
+
+    assert!((average.r() - 1.0).abs() < 0.01, "{:?}", average);
+    assert!((average.g() - 1.0).abs() < 0.01, "{:?}", average);
+    assert!((average.b() - 1.0).abs() < 0.01, "{:?}", average);
+}
+
+#[test]
+fn the_refractive_index_of_bk7_glass() {
+    // Measured at the sodium D line
+    let n = Dispersion::bk7().refractive_index(587.6);
+    assert!((n - 1.5168).abs() < 1e-4, "{}", n);
+}
+
+#[test]
+fn blue_light_is_bent_more_than_red_light() {
+    let cauchy = Dispersion::Cauchy {
+        a: 1.5046,
+        b: 0.0042,
+    };
+    assert!((cauchy.refractive_index(1000.0) - 1.5088).abs() < 1e-9);
+    assert!(cauchy.refractive_index(450.0) > cauchy.refractive_index(650.0));
+
+    let bk7 = Dispersion::bk7();
+    assert!(bk7.refractive_index(450.0) > bk7.refractive_index(650.0));
+}
+
+#[test]
+fn materials_only_disperse_light_of_a_single_wavelength() {
+    let mut material = Material {
+        refractive_index: 1.5,
+        dispersion: Some(Dispersion::bk7()),
+        ..Material::default()
+    };
+
+    assert_eq!(material.refractive_index_at(None), 1.5);
+    assert!(material.refractive_index_at(Some(450.0)) > 1.52);
+
+    material.dispersion = None;
+    assert_eq!(material.refractive_index_at(Some(450.0)), 1.5);
+}
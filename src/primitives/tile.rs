@@ -0,0 +1,211 @@
+use std::str::FromStr;
+
+/// A rectangle of pixels rendered together
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tile {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Tile {
+    /// Each pixel in the tile, row by row
+    pub fn pixels(&self) -> impl Iterator<Item = (i32, i32)> {
+        let (x, y, width) = (self.x, self.y, self.width);
+        (y..y + self.height).flat_map(move |py| (x..x + width).map(move |px| (px, py)))
+    }
+
+    pub fn pixel_count(&self) -> usize {
+        (self.width * self.height) as usize
+    }
+}
+
+/// The order the tiles of an image are rendered in
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TileOrder {
+    /// Row by row from the top left, like reading
+    Scanline,
+    /// Spiralling out from the middle, where the interesting part of an image usually is
+    Spiral,
+    /// Along a Hilbert curve, so each tile is next to the one before
+    Hilbert,
+    /// Nearest a point first, such as where the mouse is. Spirals from the middle without one.
+    Cursor,
+}
+
+impl TileOrder {
+    pub const ALL: [TileOrder; 4] = [
+        TileOrder::Scanline,
+        TileOrder::Spiral,
+        TileOrder::Hilbert,
+        TileOrder::Cursor,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TileOrder::Scanline => "scanline",
+            TileOrder::Spiral => "spiral",
+            TileOrder::Hilbert => "hilbert",
+            TileOrder::Cursor => "cursor",
+        }
+    }
+}
+
+impl FromStr for TileOrder {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        TileOrder::ALL
+            .iter()
+            .find(|order| order.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = TileOrder::ALL.iter().map(|order| order.name()).collect();
+                format!(
+                    "unknown tile order `{}`. Expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/*
+    Splits an image into square tiles & decides which order to render them in. Tiles on
+    the right & bottom edges are cut down to fit the image.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TileScheduler {
+    width: i32,
+    height: i32,
+    tile_size: i32,
+    order: TileOrder,
+}
+
+impl TileScheduler {
+    pub fn new(width: i32, height: i32, tile_size: i32) -> Self {
+        Self {
+            width,
+            height,
+            tile_size: tile_size.max(1),
+            order: TileOrder::Scanline,
+        }
+    }
+
+    pub fn with_order(mut self, order: TileOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// How many tiles there are across & down the image
+    pub fn grid_size(&self) -> (i32, i32) {
+        let count = |size: i32| (size + self.tile_size - 1) / self.tile_size;
+        (count(self.width), count(self.height))
+    }
+
+    /// The tile in a column & row of the grid
+    pub fn tile_at(&self, column: i32, row: i32) -> Tile {
+        let (x, y) = (column * self.tile_size, row * self.tile_size);
+        Tile {
+            x,
+            y,
+            width: self.tile_size.min(self.width - x),
+            height: self.tile_size.min(self.height - y),
+        }
+    }
+
+    /// Every tile in the image, in the scheduler's order
+    pub fn tiles(&self) -> Vec<Tile> {
+        let (columns, rows) = self.grid_size();
+        let cells = match self.order {
+            TileOrder::Scanline => (0..rows)
+                .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                .collect(),
+            TileOrder::Spiral | TileOrder::Cursor => spiral(columns, rows),
+            TileOrder::Hilbert => {
+                let mut cells: Vec<(i32, i32)> = (0..rows)
+                    .flat_map(|row| (0..columns).map(move |column| (column, row)))
+                    .collect();
+                let side = (columns.max(rows).max(1) as u32).next_power_of_two() as i32;
+                cells.sort_by_key(|&(column, row)| hilbert_index(side, column, row));
+                cells
+            }
+        };
+        cells
+            .into_iter()
+            .map(|(column, row)| self.tile_at(column, row))
+            .collect()
+    }
+
+    /// Every tile in the image, highest priority first. Ties keep the scheduler's order.
+    pub fn tiles_by_priority<F: Fn(&Tile) -> f64>(&self, priority: F) -> Vec<Tile> {
+        let mut tiles: Vec<(f64, Tile)> = self
+            .tiles()
+            .into_iter()
+            .map(|tile| (priority(&tile), tile))
+            .collect();
+        tiles.sort_by(|a, b| b.0.total_cmp(&a.0));
+        tiles.into_iter().map(|(_, tile)| tile).collect()
+    }
+
+    /// Every tile in the image, nearest a pixel first
+    pub fn tiles_nearest(&self, x: f64, y: f64) -> Vec<Tile> {
+        self.tiles_by_priority(|tile| {
+            let dx = tile.x as f64 + tile.width as f64 / 2.0 - x;
+            let dy = tile.y as f64 + tile.height as f64 / 2.0 - y;
+            -(dx * dx + dy * dy)
+        })
+    }
+}
+
+/// The cells of a grid in a square spiral out from the middle, clockwise
+fn spiral(columns: i32, rows: i32) -> Vec<(i32, i32)> {
+    let total = (columns * rows).max(0) as usize;
+    let mut cells = Vec::with_capacity(total);
+    let (mut column, mut row) = ((columns - 1) / 2, (rows - 1) / 2);
+    let directions = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+    let mut step = 1;
+    let mut direction = 0;
+
+    while cells.len() < total {
+        // Each length is walked twice before it grows: right 1, down 1, left 2, up 2...
+        for _ in 0..2 {
+            let (dx, dy) = directions[direction % 4];
+            for _ in 0..step {
+                if (0..columns).contains(&column) && (0..rows).contains(&row) {
+                    cells.push((column, row));
+                }
+                column += dx;
+                row += dy;
+            }
+            direction += 1;
+        }
+        step += 1;
+    }
+    cells.truncate(total);
+    cells
+}
+
+/// The distance along a Hilbert curve filling a square with a power of two side
+fn hilbert_index(side: i32, x: i32, y: i32) -> i64 {
+    let (mut x, mut y) = (x as i64, y as i64);
+    let mut index = 0;
+    let mut s = side as i64 / 2;
+    while s > 0 {
+        let rx = ((x & s) > 0) as i64;
+        let ry = ((y & s) > 0) as i64;
+        index += s * s * ((3 * rx) ^ ry);
+
+        // Rotate the quadrant so the curve joins up
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
@@ -0,0 +1,143 @@
+use super::{Camera, Tile, TileOrder, TileScheduler, World};
+use crate::maths::{Matrix4x4, Point, Vector};
+
+fn positions(tiles: &[Tile]) -> Vec<(i32, i32)> {
+    tiles.iter().map(|tile| (tile.x, tile.y)).collect()
+}
+
+#[test]
+fn tile_orders_can_be_named() {
+    for order in TileOrder::ALL {
+        assert_eq!(order.name().parse::<TileOrder>(), Ok(order));
+    }
+    assert!("random".parse::<TileOrder>().is_err());
+}
+
+#[test]
+fn tiles_on_the_edges_are_cut_down_to_fit() {
+    let scheduler = TileScheduler::new(10, 5, 4);
+    let tiles = scheduler.tiles();
+
+    assert_eq!(scheduler.grid_size(), (3, 2));
+    assert_eq!(tiles.len(), 6);
+    assert_eq!(
+        tiles[5],
+        Tile {
+            x: 8,
+            y: 4,
+            width: 2,
+            height: 1
+        }
+    );
+    let pixels: usize = tiles.iter().map(Tile::pixel_count).sum();
+    assert_eq!(pixels, 50);
+}
+
+#[test]
+fn the_pixels_of_a_tile_are_in_rows() {
+    let tile = Tile {
+        x: 2,
+        y: 3,
+        width: 2,
+        height: 2,
+    };
+    let pixels: Vec<(i32, i32)> = tile.pixels().collect();
+    assert_eq!(pixels, vec![(2, 3), (3, 3), (2, 4), (3, 4)]);
+}
+
+#[test]
+fn scanline_tiles_go_across_then_down() {
+    let tiles = TileScheduler::new(4, 4, 2).tiles();
+    assert_eq!(positions(&tiles), vec![(0, 0), (2, 0), (0, 2), (2, 2)]);
+}
+
+#[test]
+fn spiral_tiles_start_in_the_middle() {
+    let tiles = TileScheduler::new(9, 9, 3)
+        .with_order(TileOrder::Spiral)
+        .tiles();
+
+    assert_eq!(
+        positions(&tiles),
+        vec![
+            (3, 3),
+            (6, 3),
+            (6, 6),
+            (3, 6),
+            (0, 6),
+            (0, 3),
+            (0, 0),
+            (3, 0),
+            (6, 0)
+        ]
+    );
+}
+
+#[test]
+fn every_order_covers_every_tile_once() {
+    for order in TileOrder::ALL {
+        let mut tiles = positions(&TileScheduler::new(70, 30, 8).with_order(order).tiles());
+        tiles.sort();
+        tiles.dedup();
+        assert_eq!(tiles.len(), 9 * 4, "{}", order.name());
+    }
+}
+
+#[test]
+fn hilbert_tiles_are_next_to_the_one_before() {
+    let tiles = TileScheduler::new(8, 8, 1)
+        .with_order(TileOrder::Hilbert)
+        .tiles();
+
+    assert_eq!(tiles[0], TileScheduler::new(8, 8, 1).tile_at(0, 0));
+    for pair in tiles.windows(2) {
+        let step = (pair[0].x - pair[1].x).abs() + (pair[0].y - pair[1].y).abs();
+        assert_eq!(step, 1);
+    }
+}
+
+#[test]
+fn tiles_can_be_given_a_priority() {
+    // Render the bottom row first, keeping the scanline order within it
+    let tiles = TileScheduler::new(4, 4, 2).tiles_by_priority(|tile| tile.y as f64);
+    assert_eq!(positions(&tiles), vec![(0, 2), (2, 2), (0, 0), (2, 0)]);
+}
+
+#[test]
+fn tiles_can_be_rendered_nearest_a_pixel_first() {
+    let tiles = TileScheduler::new(6, 6, 2).tiles_nearest(5.0, 0.5);
+    assert_eq!(tiles[0], TileScheduler::new(6, 6, 2).tile_at(2, 0));
+    assert_eq!(positions(&tiles[1..3]), vec![(2, 0), (4, 2)]);
+    assert_eq!(
+        tiles.last(),
+        Some(&TileScheduler::new(6, 6, 2).tile_at(0, 2))
+    );
+}
+
+#[test]
+fn a_tile_renders_the_same_pixels_as_the_whole_image() {
+    let w = World::default().generate();
+    let c = Camera::new(
+        11,
+        11,
+        std::f64::consts::FRAC_PI_2,
+        Matrix4x4::view(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::up(),
+        ),
+    );
+    let tile = Tile {
+        x: 4,
+        y: 5,
+        width: 3,
+        height: 2,
+    };
+
+    let pixels = c.render_tile(&w, tile);
+    let image = c.render(&w);
+
+    assert_eq!((pixels.width(), pixels.height()), (3, 2));
+    assert_eq!(pixels.pixel_at(1, 0), image.pixel_at(5, 5));
+    assert_eq!(pixels.pixel_at(2, 1), image.pixel_at(6, 6));
+}
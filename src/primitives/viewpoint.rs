@@ -0,0 +1,118 @@
+use super::Camera;
+use crate::maths::{Matrix4x4, Point, Vector};
+
+/// Stop orbiting just short of looking straight up or down, where the view flips over
+const MAX_ELEVATION: f64 = 89.0 * std::f64::consts::PI / 180.0;
+/// How close zooming can get to the point being looked at
+const MIN_DISTANCE: f64 = 0.01;
+
+/*
+    Where a camera looks from & what it looks at, for moving it around interactively.
+    Orbiting & zooming move around the point being looked at while panning & flying
+    move both points together. Orbiting swings around the world's +y axis, while the
+    view keeps its own up so cameras looking straight down or rolled over still work.
+*/
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewpoint {
+    pub from: Point,
+    pub to: Point,
+    /// The top of the image, always at right angles to the way the view faces
+    pub up: Vector,
+}
+
+impl Viewpoint {
+    /// A view with the world's up at the top, which it mustn't be looking along
+    pub fn new(from: Point, to: Point) -> Self {
+        let mut view = Self {
+            from,
+            to,
+            up: Vector::up(),
+        };
+        view.level_up(Vector::up());
+        view
+    }
+
+    /// Work out where a camera is looking, treating the point `distance` in front of it as the target
+    pub fn from_camera(camera: &Camera, distance: f64) -> Self {
+        let inverse = camera.transform().inverse().unwrap();
+        let from = inverse * Point::new(0.0, 0.0, 0.0);
+        let forward = (inverse * Vector::new(0.0, 0.0, -1.0)).normalize();
+        let up = (inverse * Vector::up()).normalize();
+        Self {
+            from,
+            to: from + forward * distance,
+            up,
+        }
+    }
+
+    pub fn transform(&self) -> Matrix4x4 {
+        Matrix4x4::view(self.from, self.to, self.up)
+    }
+
+    pub fn distance(&self) -> f64 {
+        (self.to - self.from).len()
+    }
+
+    pub fn forward(&self) -> Vector {
+        (self.to - self.from).normalize()
+    }
+
+    /// The direction to the right of the image
+    pub fn right(&self) -> Vector {
+        Vector::cross(self.up, self.forward()).normalize()
+    }
+
+    /// Swing around the target. Positive yaw moves the camera to its right & positive pitch raises it.
+    pub fn orbit(&mut self, yaw: f64, pitch: f64) {
+        let offset = self.from - self.to;
+        let distance = offset.len();
+        let azimuth = offset.z().atan2(offset.x()) + yaw;
+        let elevation = ((offset.y() / distance).clamp(-1.0, 1.0).asin() + pitch)
+            .clamp(-MAX_ELEVATION, MAX_ELEVATION);
+
+        let offset = Vector::new(
+            distance * elevation.cos() * azimuth.cos(),
+            distance * elevation.sin(),
+            distance * elevation.cos() * azimuth.sin(),
+        );
+        self.from = self.to + offset;
+        self.level_up(self.up);
+    }
+
+    /*
+        Point `up` at right angles to the way the view now faces. When it's too close to
+        the view's direction to do that the world's up is used, which orbiting never
+        looks along.
+    */
+    fn level_up(&mut self, up: Vector) {
+        let forward = self.forward();
+        let candidates = [up, Vector::up(), Vector::new(0.0, 0.0, 1.0)];
+        for candidate in candidates {
+            let level = candidate - forward * Vector::dot(candidate, forward);
+            if level.len() > 1e-6 {
+                self.up = level.normalize();
+                return;
+            }
+        }
+    }
+
+    /// Slide the view sideways & up without turning it
+    pub fn pan(&mut self, right: f64, up: f64) {
+        let offset = self.right() * right + self.up * up;
+        self.from = self.from + offset;
+        self.to = self.to + offset;
+    }
+
+    /// Move towards the target, scaling the distance to it by `factor`
+    pub fn zoom(&mut self, factor: f64) {
+        let distance = (self.distance() * factor).max(MIN_DISTANCE);
+        self.from = self.to - self.forward() * distance;
+    }
+
+    /// Move forwards, right & up through the scene, taking the target along
+    pub fn fly(&mut self, forward: f64, right: f64, up: f64) {
+        let offset = self.forward() * forward + self.right() * right + Vector::up() * up;
+        self.from = self.from + offset;
+        self.to = self.to + offset;
+    }
+}
@@ -0,0 +1,128 @@
+use super::{Camera, Viewpoint};
+use crate::maths::{Matrix4x4, Point, Vector};
+use std::f64::consts::{FRAC_PI_2, PI};
+
+fn round(p: Point) -> Point {
+    let r = |v: f64| (v * 100000.0).round() / 100000.0;
+    Point::new(r(p.x()), r(p.y()), r(p.z()))
+}
+
+fn looking_forward() -> Viewpoint {
+    Viewpoint::new(Point::new(0.0, 0.0, -5.0), Point::new(0.0, 0.0, 0.0))
+}
+
+#[test]
+fn a_viewpoint_can_be_found_from_a_camera() {
+    let transform = Matrix4x4::view(
+        Point::new(1.0, 2.0, -5.0),
+        Point::new(1.0, 2.0, 0.0),
+        Vector::up(),
+    );
+    let camera = Camera::new(10, 10, FRAC_PI_2, transform);
+
+    let view = Viewpoint::from_camera(&camera, 3.0);
+
+    assert_eq!(round(view.from), Point::new(1.0, 2.0, -5.0));
+    assert_eq!(round(view.to), Point::new(1.0, 2.0, -2.0));
+    assert_eq!(view.transform(), transform);
+}
+
+#[test]
+fn right_is_the_right_of_the_rendered_image() {
+    let view = looking_forward();
+    let camera = Camera::new(11, 11, FRAC_PI_2, view.transform());
+
+    assert_eq!(view.right(), Vector::new(1.0, 0.0, 0.0));
+    assert!(camera.ray_for_pixel(10, 5).direction().x() > 0.0);
+}
+
+#[test]
+fn orbiting_keeps_the_distance_to_the_target() {
+    let mut view = looking_forward();
+
+    view.orbit(FRAC_PI_2, 0.0);
+    assert_eq!(round(view.from), Point::new(5.0, 0.0, 0.0));
+    assert_eq!(view.to, Point::new(0.0, 0.0, 0.0));
+
+    view.orbit(0.0, PI);
+    assert!((view.distance() - 5.0).abs() < 1e-9);
+    // It stops short of looking straight down
+    assert!(view.from.y() < 5.0);
+    assert!(view.from.y() > 4.99);
+}
+
+#[test]
+fn panning_moves_the_target_too() {
+    let mut view = looking_forward();
+
+    view.pan(1.0, 2.0);
+
+    assert_eq!(round(view.from), Point::new(1.0, 2.0, -5.0));
+    assert_eq!(round(view.to), Point::new(1.0, 2.0, 0.0));
+}
+
+#[test]
+fn zooming_scales_the_distance_to_the_target() {
+    let mut view = looking_forward();
+
+    view.zoom(0.5);
+    assert_eq!(round(view.from), Point::new(0.0, 0.0, -2.5));
+
+    view.zoom(0.0);
+    assert!(view.distance() > 0.0);
+}
+
+#[test]
+fn flying_moves_relative_to_the_view() {
+    let mut view = looking_forward();
+
+    view.fly(1.0, 2.0, 3.0);
+
+    assert_eq!(round(view.from), Point::new(2.0, 3.0, -4.0));
+    assert_eq!(round(view.to), Point::new(2.0, 3.0, 1.0));
+}
+
+#[test]
+fn a_camera_looking_straight_down_can_be_moved() {
+    let transform = Matrix4x4::view(
+        Point::new(0.0, 10.0, 0.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(0.0, 0.0, 1.0),
+    );
+    let camera = Camera::new(10, 10, FRAC_PI_2, transform);
+    let mut view = Viewpoint::from_camera(&camera, 10.0);
+    assert_eq!(view.up, Vector::new(0.0, 0.0, 1.0));
+    assert_eq!(view.transform(), transform);
+
+    view.zoom(0.9);
+    view.fly(1.0, 1.0, 1.0);
+    view.pan(1.0, 1.0);
+    let _ = Camera::new(10, 10, FRAC_PI_2, view.transform());
+
+    // Orbiting moves off the pole & keeps the top of the image the same way round
+    view.orbit(0.1, 0.1);
+    assert!(view.from.y() > view.to.y());
+    assert!(view.up.z() > 0.9);
+    let _ = Camera::new(10, 10, FRAC_PI_2, view.transform());
+
+    // A view made from points straight above the target also has a usable up
+    let view = Viewpoint::new(Point::new(0.0, 10.0, 0.0), Point::new(0.0, 0.0, 0.0));
+    let _ = Camera::new(10, 10, FRAC_PI_2, view.transform());
+}
+
+#[test]
+fn a_rolled_camera_keeps_its_roll() {
+    let transform = Matrix4x4::view(
+        Point::new(0.0, 0.0, -5.0),
+        Point::new(0.0, 0.0, 0.0),
+        Vector::new(1.0, 1.0, 0.0),
+    );
+    let camera = Camera::new(10, 10, FRAC_PI_2, transform);
+
+    let mut view = Viewpoint::from_camera(&camera, 5.0);
+    view.zoom(0.5);
+
+    let up = view.up;
+    assert!((up.x() - up.y()).abs() < 1e-9 && up.x() > 0.0);
+    assert_eq!(round(view.from), Point::new(0.0, 0.0, -2.5));
+}
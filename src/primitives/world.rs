@@ -1,15 +1,70 @@
-use super::{Intersection, IntersectionStats, Material, PointLight, Ray, Shape};
+use super::{
+    aov::SurfaceInfo,
+    spectrum::{sample_wavelength, wavelength_weight},
+    Background, Fog, Inspection, Integrator, Intersection, IntersectionStats, Material, Medium,
+    PointLight, Ray, Shape, ShapeIds, Tracer,
+};
 use crate::{
     image::Color,
     maths::{Matrix4x4, Point, Vector},
 };
+use rand::Rng;
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// How many volume boundaries a ray may pass through before giving up
+const MAX_VOLUME_CROSSINGS: u32 = 16;
+
+/// How many reflected, refracted or volume rays the Whitted integrator traces from each camera ray
+const MAX_RECURSION: u32 = 16;
+
+/// How many points along a ray through a volume are lit by the Whitted integrator
+const MARCH_STEPS: u32 = 32;
+
+/// Shadow rays towards the environment stop this far away
+const FAR_AWAY: f64 = 1.0e6;
+
+/// Gives every world its own id so rays counted on a thread are added to the right one
+static NEXT_WORLD_ID: AtomicU64 = AtomicU64::new(0);
+
+thread_local! {
+    /*
+        The world this thread last cast rays in & how many it hasn't added to that world's
+        total yet. Counting here keeps the threads from fighting over the total for every ray.
+    */
+    static UNCOUNTED_RAYS: Cell<(u64, u64)> = const { Cell::new((u64::MAX, 0)) };
+}
 
 pub struct WorldImpl {
     lights: Vec<PointLight>,
     objects: Vec<Shape>,
+    emitters: Vec<Shape>,
+    integrator: Integrator,
+    background: Background,
+    fog: Option<Fog>,
+    id: u64,
+    // Counts every ray tested against the objects, including shadow rays
+    rays_cast: AtomicU64,
 }
 
 impl WorldImpl {
+    pub fn integrator(&self) -> Integrator {
+        self.integrator
+    }
+
+    pub fn background(&self) -> &Background {
+        &self.background
+    }
+
+    pub fn fog(&self) -> Option<Fog> {
+        self.fog
+    }
+
+    /// The emissive shapes which can be sampled as area lights
+    pub fn emitters(&self) -> Vec<Shape> {
+        self.emitters.clone()
+    }
+
     pub fn lights(&self) -> Vec<PointLight> {
         self.lights.clone()
     }
@@ -18,65 +73,732 @@ impl WorldImpl {
         self.objects.clone()
     }
 
+    /// How many rays have been tested against the objects in the world & added with `count_rays`
+    pub fn rays_cast(&self) -> u64 {
+        self.rays_cast.load(Ordering::Relaxed)
+    }
+
+    /// Add the rays this thread has cast in the world to its total. Renderers call this after each tile.
+    pub fn count_rays(&self) {
+        let (world, rays) = UNCOUNTED_RAYS.with(|uncounted| uncounted.get());
+        if world == self.id && rays > 0 {
+            UNCOUNTED_RAYS.with(|uncounted| uncounted.set((self.id, 0)));
+            self.rays_cast.fetch_add(rays, Ordering::Relaxed);
+        }
+    }
+
     pub fn ray_intersects(&self, ray: Ray) -> Vec<Intersection> {
+        UNCOUNTED_RAYS.with(|uncounted| {
+            // Rays left over from another world are dropped rather than counted in this one
+            let (world, rays) = uncounted.get();
+            let rays = if world == self.id { rays + 1 } else { 1 };
+            uncounted.set((self.id, rays));
+        });
         let mut f: Vec<Intersection> = self
             .objects
             .iter()
-            .map(|obj| ray.intersects(*obj))
-            .flatten()
+            .flat_map(|obj| ray.intersects(obj))
             .collect();
         f.sort_unstable();
         f
     }
 
+    /// The first hit along a ray, skipping the invisible boundaries of volumes
+    fn solid_hit(&self, ray: Ray) -> Option<Intersection> {
+        solid_hit_in(&self.ray_intersects(ray))
+    }
+
+    /// Find out everything about the first thing a ray hits, including volume boundaries
+    pub fn inspect(&self, ray: Ray) -> Option<Inspection> {
+        let xs = self.ray_intersects(ray);
+        let hit = Intersection::hit(xs.clone())?;
+        let comps = hit.prepare_computations_in(ray, &xs);
+        let object = comps.object();
+
+        Some(Inspection {
+            object: object.clone(),
+            object_id: object.ids()?.object,
+            t: hit.t(),
+            point: comps.point(),
+            normal: comps.normalv(),
+            inside: comps.inside(),
+            lights: self
+                .lights
+                .iter()
+                .map(|&light| {
+                    let blocked = self.is_occluded(comps.over_point(), light.position());
+                    (light, blocked)
+                })
+                .collect(),
+            emitters: self
+                .emitters
+                .iter()
+                .map(|emitter| {
+                    let blocked = !self.can_see(comps.over_point(), emitter);
+                    (emitter.clone(), blocked)
+                })
+                .collect(),
+        })
+    }
+
+    /// Whether the middle of an emitter's surface can be seen from a point without anything else in the way
+    fn can_see(&self, point: Point, emitter: &Shape) -> bool {
+        let target = match emitter.sample_surface(0.5, 0.5) {
+            Some((target, _, _)) => target,
+            None => return false,
+        };
+        let ray = Ray::new(point, (target - point).normalize());
+        self.solid_hit(ray)
+            .is_some_and(|hit| hit.object().ids() == emitter.ids())
+    }
+
+    /// Find what a ray hits first for the AOVs. Volumes are looked through.
+    pub fn surface_info_at(&self, ray: Ray) -> SurfaceInfo {
+        self.surface_info_in(ray, &self.ray_intersects(ray))
+    }
+
+    /// Find what's hit first for the AOVs from the intersections already found along a ray
+    fn surface_info_in(&self, ray: Ray, intersections: &[Intersection]) -> SurfaceInfo {
+        let hit = match solid_hit_in(intersections) {
+            Some(hit) => hit,
+            None => return SurfaceInfo::miss(),
+        };
+        let comps = hit.prepare_computations(ray);
+        let object = comps.object();
+
+        let lit = self
+            .lights
+            .iter()
+            .filter(|light| !self.is_occluded(comps.over_point(), light.position()))
+            .count();
+        let shadow = if self.lights.is_empty() {
+            1.0
+        } else {
+            lit as f64 / self.lights.len() as f64
+        };
+
+        SurfaceInfo {
+            depth: hit.t() * ray.direction().len(),
+            normal: comps.normalv(),
+            albedo: object.material().color_at(&comps.point()),
+            shadow,
+            object_id: object.ids().map(|ids| ids.object),
+            material_id: object.ids().map(|ids| ids.material),
+        }
+    }
+
     pub fn shade_hit(&self, comps: IntersectionStats) -> Color {
-        let in_shadow = self.is_shadowed(comps.over_point());
-        comps.object().material().lighting(
-            &self.lights,
-            comps.over_point(),
-            comps.eyev(),
-            comps.normalv(),
-            in_shadow,
-        )
+        self.shade_hit_within(comps, MAX_RECURSION)
+    }
+
+    /// Shade a hit allowing up to `remaining` more rays to be traced for reflection & refraction
+    fn shade_hit_within(&self, comps: IntersectionStats, remaining: u32) -> Color {
+        let material = comps.object().material();
+        let tracer = WhittedTracer {
+            world: self,
+            remaining,
+        };
+        let surface = material.shader().shade(&comps, &self.lights, &tracer);
+
+        let reflected = self.reflected_color(&comps, remaining);
+        let refracted = self.refracted_color(&comps, remaining);
+
+        if material.reflective > 0.0 && material.transparency > 0.0 {
+            let reflectance = comps.schlick();
+            surface + reflected * reflectance + refracted * (1.0 - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
+    }
+
+    pub fn reflected_color(&self, comps: &IntersectionStats, remaining: u32) -> Color {
+        let material = comps.object().material();
+        if remaining == 0 || material.reflective == 0.0 {
+            return Color::black();
+        }
+
+        let ray = Ray::new(comps.over_point(), comps.reflectv());
+        self.secondary_color_at(comps, ray, comps.inside(), remaining - 1) * material.reflective
+    }
+
+    pub fn refracted_color(&self, comps: &IntersectionStats, remaining: u32) -> Color {
+        let material = comps.object().material();
+        if remaining == 0 || material.transparency == 0.0 {
+            return Color::black();
+        }
+
+        let direction = match comps.refracted_direction() {
+            Some(direction) => direction,
+            None => return Color::black(),
+        };
+
+        let ray = Ray::new(comps.under_point(), direction);
+        self.secondary_color_at(comps, ray, !comps.inside(), remaining - 1) * material.transparency
+    }
+
+    /// The color along a ray leaving a surface, dimmed by the material if the ray travels through it
+    fn secondary_color_at(
+        &self,
+        comps: &IntersectionStats,
+        ray: Ray,
+        through_object: bool,
+        remaining: u32,
+    ) -> Color {
+        let color = self.direct_color_at(ray, remaining, None);
+        let material = comps.object().material();
+        if !through_object || material.absorption == Color::black() {
+            return color;
+        }
+
+        match Intersection::hit(self.ray_intersects(ray)) {
+            Some(hit) => color * material.transmittance(hit.t() * ray.direction().len()),
+            None => color,
+        }
     }
 
     pub fn color_at(&self, ray: Ray) -> Color {
+        self.sample_color_at(ray, &mut rand::thread_rng())
+    }
+
+    /// Find the color along a ray & what it hits first for the AOVs
+    pub fn color_and_surface_at(&self, ray: Ray) -> (Color, SurfaceInfo) {
+        self.sample_color_and_surface_at(ray, &mut rand::thread_rng())
+    }
+
+    /// Find the color along a ray, taking any random numbers the integrator needs from `rng`
+    pub fn sample_color_at<R: Rng>(&self, ray: Ray, rng: &mut R) -> Color {
+        self.trace(ray, rng, None)
+    }
+
+    /// Find a sample of the color along a ray along with what the ray hits first for the AOVs
+    pub fn sample_color_and_surface_at<R: Rng>(
+        &self,
+        ray: Ray,
+        rng: &mut R,
+    ) -> (Color, SurfaceInfo) {
+        let mut surface = SurfaceInfo::miss();
+        let color = self.trace(ray, rng, Some(&mut surface));
+        (color, surface)
+    }
+
+    /// Find the color along a ray, filling in `surface` from the first hit if it's given
+    fn trace<R: Rng>(&self, ray: Ray, rng: &mut R, mut surface: Option<&mut SurfaceInfo>) -> Color {
+        let color = match self.integrator {
+            Integrator::Whitted => self.direct_color_at(ray, MAX_RECURSION, surface.as_deref_mut()),
+            Integrator::PathTracer {
+                max_depth,
+                spectral: true,
+                ..
+            } => {
+                let wavelength = sample_wavelength(rng.gen());
+                self.trace_path(
+                    ray,
+                    max_depth,
+                    Some(wavelength),
+                    rng,
+                    surface.as_deref_mut(),
+                ) * wavelength_weight(wavelength)
+            }
+            Integrator::PathTracer { max_depth, .. } => {
+                self.trace_path(ray, max_depth, None, rng, surface.as_deref_mut())
+            }
+        };
+
+        match self.fog {
+            Some(fog) => {
+                // The surface has already been found if it was asked for
+                let distance = match surface {
+                    Some(surface) => surface.depth,
+                    None => self
+                        .solid_hit(ray)
+                        .map(|hit| hit.t() * ray.direction().len())
+                        .unwrap_or(f64::INFINITY),
+                };
+                fog.apply(color, distance)
+            }
+            None => color,
+        }
+    }
+
+    fn direct_color_at(
+        &self,
+        ray: Ray,
+        remaining: u32,
+        surface: Option<&mut SurfaceInfo>,
+    ) -> Color {
         let intersections = self.ray_intersects(ray);
+        if let Some(surface) = surface {
+            *surface = self.surface_info_in(ray, &intersections);
+        }
 
-        let hit = Intersection::hit(intersections);
+        let hit = Intersection::hit(intersections.clone());
 
         match hit {
             Some(intersection) => {
-                let comps = intersection.prepare_computations(ray);
-                self.shade_hit(comps)
+                let comps = intersection.prepare_computations_in(ray, &intersections);
+                match comps.object().material().medium {
+                    Some(medium) if remaining > 0 => {
+                        self.march_volume(ray, &comps, medium, remaining - 1)
+                    }
+                    Some(_) => Color::black(),
+                    None => self.shade_hit_within(comps, remaining),
+                }
             }
-            None => Color::black(),
+            None => self.background.color_in_direction(ray.direction()),
         }
     }
 
+    /*
+        Step through a volume adding the light scattered towards the eye at each
+        point, then add whatever is behind the volume dimmed by the medium.
+    */
+    fn march_volume(
+        &self,
+        ray: Ray,
+        comps: &IntersectionStats,
+        medium: Medium,
+        remaining: u32,
+    ) -> Color {
+        let object = comps.object();
+        let direction = ray.direction().normalize();
+        let speed = ray.direction().len();
+
+        // Find the part of the ray inside the volume
+        let (start, exit) = if comps.inside() {
+            (0.0, comps.t())
+        } else {
+            let exit = ray
+                .intersects(object)
+                .iter()
+                .map(|i| i.t())
+                .filter(|&t| t > comps.t())
+                .fold(f64::INFINITY, f64::min);
+            (comps.t(), exit)
+        };
+
+        // Anything solid inside the volume cuts it short
+        let solid = self.solid_hit(ray).filter(|hit| hit.t() < exit);
+        let end = solid.as_ref().map(|hit| hit.t()).unwrap_or(exit);
+        if !end.is_finite() {
+            return Color::black();
+        }
+
+        let step = (end - start) / MARCH_STEPS as f64;
+        let mut scattered = Color::black();
+        for i in 0..MARCH_STEPS {
+            let t = start + (i as f64 + 0.5) * step;
+            let point = ray.position(t);
+
+            let in_scattered = self
+                .lights
+                .iter()
+                .map(|light| {
+                    let lightv = (light.position() - point).normalize();
+                    let phase = medium.phase(Vector::dot(direction, lightv));
+                    light.intensity()
+                        * (self.transmittance(point, light.position())
+                            * phase
+                            * std::f64::consts::PI)
+                })
+                .fold(Color::black(), |total, col| total + col);
+
+            let travelled = (t - start) * speed;
+            scattered = scattered
+                + in_scattered
+                    * medium.color
+                    * (medium.density * step * speed * medium.transmittance(travelled));
+        }
+
+        let behind = match solid {
+            Some(hit) => self.shade_hit_within(hit.prepare_computations(ray), remaining),
+            None => {
+                let exit_comps = Intersection::new(exit, object.clone()).prepare_computations(ray);
+                self.direct_color_at(
+                    Ray::new(exit_comps.under_point(), ray.direction()),
+                    remaining,
+                    None,
+                )
+            }
+        };
+
+        scattered + behind * medium.transmittance((end - start) * speed)
+    }
+
     pub fn is_shadowed(&self, point: Point) -> bool {
-        self.lights.iter().all(|light| {
-            let v: Vector = light.position() - point;
-            let distance = v.len();
-            let direction = v.normalize();
+        self.lights
+            .iter()
+            .all(|light| self.is_occluded(point, light.position()))
+    }
+
+    /// Check whether anything solid lies between a point and a target
+    pub fn is_occluded(&self, point: Point, target: Point) -> bool {
+        let v: Vector = target - point;
+        let distance = v.len();
+        let direction = v.normalize();
+
+        let ray = Ray::new(point, direction);
+        if let Some(intersection) = self.solid_hit(ray) {
+            if intersection.t() < distance {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// The fraction of light which gets from a point to a target through any volumes on the way
+    pub fn transmittance(&self, point: Point, target: Point) -> f64 {
+        if self.is_occluded(point, target) {
+            return 0.0;
+        }
+
+        let v: Vector = target - point;
+        let distance = v.len();
+        let ray = Ray::new(point, v.normalize());
+
+        // Each pair of intersections with a volume bounds a stretch of medium
+        self.objects
+            .iter()
+            .filter_map(|obj| obj.material().medium.map(|medium| (obj, medium)))
+            .map(|(obj, medium)| {
+                let mut ts: Vec<f64> = ray.intersects(obj).iter().map(|i| i.t()).collect();
+                ts.sort_by(|a, b| a.total_cmp(b));
+                let inside: f64 = ts
+                    .chunks_exact(2)
+                    .map(|pair| (pair[1].min(distance) - pair[0].max(0.0)).max(0.0))
+                    .sum();
+                medium.transmittance(inside)
+            })
+            .product()
+    }
+
+    /// Find the volume a point is inside of, if any
+    fn medium_at(&self, point: Point) -> Option<Medium> {
+        let ray = Ray::new(point, Vector::new(0.0, 1.0, 0.0));
+        self.objects
+            .iter()
+            .filter_map(|obj| obj.material().medium.map(|medium| (obj, medium)))
+            .find(|(obj, _)| {
+                let ts: Vec<f64> = ray.intersects(obj).iter().map(|i| i.t()).collect();
+                ts.iter().any(|&t| t < 0.0) && ts.iter().any(|&t| t >= 0.0)
+            })
+            .map(|(_, medium)| medium)
+    }
+
+    /// Follow a path of light, which is a single wavelength in nanometres when one is given
+    fn trace_path<R: Rng>(
+        &self,
+        mut ray: Ray,
+        max_depth: u32,
+        wavelength: Option<f64>,
+        rng: &mut R,
+        mut surface: Option<&mut SurfaceInfo>,
+    ) -> Color {
+        let mut radiance = Color::black();
+        let mut throughput = Color::white();
+        // The pdf of the BRDF picking the current direction if the environment was sampled too
+        let mut brdf_pdf = None;
+        let mut medium = self.medium_at(ray.origin());
+        // The transparent material the path is travelling through
+        let mut glass: Option<Material> = None;
+        // Whether the emitters were light sampled at the last bounce
+        let mut emitters_sampled = false;
+        let mut crossings = 0;
+        let mut depth = 0;
 
-            let ray = Ray::new(point, direction);
+        while depth < max_depth {
             let intersections = self.ray_intersects(ray);
-            if let Some(intersection) = Intersection::hit(intersections) {
-                if intersection.t() < distance {
-                    return true;
+            if let Some(surface) = surface.take() {
+                *surface = self.surface_info_in(ray, &intersections);
+            }
+            let hit = Intersection::hit(intersections.clone());
+
+            // Light may scatter inside the medium before it reaches the next surface
+            if let Some(current) = medium {
+                let speed = ray.direction().len();
+                let limit = hit
+                    .as_ref()
+                    .map(|hit| hit.t() * speed)
+                    .unwrap_or(f64::INFINITY);
+                let distance = current.sample_distance(rng.gen());
+                if distance < limit {
+                    let direction = ray.direction().normalize();
+                    let point = ray.position(distance / speed);
+
+                    throughput = throughput * current.color;
+                    if throughput == Color::black() {
+                        break;
+                    }
+                    radiance = radiance
+                        + throughput * self.sample_medium_light(point, direction, &current, rng);
+
+                    let scattered = current.sample_direction(direction, rng.gen(), rng.gen());
+                    brdf_pdf = None;
+                    emitters_sampled = true;
+                    ray = Ray::new(point, scattered);
+                    depth += 1;
+                    continue;
                 }
             }
 
-            false
-        })
+            let hit = match hit {
+                Some(hit) => hit,
+                None => {
+                    let background = self.background.color_in_direction(ray.direction());
+                    let weight = match (self.background.environment_map(), brdf_pdf) {
+                        // Balance against the environment light sampling at the last bounce
+                        (Some(map), Some(pdf)) => pdf / (pdf + map.pdf(ray.direction())),
+                        _ => 1.0,
+                    };
+                    radiance = radiance + throughput * background * weight;
+                    break;
+                }
+            };
+            if let Some(glass) = &glass {
+                throughput = throughput * glass.transmittance(hit.t() * ray.direction().len());
+            }
+
+            let comps = hit.prepare_computations_for_wavelength(ray, &intersections, wavelength);
+            let object = comps.object();
+            let material = object.material();
+
+            // Pass straight through the boundary of a volume
+            if let Some(boundary) = material.medium {
+                crossings += 1;
+                if crossings > MAX_VOLUME_CROSSINGS {
+                    break;
+                }
+                medium = if comps.inside() { None } else { Some(boundary) };
+                ray = Ray::new(comps.under_point(), ray.direction());
+                continue;
+            }
+
+            /*
+                Emitters which can be sampled have already been counted by the light
+                sampling at the previous bounce so only add them when seen directly
+            */
+            if !emitters_sampled || !self.emitters.contains(object) {
+                radiance = radiance + throughput * material.emission;
+            }
+
+            /*
+                Perfectly smooth surfaces can't be light sampled so the path follows them
+                exactly. Each lobe is picked in proportion to how much light it carries.
+            */
+            let lobe: f64 = rng.gen();
+            let mirror = material.reflective * (1.0 - material.transparency);
+            if lobe < material.transparency + mirror {
+                let refracted = if lobe < material.transparency {
+                    comps
+                        .refracted_direction()
+                        .filter(|_| rng.gen::<f64>() >= comps.schlick())
+                } else {
+                    None
+                };
+                ray = match refracted {
+                    Some(direction) => Ray::new(comps.under_point(), direction),
+                    None => Ray::new(comps.over_point(), comps.reflectv()),
+                };
+
+                // Light travelling inside a transparent material is absorbed along the way
+                let inside_after = refracted.is_some() != comps.inside();
+                glass = if lobe < material.transparency && inside_after {
+                    Some(material.clone())
+                } else {
+                    None
+                };
+                brdf_pdf = None;
+                emitters_sampled = false;
+                depth += 1;
+                continue;
+            }
+            glass = None;
+
+            radiance = radiance + throughput * self.sample_direct_light(&comps, rng);
+
+            // Continue the path in a direction picked by the material
+            let random = (rng.gen(), rng.gen(), rng.gen());
+            let (direction, weight) = match material.sample_brdf(
+                comps.over_point(),
+                comps.normalv(),
+                comps.eyev(),
+                random,
+            ) {
+                Some(sample) => sample,
+                None => break,
+            };
+            throughput = throughput * weight;
+            if throughput == Color::black() {
+                break;
+            }
+            brdf_pdf = Some(material.brdf_pdf(comps.normalv(), comps.eyev(), direction));
+            emitters_sampled = true;
+            ray = Ray::new(comps.over_point(), direction);
+            depth += 1;
+        }
+
+        radiance
+    }
+
+    fn sample_direct_light<R: Rng>(&self, comps: &IntersectionStats, rng: &mut R) -> Color {
+        let position = comps.over_point();
+        let material = comps.object().material();
+
+        // Point lights use the same shading as the Whitted integrator, minus the ambient term
+        let point_lights = self
+            .lights
+            .iter()
+            .map(|light| {
+                let visible = self.transmittance(position, light.position());
+                material.internal_lighting(
+                    light,
+                    position,
+                    comps.eyev(),
+                    comps.normalv(),
+                    visible == 0.0,
+                ) * visible
+            })
+            .fold(Color::black(), |total, col| total + col);
+
+        let direct = point_lights + self.sample_environment_light(comps, rng);
+
+        let (lightv, emission, light_pdf) = match self.sample_emitter(position, rng) {
+            Some(sample) => sample,
+            None => return direct,
+        };
+
+        let cos_surface = Vector::dot(lightv, comps.normalv());
+        if cos_surface <= 0.0 {
+            return direct;
+        }
+
+        let brdf = material.brdf(position, comps.normalv(), comps.eyev(), lightv);
+        direct + brdf * emission * (cos_surface / light_pdf)
+    }
+
+    /*
+        Pick one emitter & a point on its surface. Returns the direction to the point,
+        the light arriving from it after passing through any volumes & the solid angle pdf.
+    */
+    fn sample_emitter<R: Rng>(&self, position: Point, rng: &mut R) -> Option<(Vector, Color, f64)> {
+        if self.emitters.is_empty() {
+            return None;
+        }
+
+        let chosen = rng.gen_range(0..self.emitters.len());
+        let emitter = &self.emitters[chosen];
+        let (light_point, light_normal, pdf) = emitter.sample_surface(rng.gen(), rng.gen())?;
+
+        let to_light = light_point - position;
+        let distance_squared = Vector::dot(to_light, to_light);
+        let lightv = to_light.normalize();
+
+        // Emitters give off light from both sides of their surface
+        let cos_light = Vector::dot(-lightv, light_normal).abs();
+        if cos_light <= 0.0 {
+            return None;
+        }
+
+        // Stop the shadow ray just short of the emitter so it doesn't shadow itself
+        let visible = self.transmittance(position, light_point - lightv * f32::EPSILON as f64);
+        if visible == 0.0 {
+            return None;
+        }
+
+        // Convert the area pdf into solid angle & account for choosing 1 of the emitters
+        let light_pdf = pdf * distance_squared / cos_light / self.emitters.len() as f64;
+        Some((lightv, emitter.material().emission * visible, light_pdf))
+    }
+
+    fn sample_environment_light<R: Rng>(&self, comps: &IntersectionStats, rng: &mut R) -> Color {
+        let map = match self.background.environment_map() {
+            Some(map) => map,
+            None => return Color::black(),
+        };
+
+        let (lightv, light, light_pdf) = map.sample(rng.gen(), rng.gen());
+        let cos_surface = Vector::dot(lightv, comps.normalv());
+        if cos_surface <= 0.0 || light_pdf <= 0.0 {
+            return Color::black();
+        }
+
+        // The environment is infinitely far away so anything solid in the way blocks it
+        let position = comps.over_point();
+        let visible = self.transmittance(position, position + lightv * FAR_AWAY);
+        if visible == 0.0 {
+            return Color::black();
+        }
+
+        let material = comps.object().material();
+        let brdf = material.brdf(position, comps.normalv(), comps.eyev(), lightv);
+        let brdf_pdf = material.brdf_pdf(comps.normalv(), comps.eyev(), lightv);
+
+        // Balance heuristic against hitting the environment by sampling the BRDF
+        let weight = light_pdf / (light_pdf + brdf_pdf);
+        brdf * light * (cos_surface * weight * visible / light_pdf)
+    }
+
+    /// Light scattered towards `-direction` from a point inside a volume
+    fn sample_medium_light<R: Rng>(
+        &self,
+        point: Point,
+        direction: Vector,
+        medium: &Medium,
+        rng: &mut R,
+    ) -> Color {
+        // Point lights are scaled to match the brightness of a surface lit by them
+        let point_lights = self
+            .lights
+            .iter()
+            .map(|light| {
+                let to_light = light.position() - point;
+                let phase = medium.phase(Vector::dot(direction, to_light.normalize()));
+                light.intensity()
+                    * (self.transmittance(point, light.position()) * phase * std::f64::consts::PI)
+            })
+            .fold(Color::black(), |total, col| total + col);
+
+        match self.sample_emitter(point, rng) {
+            Some((lightv, emission, light_pdf)) => {
+                let phase = medium.phase(Vector::dot(direction, lightv));
+                point_lights + emission * (phase / light_pdf)
+            }
+            None => point_lights,
+        }
+    }
+}
+
+/// Traces the rays a surface shader asks for, counting them towards the recursion limit
+struct WhittedTracer<'a> {
+    world: &'a WorldImpl,
+    remaining: u32,
+}
+
+impl Tracer for WhittedTracer<'_> {
+    fn trace(&self, ray: Ray) -> Color {
+        if self.remaining == 0 {
+            return Color::black();
+        }
+        self.world.direct_color_at(ray, self.remaining - 1, None)
+    }
+
+    fn is_shadowed(&self, point: Point) -> bool {
+        self.world.is_shadowed(point)
+    }
+
+    fn is_occluded(&self, point: Point, target: Point) -> bool {
+        self.world.is_occluded(point, target)
     }
 }
 
 /// World is a builder for WorldImpl
+#[derive(Debug)]
 pub struct World {
     lights: Vec<PointLight>,
     objects: Vec<Shape>,
+    integrator: Integrator,
+    background: Background,
+    fog: Option<Fog>,
 }
 
 impl World {
@@ -84,16 +806,70 @@ impl World {
         World {
             lights: vec![],
             objects: vec![],
+            integrator: Integrator::Whitted,
+            background: Background::black(),
+            fog: None,
         }
     }
 
     pub fn generate(self) -> WorldImpl {
+        // Number the objects & materials once here rather than looking them up for every pixel
+        let mut materials: Vec<(&Material, usize)> = vec![];
+        let ids: Vec<ShapeIds> = self
+            .objects
+            .iter()
+            .enumerate()
+            .map(|(object, shape)| {
+                let material = match materials.iter().find(|(m, _)| *m == shape.material()) {
+                    Some(&(_, material)) => material,
+                    None => {
+                        materials.push((shape.material(), object));
+                        object
+                    }
+                };
+                ShapeIds { object, material }
+            })
+            .collect();
+        let objects: Vec<Shape> = self
+            .objects
+            .into_iter()
+            .zip(ids)
+            .map(|(shape, ids)| shape.with_ids(ids))
+            .collect();
+
+        let emitters = objects
+            .iter()
+            .filter(|obj| obj.material().is_emissive() && obj.sample_surface(0.5, 0.5).is_some())
+            .cloned()
+            .collect();
+
         WorldImpl {
             lights: self.lights,
-            objects: self.objects,
+            objects,
+            emitters,
+            integrator: self.integrator,
+            background: self.background,
+            fog: self.fog,
+            id: NEXT_WORLD_ID.fetch_add(1, Ordering::Relaxed),
+            rays_cast: AtomicU64::new(0),
         }
     }
 
+    pub fn set_integrator(mut self, integrator: Integrator) -> Self {
+        self.integrator = integrator;
+        self
+    }
+
+    pub fn set_background(mut self, background: Background) -> Self {
+        self.background = background;
+        self
+    }
+
+    pub fn set_fog(mut self, fog: Fog) -> Self {
+        self.fog = Some(fog);
+        self
+    }
+
     pub fn reset_lights(mut self) -> Self {
         self.lights = vec![];
         self
@@ -104,18 +880,24 @@ impl World {
         self
     }
 
-    pub fn add_object(mut self, object: Shape) -> Self {
-        self.objects.push(object);
+    /// Add a built-in shape, or a user's geometry in a `Box` or `Arc`
+    pub fn add_object(mut self, object: impl Into<Shape>) -> Self {
+        self.objects.push(object.into());
         self
     }
+}
 
-    pub fn default() -> World {
+/// The default world from the book: a light & two spheres, one inside the other
+impl Default for World {
+    fn default() -> World {
         let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::white());
 
-        let mut material = Material::default();
-        material.color = Color::new(0.8, 1.0, 0.6);
-        material.diffuse = 0.7;
-        material.specular = 0.2;
+        let material = Material {
+            color: Color::new(0.8, 1.0, 0.6),
+            diffuse: 0.7,
+            specular: 0.2,
+            ..Material::default()
+        };
 
         let sphere1 = Shape::sphere(Matrix4x4::identity(), material);
         let sphere2 = Shape::sphere(Matrix4x4::scaling(0.5, 0.5, 0.5), Material::default());
@@ -126,3 +908,13 @@ impl World {
             .add_object(sphere2)
     }
 }
+
+/// The first hit among some intersections, skipping the invisible boundaries of volumes
+fn solid_hit_in(intersections: &[Intersection]) -> Option<Intersection> {
+    let solid = intersections
+        .iter()
+        .filter(|i| i.object().material().medium.is_none())
+        .cloned()
+        .collect();
+    Intersection::hit(solid)
+}
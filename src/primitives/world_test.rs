@@ -1,5 +1,11 @@
-use super::{Intersection, Material, PointLight, Ray, Shape, World};
-use crate::{image::Color, Matrix4x4, Point, Vector};
+use super::{
+    sampling::sample_rng, Background, EnvironmentMap, Fog, Integrator, Intersection, Material,
+    Medium, Microfacet, PointLight, Ray, Shape, World, WorldImpl,
+};
+use crate::{
+    image::{Canvas, Color},
+    Matrix4x4, Point, Vector,
+};
 
 #[test]
 fn creating_a_world() {
@@ -13,10 +19,12 @@ fn creating_a_world() {
 fn the_default_world() {
     let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::white());
 
-    let mut material = Material::default();
-    material.color = Color::new(0.8, 1.0, 0.6);
-    material.diffuse = 0.7;
-    material.specular = 0.2;
+    let material = Material {
+        color: Color::new(0.8, 1.0, 0.6),
+        diffuse: 0.7,
+        specular: 0.2,
+        ..Material::default()
+    };
 
     let sphere1 = Shape::sphere(Matrix4x4::identity(), material);
     let sphere2 = Shape::sphere(Matrix4x4::scaling(0.5, 0.5, 0.5), Material::default());
@@ -50,7 +58,7 @@ fn round(v: f64) -> f64 {
 fn shading_an_intersection() {
     let w = World::default().generate();
     let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
-    let shape = w.objects()[0];
+    let shape = w.objects()[0].clone();
     let i = Intersection::new(4.0, shape);
     let comps = i.prepare_computations(ray);
 
@@ -69,7 +77,7 @@ fn shading_an_intersection_from_the_inside() {
         .generate();
 
     let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
-    let shape = w.objects()[1];
+    let shape = w.objects()[1].clone();
 
     let i = Intersection::new(0.5, shape);
     let comps = i.prepare_computations(ray);
@@ -103,17 +111,21 @@ fn the_color_when_a_ray_hits() {
 
 #[test]
 fn the_color_with_an_intersection_behind_the_ray() {
-    let mut material1 = Material::default();
-    material1.color = Color::new(0.8, 1.0, 0.6);
-    material1.diffuse = 0.7;
-    material1.specular = 0.2;
-    material1.ambient = 1.0;
+    let material1 = Material {
+        color: Color::new(0.8, 1.0, 0.6),
+        diffuse: 0.7,
+        specular: 0.2,
+        ambient: 1.0,
+        ..Material::default()
+    };
 
-    let mut material2 = Material::default();
-    material2.ambient = 1.0;
+    let material2 = Material {
+        ambient: 1.0,
+        ..Material::default()
+    };
 
     let sphere1 = Shape::sphere(Matrix4x4::identity(), material1);
-    let sphere2 = Shape::sphere(Matrix4x4::scaling(0.5, 0.5, 0.5), material2);
+    let sphere2 = Shape::sphere(Matrix4x4::scaling(0.5, 0.5, 0.5), material2.clone());
 
     let w = World::new()
         .add_light(PointLight::new(
@@ -133,10 +145,10 @@ fn the_color_with_an_intersection_behind_the_ray() {
 #[test]
 fn testing_is_shadowed_at_various_points() {
     let w = World::default().generate();
-    assert_eq!(w.is_shadowed(Point::new(0.0, 10.0, 0.0)), false);
-    assert_eq!(w.is_shadowed(Point::new(10.0, -10.0, 10.0)), true);
-    assert_eq!(w.is_shadowed(Point::new(-20.0, 20.0, -20.0)), false);
-    assert_eq!(w.is_shadowed(Point::new(-2.0, 2.0, -2.0)), false);
+    assert!(!w.is_shadowed(Point::new(0.0, 10.0, 0.0)));
+    assert!(w.is_shadowed(Point::new(10.0, -10.0, 10.0)));
+    assert!(!w.is_shadowed(Point::new(-20.0, 20.0, -20.0)));
+    assert!(!w.is_shadowed(Point::new(-2.0, 2.0, -2.0)));
 }
 
 #[test]
@@ -145,13 +157,522 @@ fn shade_hit_is_given_an_intersection_in_shadow() {
     let w = World::new()
         .add_light(PointLight::new(Point::new(0.0, 0.0, -10.0), Color::white()))
         .add_object(Shape::default())
-        .add_object(s2)
+        .add_object(s2.clone())
         .generate();
 
     let ray = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
-    let i = Intersection::new(4.0, s2);
+    let i = Intersection::new(4.0, s2.clone());
     let comps = i.prepare_computations(ray);
 
     let c = w.shade_hit(comps);
     assert_eq!(c, Color::new(0.1, 0.1, 0.1));
 }
+
+fn emissive_sphere(transform: Matrix4x4) -> Shape {
+    let material = Material {
+        diffuse: 0.0,
+        emission: Color::white(),
+        ..Material::default()
+    };
+    Shape::sphere(transform, material)
+}
+
+#[test]
+fn emissive_spheres_are_emitters() {
+    let w = World::new()
+        .add_object(emissive_sphere(Matrix4x4::identity()))
+        .add_object(Shape::default())
+        .generate();
+
+    assert_eq!(w.emitters().len(), 1);
+}
+
+#[test]
+fn path_tracing_a_ray_which_hits_an_emitter() {
+    let w = World::new()
+        .add_object(emissive_sphere(Matrix4x4::identity()))
+        .set_integrator(Integrator::path_tracer(1, 4))
+        .generate();
+
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let c = w.sample_color_at(ray, &mut sample_rng(0, 0, 0, 0));
+
+    assert_eq!(c, Color::white());
+}
+
+/// The average of many path traced samples of a ray
+fn average_color(w: &WorldImpl, ray: Ray, samples: u32) -> Color {
+    let total = (0..samples)
+        .map(|sample| w.sample_color_at(ray, &mut sample_rng(0, 0, 0, sample)))
+        .fold(Color::black(), |total, col| total + col);
+    total * (1.0 / samples as f64)
+}
+
+#[test]
+fn an_emitter_lights_a_diffuse_surface_when_path_tracing() {
+    // A unit sphere 2 units above the floor gives an outgoing radiance of albedo * (r / d)^2
+    let w = World::new()
+        .add_object(Shape::plane_default())
+        .add_object(emissive_sphere(Matrix4x4::translation(0.0, 2.0, 0.0)))
+        .set_integrator(Integrator::path_tracer(1, 1))
+        .generate();
+
+    let ray = Ray::new(
+        Point::new(0.0, 1.0, -1.0),
+        Vector::new(0.0, -1.0, 1.0).normalize(),
+    );
+    let average = average_color(&w, ray, 4000);
+
+    assert!((average.r() - 0.9 * 0.25).abs() < 0.01);
+}
+
+#[test]
+fn the_default_world_has_no_emitters() {
+    let w = World::default().generate();
+    assert!(w.emitters().is_empty());
+    assert_eq!(w.integrator(), Integrator::Whitted);
+}
+
+fn furnace_color(material: Material) -> Color {
+    // A sphere inside a larger sphere which gives off white light everywhere
+    let w = World::new()
+        .add_object(emissive_sphere(Matrix4x4::scaling(10.0, 10.0, 10.0)))
+        .add_object(Shape::sphere(Matrix4x4::identity(), material))
+        .set_integrator(Integrator::path_tracer(1, 8))
+        .generate();
+
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    average_color(&w, ray, 20000)
+}
+
+#[test]
+fn a_white_diffuse_sphere_disappears_in_a_furnace() {
+    let material = Material {
+        diffuse: 1.0,
+        ..Material::default()
+    };
+
+    let c = furnace_color(material);
+    assert!((c.r() - 1.0).abs() < 0.03, "{:?}", c);
+}
+
+#[test]
+fn a_microfacet_sphere_does_not_create_energy_in_a_furnace() {
+    let material = Material {
+        microfacet: Some(Microfacet::new(1.0, 0.3)),
+        ..Material::default()
+    };
+
+    let c = furnace_color(material);
+    assert!(c.r() <= 1.03, "{:?}", c);
+}
+
+#[test]
+fn a_ray_which_misses_sees_the_background() {
+    let w = World::default()
+        .set_background(Background::Solid(Color::green()))
+        .generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+    assert_eq!(w.color_at(ray), Color::green());
+}
+
+fn environment_furnace_color(background: Background) -> Color {
+    let material = Material {
+        diffuse: 1.0,
+        ..Material::default()
+    };
+
+    let w = World::new()
+        .add_object(Shape::sphere(Matrix4x4::identity(), material))
+        .set_background(background)
+        .set_integrator(Integrator::path_tracer(1, 8))
+        .generate();
+
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    average_color(&w, ray, 4000)
+}
+
+#[test]
+fn a_white_diffuse_sphere_disappears_against_a_white_background() {
+    let c = environment_furnace_color(Background::Solid(Color::white()));
+    assert!((c.r() - 1.0).abs() < 0.03, "{:?}", c);
+}
+
+#[test]
+fn a_white_diffuse_sphere_disappears_in_a_white_environment_map() {
+    let mut canvas = Canvas::new(8, 4);
+    for y in 0..4 {
+        for x in 0..8 {
+            canvas.write_pixel(x, y, Color::white());
+        }
+    }
+    let map = EnvironmentMap::new(canvas, 1.0);
+
+    let c = environment_furnace_color(Background::environment(map));
+    assert!((c.r() - 1.0).abs() < 0.03, "{:?}", c);
+}
+
+#[test]
+fn a_ray_which_misses_is_lost_in_the_fog() {
+    let w = World::default()
+        .set_fog(Fog::new(Color::new(0.5, 0.5, 0.5), 0.1))
+        .generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+    assert_eq!(w.color_at(ray), Color::new(0.5, 0.5, 0.5));
+}
+
+#[test]
+fn fog_fades_surfaces_with_distance() {
+    let fog = Fog::new(Color::white(), 0.1);
+    let clear = World::default().generate();
+    let foggy = World::default().set_fog(fog).generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    // The first surface is 4 units away
+    let visible = (-0.4f64).exp();
+    let expected = clear.color_at(ray) * visible + Color::white() * (1.0 - visible);
+    assert_eq!(foggy.color_at(ray), expected);
+}
+
+fn volume(transform: Matrix4x4, color: Color, density: f64) -> Shape {
+    let material = Material {
+        medium: Some(Medium::new(color, density)),
+        ..Material::default()
+    };
+    Shape::sphere(transform, material)
+}
+
+#[test]
+fn volumes_do_not_cast_hard_shadows() {
+    let w = World::new()
+        .add_object(volume(Matrix4x4::identity(), Color::white(), 0.5))
+        .generate();
+    let from = Point::new(0.0, 0.0, -5.0);
+    let to = Point::new(0.0, 0.0, 5.0);
+
+    assert!(!w.is_occluded(from, to));
+    assert!((w.transmittance(from, to) - (-1.0f64).exp()).abs() < 1e-9);
+}
+
+#[test]
+fn a_dark_volume_dims_the_background() {
+    let w = World::new()
+        .add_object(volume(Matrix4x4::identity(), Color::black(), 0.5))
+        .set_background(Background::Solid(Color::white()))
+        .generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    let c = w.color_at(ray);
+    assert!((c.r() - (-1.0f64).exp()).abs() < 1e-9, "{:?}", c);
+}
+
+#[test]
+fn a_volume_with_no_density_is_invisible() {
+    let w = World::new()
+        .add_object(volume(Matrix4x4::identity(), Color::white(), 0.0))
+        .add_light(PointLight::new(Point::new(0.0, 5.0, 0.0), Color::white()))
+        .set_background(Background::Solid(Color::green()))
+        .generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    assert_eq!(w.color_at(ray), Color::green());
+}
+
+#[test]
+fn an_object_casts_a_shaft_of_shadow_through_a_volume() {
+    let light = PointLight::new(Point::new(0.0, 8.0, 0.0), Color::white());
+    let smoke = volume(Matrix4x4::scaling(6.0, 6.0, 6.0), Color::white(), 0.2);
+    let blocker = Shape::sphere(Matrix4x4::translation(0.0, 4.0, 0.0), Material::default());
+
+    let lit = World::new()
+        .add_light(light)
+        .add_object(smoke.clone())
+        .generate();
+    let shadowed = World::new()
+        .add_light(light)
+        .add_object(smoke.clone())
+        .add_object(blocker)
+        .generate();
+    let dark = World::new().add_object(smoke.clone()).generate();
+
+    // Look through the volume underneath the blocker
+    let ray = Ray::new(Point::new(0.0, -1.0, -10.0), Vector::new(0.0, 0.0, 1.0));
+    let lit = lit.color_at(ray);
+    let shadowed = shadowed.color_at(ray);
+
+    assert!(lit.r() > shadowed.r() * 1.2, "{:?} {:?}", lit, shadowed);
+    assert!(shadowed.r() > 0.0);
+    assert_eq!(dark.color_at(ray), Color::black());
+}
+
+fn path_traced_volume_color(medium: Shape) -> Color {
+    let w = World::new()
+        .add_object(medium)
+        .set_background(Background::Solid(Color::white()))
+        .set_integrator(Integrator::path_tracer(1, 32))
+        .generate();
+
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    average_color(&w, ray, 4000)
+}
+
+#[test]
+fn path_tracing_through_an_absorbing_volume() {
+    let c = path_traced_volume_color(volume(Matrix4x4::identity(), Color::black(), 0.5));
+    assert!((c.r() - (-1.0f64).exp()).abs() < 0.03, "{:?}", c);
+}
+
+#[test]
+fn a_white_volume_disappears_against_a_white_background() {
+    // Light is only scattered so none is lost
+    let c = path_traced_volume_color(volume(Matrix4x4::identity(), Color::white(), 0.5));
+    assert!((c.r() - 1.0).abs() < 0.03, "{:?}", c);
+}
+
+fn assert_close(actual: Color, expected: Color) {
+    let difference = actual - expected;
+    assert!(
+        difference.r().abs() < 1e-4 && difference.g().abs() < 1e-4 && difference.b().abs() < 1e-4,
+        "{:?} != {:?}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn the_reflected_color_for_a_nonreflective_material() {
+    let w = World::default().generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+    let i = Intersection::new(1.0, w.objects()[1].clone());
+    let comps = i.prepare_computations(ray);
+
+    assert_eq!(w.reflected_color(&comps, 5), Color::black());
+}
+
+fn reflective_plane() -> Shape {
+    let material = Material {
+        reflective: 0.5,
+        ..Material::default()
+    };
+    Shape::plane(Matrix4x4::translation(0.0, -1.0, 0.0), material)
+}
+
+#[test]
+fn the_reflected_color_for_a_reflective_material() {
+    let w = World::default().add_object(reflective_plane()).generate();
+    let ray = Ray::new(
+        Point::new(0.0, 0.0, -3.0),
+        Vector::new(0.0, -(2f64.sqrt()) / 2.0, 2f64.sqrt() / 2.0),
+    );
+    let i = Intersection::new(2f64.sqrt(), reflective_plane());
+    let comps = i.prepare_computations(ray);
+
+    assert_close(
+        w.reflected_color(&comps, 5),
+        Color::new(0.19033, 0.23791, 0.14274),
+    );
+    assert_eq!(w.reflected_color(&comps, 0), Color::black());
+}
+
+#[test]
+fn color_at_with_mutually_reflective_surfaces() {
+    let material = Material {
+        reflective: 1.0,
+        ..Material::default()
+    };
+    let lower = Shape::plane(Matrix4x4::translation(0.0, -1.0, 0.0), material.clone());
+    let upper = Shape::plane(Matrix4x4::translation(0.0, 1.0, 0.0), material);
+    let w = World::new()
+        .add_light(PointLight::new(Point::new(0.0, 0.0, 0.0), Color::white()))
+        .add_object(lower)
+        .add_object(upper)
+        .generate();
+
+    // Terminates rather than recursing forever
+    let ray = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0));
+    w.color_at(ray);
+}
+
+#[test]
+fn the_refracted_color_with_an_opaque_surface() {
+    let w = World::default().generate();
+    let shape = w.objects()[0].clone();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let xs = vec![
+        Intersection::new(4.0, shape.clone()),
+        Intersection::new(6.0, shape.clone()),
+    ];
+    let comps = xs[0].prepare_computations_in(ray, &xs);
+
+    assert_eq!(w.refracted_color(&comps, 5), Color::black());
+}
+
+fn glass_default_world() -> (Shape, World) {
+    let world = World::default();
+    let material = Material {
+        color: Color::new(0.8, 1.0, 0.6),
+        diffuse: 0.7,
+        specular: 0.2,
+        transparency: 1.0,
+        refractive_index: 1.5,
+        ..Material::default()
+    };
+    let shape = Shape::sphere(Matrix4x4::identity(), material);
+    (shape, world)
+}
+
+#[test]
+fn the_refracted_color_at_the_maximum_recursive_depth() {
+    let (shape, world) = glass_default_world();
+    let w = World::new()
+        .add_light(world.generate().lights()[0])
+        .add_object(shape.clone())
+        .generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+    let xs = vec![
+        Intersection::new(4.0, shape.clone()),
+        Intersection::new(6.0, shape.clone()),
+    ];
+    let comps = xs[0].prepare_computations_in(ray, &xs);
+
+    assert_eq!(w.refracted_color(&comps, 0), Color::black());
+}
+
+#[test]
+fn the_refracted_color_under_total_internal_reflection() {
+    let (shape, _) = glass_default_world();
+    let w = World::new().add_object(shape.clone()).generate();
+    let ray = Ray::new(
+        Point::new(0.0, 0.0, 2f64.sqrt() / 2.0),
+        Vector::new(0.0, 1.0, 0.0),
+    );
+    let xs = vec![
+        Intersection::new(-(2f64.sqrt()) / 2.0, shape.clone()),
+        Intersection::new(2f64.sqrt() / 2.0, shape.clone()),
+    ];
+    let comps = xs[1].prepare_computations_in(ray, &xs);
+
+    assert_eq!(w.refracted_color(&comps, 5), Color::black());
+}
+
+#[test]
+fn shade_hit_with_a_transparent_material() {
+    let floor_material = Material {
+        transparency: 0.5,
+        refractive_index: 1.5,
+        ..Material::default()
+    };
+    let floor = Shape::plane(Matrix4x4::translation(0.0, -1.0, 0.0), floor_material);
+
+    let ball_material = Material {
+        color: Color::red(),
+        ambient: 0.5,
+        ..Material::default()
+    };
+    let ball = Shape::sphere(Matrix4x4::translation(0.0, -3.5, -0.5), ball_material);
+
+    let w = World::default()
+        .add_object(floor.clone())
+        .add_object(ball)
+        .generate();
+    let ray = Ray::new(
+        Point::new(0.0, 0.0, -3.0),
+        Vector::new(0.0, -(2f64.sqrt()) / 2.0, 2f64.sqrt() / 2.0),
+    );
+    let xs = vec![Intersection::new(2f64.sqrt(), floor.clone())];
+    let comps = xs[0].prepare_computations_in(ray, &xs);
+
+    assert_close(w.shade_hit(comps), Color::new(0.93642, 0.68642, 0.68642));
+}
+
+fn tinted_glass(thickness: f64) -> Shape {
+    let material = Material {
+        diffuse: 0.0,
+        specular: 0.0,
+        ambient: 0.0,
+        transparency: 1.0,
+        refractive_index: 1.0,
+        absorption: Color::new(0.1, 0.5, 0.5),
+        ..Material::default()
+    };
+    Shape::sphere(
+        Matrix4x4::scaling(thickness, thickness, thickness),
+        material,
+    )
+}
+
+fn color_through(glass: Shape, integrator: Integrator) -> Color {
+    let w = World::new()
+        .add_object(glass)
+        .set_background(Background::Solid(Color::white()))
+        .set_integrator(integrator)
+        .generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -10.0), Vector::new(0.0, 0.0, 1.0));
+    w.sample_color_at(ray, &mut sample_rng(0, 0, 0, 0))
+}
+
+#[test]
+fn light_is_absorbed_along_its_path_through_glass() {
+    // Matching refractive indices means the light passes straight through 2 units of glass
+    let c = color_through(tinted_glass(1.0), Integrator::Whitted);
+    assert_close(
+        c,
+        Color::new((-0.2f64).exp(), (-1.0f64).exp(), (-1.0f64).exp()),
+    );
+}
+
+#[test]
+fn thick_glass_is_darker_than_thin_glass() {
+    let thin = color_through(tinted_glass(0.5), Integrator::Whitted);
+    let thick = color_through(tinted_glass(2.0), Integrator::Whitted);
+
+    assert!(thick.g() < thin.g());
+    assert!(thick.r() > thick.g());
+}
+
+#[test]
+fn path_traced_glass_absorbs_light_the_same_way() {
+    let c = color_through(tinted_glass(1.0), Integrator::path_tracer(1, 8));
+    assert_close(
+        c,
+        Color::new((-0.2f64).exp(), (-1.0f64).exp(), (-1.0f64).exp()),
+    );
+}
+
+#[test]
+fn spectral_rendering_without_dispersion_keeps_colors() {
+    let w = World::new()
+        .set_background(Background::Solid(Color::new(1.0, 0.5, 0.25)))
+        .set_integrator(Integrator::spectral_path_tracer(1, 4))
+        .generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    let average = average_color(&w, ray, 4000);
+
+    assert!((average.r() - 1.0).abs() < 0.05, "{:?}", average);
+    assert!((average.g() - 0.5).abs() < 0.03, "{:?}", average);
+    assert!((average.b() - 0.25).abs() < 0.02, "{:?}", average);
+}
+
+#[test]
+fn rays_are_added_to_the_total_when_counted() {
+    let w = World::default().generate();
+    let other = World::default().generate();
+    let ray = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+    w.color_at(ray);
+    assert_eq!(w.rays_cast(), 0);
+    w.count_rays();
+    let counted = w.rays_cast();
+    // The camera ray & a shadow ray
+    assert_eq!(counted, 2);
+
+    // Rays cast in another world aren't added to this one
+    other.color_at(ray);
+    w.count_rays();
+    assert_eq!(w.rays_cast(), counted);
+    other.count_rays();
+    assert_eq!(other.rays_cast(), 2);
+}
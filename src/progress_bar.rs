@@ -0,0 +1,49 @@
+use rtracer::primitives::Progress;
+use std::io::{IsTerminal, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How often the bar is redrawn
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const BAR_WIDTH: usize = 30;
+
+/*
+    Prints a render's progress to stderr on a single line which is redrawn as it goes.
+    Nothing is printed when stderr isn't a terminal so logs don't fill up with bars.
+*/
+pub struct ProgressBar {
+    enabled: bool,
+    last_drawn: Mutex<Option<Instant>>,
+}
+
+impl ProgressBar {
+    pub fn new() -> Self {
+        Self {
+            enabled: std::io::stderr().is_terminal(),
+            last_drawn: Mutex::new(None),
+        }
+    }
+
+    /// Redraw the bar if it's been long enough since it was last drawn or the render is complete
+    pub fn update(&self, progress: Progress) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut last_drawn = self.last_drawn.lock().unwrap();
+        let due = last_drawn.is_none_or(|last| last.elapsed() >= REDRAW_INTERVAL);
+        if due || progress.is_complete() {
+            // Pad the line so nothing is left over from a longer one
+            eprint!("\r{} {:<50}", progress.bar(BAR_WIDTH), progress);
+            let _ = std::io::stderr().flush();
+            *last_drawn = Some(Instant::now());
+        }
+    }
+
+    /// Move on from the bar's line once the render is done, ready for the next render
+    pub fn finish(&self) {
+        if self.enabled && self.last_drawn.lock().unwrap().take().is_some() {
+            eprintln!();
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use super::output::{save_render, OutputFormat, SaveOptions};
+use crate::image::Canvas;
+use crate::primitives::{Aov, Integrator, Progress};
+use crate::scene::{load_scene, BatchJob};
+use serde::Serialize;
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Instant;
+
+/// How the jobs in a batch are rendered & saved when they don't say otherwise
+pub struct BatchOptions {
+    /// Taken from each job's output when not given
+    pub format: Option<OutputFormat>,
+    /// How the images are saved. The format is replaced by each job's.
+    pub save: SaveOptions,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    /// Saved next to each image, or as layers of an EXR
+    pub aovs: Vec<Aov>,
+    /// Make the background transparent. Only PNGs have an alpha channel.
+    pub alpha: bool,
+    /// The integrator for a job, given the samples the job asks for
+    pub integrator_for: Box<dyn Fn(Option<u32>) -> Integrator>,
+}
+
+/// What happened to one job in a batch
+#[derive(Serialize, Debug)]
+pub struct JobSummary {
+    pub scene: String,
+    pub output: String,
+    pub success: bool,
+    /// Why the job failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub seconds: f64,
+    pub rays_cast: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BatchSummary {
+    pub jobs: Vec<JobSummary>,
+    pub failures: usize,
+    pub seconds: f64,
+}
+
+impl BatchSummary {
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        std::fs::write(path, json + "\n")
+            .map_err(|error| format!("failed to save `{}`: {}", path, error))
+    }
+}
+
+/*
+    Render the jobs one at a time so each one gets every thread in the pool.
+    A job which fails, or panics, is recorded in the summary & the rest still render.
+    `on_progress` follows the job being rendered & `on_job` is told about each job as
+    it finishes.
+*/
+pub fn run_batch<P, J>(
+    jobs: &[BatchJob],
+    options: &BatchOptions,
+    on_progress: P,
+    mut on_job: J,
+) -> BatchSummary
+where
+    P: Fn(Progress) + Sync,
+    J: FnMut(&JobSummary),
+{
+    let start = Instant::now();
+
+    let jobs: Vec<JobSummary> = jobs
+        .iter()
+        .map(|job| {
+            let job_start = Instant::now();
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| render_job(job, options, &on_progress)))
+                    .unwrap_or_else(|panic| Err(panic_message(panic)));
+            let seconds = job_start.elapsed().as_secs_f64();
+
+            let summary = JobSummary {
+                scene: job.scene.display().to_string(),
+                output: job.output.display().to_string(),
+                success: result.is_ok(),
+                error: result.as_ref().err().cloned(),
+                seconds,
+                rays_cast: result.unwrap_or(0),
+            };
+            on_job(&summary);
+            summary
+        })
+        .collect();
+
+    BatchSummary {
+        failures: jobs.iter().filter(|job| !job.success).count(),
+        jobs,
+        seconds: start.elapsed().as_secs_f64(),
+    }
+}
+
+/// Describe a panic caught from a job
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    let message = match panic.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => match panic.downcast_ref::<String>() {
+            Some(message) => message.clone(),
+            None => "unknown error".to_owned(),
+        },
+    };
+    format!("the render panicked: {}", message)
+}
+
+/// Render a single job & return how many rays it took
+fn render_job<P>(job: &BatchJob, options: &BatchOptions, on_progress: &P) -> Result<u64, String>
+where
+    P: Fn(Progress) + Sync,
+{
+    let output = job.output.display().to_string();
+    let format = match options.format {
+        Some(format) => format,
+        None => OutputFormat::from_path(&output)?,
+    };
+    if options.alpha && format != OutputFormat::Png {
+        return Err(format!(
+            "`{}` can't have an alpha channel as it isn't a PNG",
+            output
+        ));
+    }
+    let save = SaveOptions {
+        format,
+        ..options.save
+    };
+
+    let scene = load_scene(&job.scene.display().to_string()).map_err(|error| error.to_string())?;
+    let world = scene
+        .world
+        .set_integrator((options.integrator_for)(job.samples))
+        .generate();
+
+    let camera = match &job.camera {
+        Some(view) => view
+            .apply(&scene.camera)
+            .ok_or("the manifest's camera has no direction to look in")?,
+        None => scene.camera,
+    };
+    let camera = camera.with_size(
+        job.width.or(options.width).unwrap_or(camera.width()),
+        job.height.or(options.height).unwrap_or(camera.height()),
+    );
+
+    // The alpha channel comes from an extra pass on the end
+    let mut aovs = options.aovs.clone();
+    if options.alpha {
+        aovs.push(Aov::Alpha);
+    }
+    let (canvas, mut passes) = camera.render_passes_with_progress(&world, &aovs, on_progress);
+    let alpha = if options.alpha { passes.pop() } else { None };
+    let passes: Vec<(Aov, Canvas)> = options.aovs.iter().copied().zip(passes).collect();
+    save_render(&canvas, alpha.as_ref(), &passes, &output, &save)?;
+    Ok(world.rays_cast())
+}
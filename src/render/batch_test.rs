@@ -0,0 +1,159 @@
+use super::batch::{run_batch, BatchOptions, BatchSummary};
+use super::output::{OutputFormat, SaveOptions};
+use crate::image::{ExrPrecision, OutputTransform, PngDepth, PpmEncoding};
+use crate::primitives::{Aov, Integrator};
+use crate::scene::{load_manifest, BatchJob};
+
+const SCENE: &str = "
+- add: camera
+  width: 100
+  height: 100
+  field-of-view: 0.785
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 1, 1]
+
+- add: sphere
+";
+
+const MANIFEST: &str = "
+- scene: scene.yml
+  output: small.png
+  width: 8
+  height: 6
+
+- scene: missing.yml
+  output: missing.png
+
+- scene: scene.yml
+  output: moved.png
+  width: 4
+  height: 4
+  camera:
+    from: [0, 5, 0]
+    to: [0, 0, 0]
+    up: [0, 0, 1]
+";
+
+fn options() -> BatchOptions {
+    BatchOptions {
+        format: None,
+        save: SaveOptions {
+            format: OutputFormat::Png,
+            precision: ExrPrecision::Float,
+            png_depth: PngDepth::Eight,
+            ppm_encoding: PpmEncoding::Binary,
+            transform: OutputTransform::default(),
+        },
+        width: None,
+        height: None,
+        aovs: vec![],
+        alpha: false,
+        integrator_for: Box::new(|_| Integrator::Whitted),
+    }
+}
+
+fn run(jobs: &[BatchJob], options: &BatchOptions) -> BatchSummary {
+    run_batch(jobs, options, |_| {}, |_| {})
+}
+
+#[test]
+fn a_batch_keeps_going_after_a_job_fails() {
+    let directory = std::env::temp_dir().join("rtracer_batch_test");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("scene.yml"), SCENE).unwrap();
+    std::fs::write(directory.join("manifest.yml"), MANIFEST).unwrap();
+
+    let jobs = load_manifest(directory.join("manifest.yml").to_str().unwrap()).unwrap();
+    let summary = run(&jobs, &options());
+
+    assert_eq!(summary.jobs.len(), 3);
+    assert_eq!(summary.failures, 1);
+
+    let failed = &summary.jobs[1];
+    assert!(!failed.success);
+    assert!(failed.error.is_some());
+    assert_eq!(failed.rays_cast, 0);
+
+    for job in [&summary.jobs[0], &summary.jobs[2]] {
+        assert!(job.success, "{:?}", job.error);
+        assert!(job.rays_cast > 0);
+        assert!(std::path::Path::new(&job.output).exists());
+    }
+    // The whitted renderer casts at least one ray per pixel
+    assert!(summary.jobs[0].rays_cast >= 8 * 6);
+}
+
+#[test]
+fn a_job_which_panics_doesnt_stop_the_batch() {
+    let directory = std::env::temp_dir().join("rtracer_batch_panic_test");
+    std::fs::create_dir_all(&directory).unwrap();
+    // A transform of NaN gets past the loader but can't be rendered
+    let broken = format!("{}  transform:\n    - [scale, nan, 1, 1]\n", SCENE);
+    std::fs::write(directory.join("broken.yml"), broken).unwrap();
+    std::fs::write(directory.join("scene.yml"), SCENE).unwrap();
+    let manifest = "
+- scene: broken.yml
+  output: broken.png
+  width: 4
+  height: 4
+
+- scene: scene.yml
+  output: fine.png
+  width: 4
+  height: 4
+";
+    std::fs::write(directory.join("manifest.yml"), manifest).unwrap();
+
+    let jobs = load_manifest(directory.join("manifest.yml").to_str().unwrap()).unwrap();
+    let summary = run(&jobs, &options());
+
+    assert_eq!(summary.failures, 1);
+    let error = summary.jobs[0].error.as_ref().unwrap();
+    assert!(error.starts_with("the render panicked"), "{}", error);
+    assert!(summary.jobs[1].success, "{:?}", summary.jobs[1].error);
+}
+
+#[test]
+fn the_summary_is_saved_as_json() {
+    let directory = std::env::temp_dir().join("rtracer_batch_summary_test");
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("summary.json");
+
+    let summary = run(&[], &options());
+    summary.save(path.to_str().unwrap()).unwrap();
+
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(json["failures"], 0);
+    assert_eq!(json["jobs"], serde_json::json!([]));
+    assert!(json["seconds"].is_number());
+}
+
+#[test]
+fn batch_jobs_save_their_aovs() {
+    let directory = std::env::temp_dir().join("rtracer_batch_aov_test");
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(directory.join("scene.yml"), SCENE).unwrap();
+    std::fs::write(
+        directory.join("manifest.yml"),
+        "- scene: scene.yml\n  output: aov.png\n  width: 4\n  height: 4\n",
+    )
+    .unwrap();
+    let _ = std::fs::remove_file(directory.join("aov.depth.png"));
+
+    let jobs = load_manifest(directory.join("manifest.yml").to_str().unwrap()).unwrap();
+    let options = BatchOptions {
+        aovs: vec![Aov::Depth],
+        ..options()
+    };
+    let summary = run(&jobs, &options);
+
+    assert_eq!(summary.failures, 0, "{:?}", summary.jobs[0].error);
+    assert!(directory.join("aov.png").exists());
+    assert!(directory.join("aov.depth.png").exists());
+}
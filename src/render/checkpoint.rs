@@ -0,0 +1,212 @@
+use crate::image::{Accumulator, Canvas};
+use crate::primitives::{Camera, Integrator, IntegratorKind, Progress, WorldImpl};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::time::{Duration, Instant};
+
+/// The first line of every checkpoint file
+const MAGIC: &str = "RTRACER CHECKPOINT 1";
+
+/// What a render was started with, so it's only carried on with the same settings
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct RenderSettings {
+    pub scene: Option<String>,
+    /// The scene's `source_hash`, or `hash_world` for a world built in code, so edits are caught
+    pub scene_hash: u64,
+    /// The exact bits of the camera's field of view & transform
+    pub camera: Vec<u64>,
+    pub width: i32,
+    pub height: i32,
+    pub integrator: IntegratorKind,
+    pub samples: u32,
+    pub max_depth: u32,
+    pub seed: u64,
+}
+
+impl RenderSettings {
+    pub fn new(
+        scene: Option<String>,
+        scene_hash: u64,
+        camera: &Camera,
+        integrator: Integrator,
+    ) -> Self {
+        let transform = camera.transform();
+        let view = std::iter::once(camera.fov())
+            .chain((0..16).map(|i| transform.at(i / 4, i % 4)))
+            .map(f64::to_bits)
+            .collect();
+
+        let (samples, max_depth, seed) = match integrator {
+            Integrator::Whitted => (1, 0, 0),
+            Integrator::PathTracer {
+                samples,
+                max_depth,
+                seed,
+                ..
+            } => (samples, max_depth, seed),
+        };
+        Self {
+            scene,
+            scene_hash,
+            camera: view,
+            width: camera.width(),
+            height: camera.height(),
+            integrator: integrator.kind(),
+            samples,
+            max_depth,
+            seed,
+        }
+    }
+
+    /*
+        Check a checkpoint saved with these settings can be carried on with `current`.
+        Everything has to match, including the text of the scene files, apart from the
+        samples, which can be raised to keep improving a finished render.
+    */
+    pub fn check_resumable(&self, current: &RenderSettings) -> Result<(), String> {
+        let samples = RenderSettings {
+            samples: current.samples,
+            ..self.clone()
+        };
+        if samples == *current {
+            return Ok(());
+        }
+
+        let mut differences = vec![];
+        if self.scene != current.scene || self.scene_hash != current.scene_hash {
+            differences.push("scene");
+        }
+        if self.camera != current.camera {
+            differences.push("camera");
+        }
+        if (self.width, self.height) != (current.width, current.height) {
+            differences.push("size");
+        }
+        if self.integrator != current.integrator {
+            differences.push("integrator");
+        }
+        if self.max_depth != current.max_depth {
+            differences.push("max depth");
+        }
+        if self.seed != current.seed {
+            differences.push("seed");
+        }
+        Err(format!(
+            "it was rendered with a different {}",
+            differences.join(", ")
+        ))
+    }
+}
+
+/*
+    Save the settings & accumulation to `path`. It's written next to it first & then
+    moved into place, so a render which dies while saving keeps the last checkpoint.
+*/
+pub fn save_checkpoint(
+    path: &str,
+    settings: &RenderSettings,
+    accumulator: &Accumulator,
+) -> Result<(), String> {
+    let error = |error: std::io::Error| format!("failed to save `{}`: {}", path, error);
+    let temp = format!("{}.tmp", path);
+
+    let mut writer = BufWriter::new(File::create(&temp).map_err(error)?);
+    let json = serde_json::to_string(settings).map_err(|error| error.to_string())?;
+    writeln!(writer, "{}\n{}", MAGIC, json).map_err(error)?;
+    accumulator.write_to(&mut writer).map_err(error)?;
+    writer
+        .into_inner()
+        .map_err(|error| error.to_string())?
+        .sync_all()
+        .map_err(error)?;
+
+    std::fs::rename(&temp, path).map_err(error)
+}
+
+pub fn load_checkpoint(path: &str) -> Result<(RenderSettings, Accumulator), String> {
+    let error = |error: std::io::Error| format!("failed to load `{}`: {}", path, error);
+    let mut reader = BufReader::new(File::open(path).map_err(error)?);
+
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(error)?;
+    if line.trim_end() != MAGIC {
+        return Err(format!("`{}` isn't a checkpoint", path));
+    }
+
+    line.clear();
+    reader.read_line(&mut line).map_err(error)?;
+    let settings: RenderSettings = serde_json::from_str(&line)
+        .map_err(|error| format!("failed to load `{}`: {}", path, error))?;
+
+    let accumulator = Accumulator::read_from(&mut reader).map_err(error)?;
+    let size = (accumulator.image().width(), accumulator.image().height());
+    if size != (settings.width, settings.height) || reader.read(&mut [0]).map_err(error)? != 0 {
+        return Err(format!("`{}` is corrupt", path));
+    }
+
+    Ok((settings, accumulator))
+}
+
+/// What happens during a render with checkpoints, for showing to the user
+#[derive(Clone, Debug, PartialEq)]
+pub enum CheckpointEvent {
+    /// Carrying on from a checkpoint which already has this many samples in every pixel
+    Resumed {
+        samples: u32,
+    },
+    Progress(Progress),
+    /// A checkpoint couldn't be saved
+    SaveFailed(String),
+}
+
+/*
+    Render a pass at a time, saving a checkpoint every `interval` & once the render is
+    finished. With `resume` the render carries on from the checkpoint already at `path`
+    & gives the same image as if it had never stopped. `on_event` is called from the
+    rendering threads, like the progress callbacks of `Camera`.
+*/
+pub fn render_with_checkpoints<F>(
+    world: &WorldImpl,
+    camera: &Camera,
+    settings: &RenderSettings,
+    path: &str,
+    interval: Duration,
+    resume: bool,
+    on_event: F,
+) -> Result<Canvas, String>
+where
+    F: Fn(CheckpointEvent) + Sync,
+{
+    let mut accumulator = if resume {
+        let (saved, accumulator) = load_checkpoint(path)?;
+        saved
+            .check_resumable(settings)
+            .map_err(|error| format!("can't resume from `{}`: {}", path, error))?;
+        on_event(CheckpointEvent::Resumed {
+            samples: accumulator.min_samples(),
+        });
+        accumulator
+    } else {
+        Accumulator::new(camera.width(), camera.height())
+    };
+
+    let mut last_saved = Instant::now();
+    camera.accumulate(
+        world,
+        &mut accumulator,
+        |progress| on_event(CheckpointEvent::Progress(progress)),
+        |accumulator| {
+            let finished = accumulator.min_samples() >= settings.samples;
+            if finished || last_saved.elapsed() >= interval {
+                // Keep rendering if a checkpoint can't be saved, there may be room for the next one
+                if let Err(error) = save_checkpoint(path, settings, accumulator) {
+                    on_event(CheckpointEvent::SaveFailed(error));
+                }
+                last_saved = Instant::now();
+            }
+        },
+    );
+
+    Ok(accumulator.image().clone())
+}
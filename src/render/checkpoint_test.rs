@@ -0,0 +1,203 @@
+use super::checkpoint::{
+    load_checkpoint, render_with_checkpoints, save_checkpoint, CheckpointEvent, RenderSettings,
+};
+use crate::image::{Accumulator, Canvas, Color};
+use crate::maths::{Matrix4x4, Point, Vector};
+use crate::primitives::{Camera, Integrator, IntegratorKind, World, WorldImpl};
+use std::sync::Mutex;
+use std::time::Duration;
+
+fn camera() -> Camera {
+    Camera::new(
+        12,
+        8,
+        std::f64::consts::FRAC_PI_2,
+        Matrix4x4::view(
+            Point::new(0.0, 0.0, -5.0),
+            Point::new(0.0, 0.0, 0.0),
+            Vector::up(),
+        ),
+    )
+}
+
+fn world(samples: u32) -> WorldImpl {
+    World::default()
+        .set_integrator(Integrator::path_tracer(samples, 3).with_seed(9))
+        .generate()
+}
+
+fn settings(samples: u32) -> RenderSettings {
+    RenderSettings::new(
+        Some("scene.yml".to_owned()),
+        42,
+        &camera(),
+        world(samples).integrator(),
+    )
+}
+
+fn temp_path(name: &str) -> String {
+    let directory = std::env::temp_dir().join("rtracer_checkpoint_test");
+    std::fs::create_dir_all(&directory).unwrap();
+    directory.join(name).to_str().unwrap().to_owned()
+}
+
+/// The exact bits of every pixel, to check images are identical rather than just close
+fn bits(canvas: &Canvas) -> Vec<u64> {
+    (0..canvas.height())
+        .flat_map(|y| (0..canvas.width()).map(move |x| canvas.pixel_at(x, y)))
+        .flat_map(|c| [c.r().to_bits(), c.g().to_bits(), c.b().to_bits()])
+        .collect()
+}
+
+#[test]
+fn settings_record_the_integrator() {
+    let s = settings(16);
+    assert_eq!((s.width, s.height), (12, 8));
+    assert_eq!(s.integrator, IntegratorKind::Path);
+    assert_eq!((s.samples, s.max_depth, s.seed), (16, 3, 9));
+    assert_eq!(s.scene_hash, 42);
+    assert_eq!(s.camera.len(), 17);
+    assert_eq!(f64::from_bits(s.camera[0]), std::f64::consts::FRAC_PI_2);
+}
+
+#[test]
+fn only_the_samples_can_change_when_resuming() {
+    assert!(settings(4).check_resumable(&settings(64)).is_ok());
+
+    let other_seed = RenderSettings {
+        seed: 2,
+        width: 13,
+        ..settings(4)
+    };
+    assert_eq!(
+        settings(4).check_resumable(&other_seed),
+        Err("it was rendered with a different size, seed".to_owned())
+    );
+
+    // Editing the scene file or moving the camera can't be carried on either
+    let edited = RenderSettings {
+        scene_hash: 43,
+        ..settings(4)
+    };
+    assert_eq!(
+        settings(4).check_resumable(&edited),
+        Err("it was rendered with a different scene".to_owned())
+    );
+
+    let moved = Camera::new(12, 8, std::f64::consts::FRAC_PI_3, Matrix4x4::identity());
+    let moved = RenderSettings::new(
+        Some("scene.yml".to_owned()),
+        42,
+        &moved,
+        world(4).integrator(),
+    );
+    assert_eq!(
+        settings(4).check_resumable(&moved),
+        Err("it was rendered with a different camera".to_owned())
+    );
+}
+
+#[test]
+fn checkpoints_can_be_saved_and_loaded() {
+    let path = temp_path("saved.ckpt");
+    let mut accumulator = Accumulator::new(12, 8);
+    accumulator.add_sample(3, 4, Color::new(0.25, 0.5, 2.0));
+
+    save_checkpoint(&path, &settings(4), &accumulator).unwrap();
+    let (loaded_settings, loaded) = load_checkpoint(&path).unwrap();
+
+    assert_eq!(loaded_settings, settings(4));
+    assert_eq!(loaded.samples_at(3, 4), 1);
+    assert_eq!(bits(loaded.image()), bits(accumulator.image()));
+    assert!(!std::path::Path::new(&format!("{}.tmp", path)).exists());
+}
+
+#[test]
+fn files_which_arent_checkpoints_are_rejected() {
+    let path = temp_path("not_a_checkpoint.ckpt");
+    std::fs::write(&path, "P3\n1 1\n255\n0 0 0\n").unwrap();
+    assert!(load_checkpoint(&path)
+        .unwrap_err()
+        .contains("isn't a checkpoint"));
+
+    // A checkpoint which is cut short
+    let mut accumulator = Accumulator::new(12, 8);
+    accumulator.add_sample(0, 0, Color::white());
+    save_checkpoint(&path, &settings(4), &accumulator).unwrap();
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 10]).unwrap();
+    assert!(load_checkpoint(&path).is_err());
+
+    assert!(load_checkpoint(&temp_path("missing.ckpt")).is_err());
+}
+
+#[test]
+fn a_resumed_render_matches_one_which_never_stopped() {
+    let path = temp_path("resumed.ckpt");
+    let every_pass = Duration::from_secs(0);
+
+    // Stop after half the samples, then carry on with all of them
+    render_with_checkpoints(
+        &world(2),
+        &camera(),
+        &settings(2),
+        &path,
+        every_pass,
+        false,
+        |_| {},
+    )
+    .unwrap();
+    let (_, halfway) = load_checkpoint(&path).unwrap();
+    assert_eq!(halfway.min_samples(), 2);
+
+    let resumed_from = Mutex::new(None);
+    let resumed = render_with_checkpoints(
+        &world(4),
+        &camera(),
+        &settings(4),
+        &path,
+        every_pass,
+        true,
+        |event| {
+            if let CheckpointEvent::Resumed { samples } = event {
+                *resumed_from.lock().unwrap() = Some(samples);
+            }
+        },
+    )
+    .unwrap();
+
+    assert_eq!(*resumed_from.lock().unwrap(), Some(2));
+    assert_eq!(bits(&resumed), bits(&camera().render(&world(4))));
+    assert_eq!(load_checkpoint(&path).unwrap().1.min_samples(), 4);
+}
+
+#[test]
+fn a_checkpoint_is_only_resumed_with_the_same_settings() {
+    let path = temp_path("mismatched.ckpt");
+    let every_pass = Duration::from_secs(0);
+    render_with_checkpoints(
+        &world(1),
+        &camera(),
+        &settings(1),
+        &path,
+        every_pass,
+        false,
+        |_| {},
+    )
+    .unwrap();
+
+    let other_scene = RenderSettings {
+        scene: None,
+        ..settings(2)
+    };
+    let result = render_with_checkpoints(
+        &world(2),
+        &camera(),
+        &other_scene,
+        &path,
+        every_pass,
+        true,
+        |_| {},
+    );
+    assert!(result.unwrap_err().contains("different scene"));
+}
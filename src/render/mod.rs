@@ -0,0 +1,23 @@
+// Code files
+mod batch;
+mod checkpoint;
+mod output;
+mod progressive;
+mod worker;
+
+// Exports
+pub use batch::{run_batch, BatchOptions, BatchSummary, JobSummary};
+pub use checkpoint::{
+    load_checkpoint, render_with_checkpoints, save_checkpoint, CheckpointEvent, RenderSettings,
+};
+pub use output::{aov_path, save_image, save_render, OutputFormat, SaveOptions};
+pub use progressive::coarse_to_fine;
+pub use worker::{Worker, WorkerState};
+
+// Tests
+#[cfg(test)]
+mod batch_test;
+#[cfg(test)]
+mod checkpoint_test;
+#[cfg(test)]
+mod progressive_test;
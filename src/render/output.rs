@@ -0,0 +1,161 @@
+use crate::image::{
+    save_exr, save_hdr, save_png, save_ppm, Canvas, ExrPrecision, OutputTransform, PngDepth,
+    PpmEncoding,
+};
+use crate::primitives::Aov;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The image formats a render can be saved in
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OutputFormat {
+    /// 8 or 16 bits per channel, clamped to 0 to 1
+    Png,
+    /// The book's format, 8 bits per channel
+    Ppm,
+    /// OpenEXR, floating point with the AOVs as extra layers
+    Exr,
+    /// Radiance RGBE, floating point
+    Hdr,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 4] = [
+        OutputFormat::Png,
+        OutputFormat::Ppm,
+        OutputFormat::Exr,
+        OutputFormat::Hdr,
+    ];
+
+    /// The name of the format, which is also its file extension
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Ppm => "ppm",
+            OutputFormat::Exr => "exr",
+            OutputFormat::Hdr => "hdr",
+        }
+    }
+
+    /// Work out the format from a file's extension
+    pub fn from_path(path: &str) -> Result<OutputFormat, String> {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        extension
+            .and_then(|extension| extension.parse().ok())
+            .ok_or_else(|| format!("can't tell the image format of `{}`", path))
+    }
+
+    /// Whether values outside 0 to 1 survive being saved
+    pub fn is_float(&self) -> bool {
+        matches!(self, OutputFormat::Exr | OutputFormat::Hdr)
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        OutputFormat::ALL
+            .iter()
+            .find(|format| format.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names: Vec<&str> = OutputFormat::ALL
+                    .iter()
+                    .map(|format| format.name())
+                    .collect();
+                format!(
+                    "unknown image format `{}`. Expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Everything which decides how a render is written out
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SaveOptions {
+    pub format: OutputFormat,
+    pub precision: ExrPrecision,
+    pub png_depth: PngDepth,
+    pub ppm_encoding: PpmEncoding,
+    /// Only applied to PNGs & PPMs. Floating point formats keep the linear values.
+    pub transform: OutputTransform,
+}
+
+/// Where to save an AOV for an image, e.g. `out.depth.png` for `out.png`
+pub fn aov_path(output: &str, aov: Aov) -> String {
+    let path = Path::new(output);
+    let name = match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => format!(
+            "{}.{}.{}",
+            stem.to_string_lossy(),
+            aov.name(),
+            extension.to_string_lossy()
+        ),
+        _ => format!("{}.{}", path.to_string_lossy(), aov.name()),
+    };
+    path.with_file_name(name).to_string_lossy().into_owned()
+}
+
+/// Save an image. Only PNGs can use the alpha canvas, other formats are always opaque.
+pub fn save_image(
+    canvas: &Canvas,
+    alpha: Option<&Canvas>,
+    path: &str,
+    options: &SaveOptions,
+) -> Result<(), String> {
+    let transform = &options.transform;
+    let result = match options.format {
+        OutputFormat::Png => save_png(canvas, alpha, path.to_owned(), transform, options.png_depth)
+            .map_err(|e| e.to_string()),
+        OutputFormat::Ppm => save_ppm(canvas, path.to_owned(), transform, options.ppm_encoding)
+            .map_err(|e| e.to_string()),
+        OutputFormat::Exr => {
+            save_exr(&[("", canvas)], path, options.precision).map_err(|e| e.to_string())
+        }
+        OutputFormat::Hdr => save_hdr(canvas, path.to_owned()).map_err(|e| e.to_string()),
+    };
+    result.map_err(|error| format!("failed to save `{}`: {}", path, error))
+}
+
+/*
+    Save an image along with its AOVs. EXR files hold every pass as a layer of one file,
+    other formats get a file per AOV next to the image. Floating point formats keep the
+    raw AOV values while PNGs get the version which is easy to look at, without any
+    gamma or tone mapping since the passes are data rather than light.
+*/
+pub fn save_render(
+    canvas: &Canvas,
+    alpha: Option<&Canvas>,
+    passes: &[(Aov, Canvas)],
+    path: &str,
+    options: &SaveOptions,
+) -> Result<(), String> {
+    if options.format == OutputFormat::Exr {
+        let layers: Vec<(&str, &Canvas)> = std::iter::once(("", canvas))
+            .chain(passes.iter().map(|(aov, pass)| (aov.name(), pass)))
+            .collect();
+        return save_exr(&layers, path, options.precision)
+            .map_err(|error| format!("failed to save `{}`: {}", path, error));
+    }
+
+    save_image(canvas, alpha, path, options)?;
+    let data = SaveOptions {
+        transform: OutputTransform::linear(),
+        ..*options
+    };
+    for (aov, pass) in passes {
+        let path = aov_path(path, *aov);
+        if options.format.is_float() {
+            save_image(pass, None, &path, &data)?;
+        } else {
+            save_image(&aov.display(pass), None, &path, &data)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,24 @@
+/// The biggest block drawn for a pixel before the image has had a full pass
+const LARGEST_BLOCK: i32 = 8;
+
+/*
+    Every pixel in the image with the size of the block it should cover until its
+    neighbours are rendered. Pixels on a grid of 8 come first, then the ones on a grid
+    of 4 & so on, so a blocky version of the whole image appears before it sharpens.
+*/
+pub fn coarse_to_fine(width: i32, height: i32) -> Vec<(i32, i32, i32)> {
+    let block_size = |x: i32, y: i32| {
+        let mut size = LARGEST_BLOCK;
+        while x % size != 0 || y % size != 0 {
+            size /= 2;
+        }
+        size
+    };
+
+    let mut pixels: Vec<(i32, i32, i32)> = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y, block_size(x, y))))
+        .collect();
+    // A stable sort keeps the rows in order within each size
+    pixels.sort_by_key(|&(_, _, size)| -size);
+    pixels
+}
@@ -0,0 +1,25 @@
+use super::progressive::coarse_to_fine;
+
+#[test]
+fn every_pixel_is_rendered_once() {
+    let mut pixels: Vec<(i32, i32)> = coarse_to_fine(13, 9)
+        .into_iter()
+        .map(|(x, y, _)| (x, y))
+        .collect();
+    pixels.sort();
+    pixels.dedup();
+
+    assert_eq!(pixels.len(), 13 * 9);
+}
+
+#[test]
+fn the_biggest_blocks_are_rendered_first() {
+    let pixels = coarse_to_fine(16, 16);
+
+    assert_eq!(&pixels[..4], &[(0, 0, 8), (8, 0, 8), (0, 8, 8), (8, 8, 8)]);
+    assert_eq!(pixels[4], (4, 0, 4));
+    assert_eq!(pixels.last(), Some(&(15, 15, 1)));
+
+    let sizes: Vec<i32> = pixels.iter().map(|&(_, _, size)| size).collect();
+    assert!(sizes.windows(2).all(|pair| pair[0] >= pair[1]));
+}
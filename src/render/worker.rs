@@ -0,0 +1,174 @@
+use rayon::prelude::*;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::Arc;
+use std::thread;
+use thread::JoinHandle;
+
+#[derive(PartialEq, Debug)]
+pub enum WorkerState<R> {
+    Values(R),
+    Complete,
+}
+
+pub struct Worker<R> {
+    receiver: Receiver<R>,
+    cancelled: Arc<AtomicBool>,
+    handle: JoinHandle<()>,
+}
+
+impl<R> Worker<R> {
+    pub fn new<I, S, W>(state: I, worker: W, chunk_size: usize) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: Send + 'static,
+        S: Send + 'static,
+        W: Fn(S) -> R + Send + 'static + std::marker::Sync,
+        R: Send + 'static,
+    {
+        Self::cancellable(state, move |item, _| worker(item), chunk_size)
+    }
+
+    /// Like `new`, but the worker can see when it's cancelled & stop part way through an item
+    pub fn cancellable<I, S, W>(state: I, worker: W, chunk_size: usize) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: Send + 'static,
+        S: Send + 'static,
+        W: Fn(S, &AtomicBool) -> R + Send + 'static + std::marker::Sync,
+        R: Send + 'static,
+    {
+        // Create a channel and send to a thread
+        let (tx, rx) = mpsc::channel();
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let thread_cancelled = cancelled.clone();
+
+        let mut items = state.into_iter();
+        let thread = thread::spawn(move || {
+            // Chop the state into multiple parts as it's needed
+            loop {
+                // Check to see whether we continue the loop
+                if thread_cancelled.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                let chunk: Vec<S> = items.by_ref().take(chunk_size).collect();
+                if chunk.is_empty() {
+                    break;
+                }
+
+                // iterate over each of the parts using rayon, skipping the rest once cancelled
+                chunk
+                    .into_par_iter()
+                    .for_each_with(tx.clone(), |tsender, item| {
+                        if !thread_cancelled.load(Ordering::Relaxed) {
+                            let _ = tsender.send(worker(item, &thread_cancelled));
+                        }
+                    });
+            }
+        });
+
+        Self {
+            receiver: rx,
+            handle: thread,
+            cancelled,
+        }
+    }
+
+    pub fn fetch(&self) -> WorkerState<Vec<R>> {
+        let mut vals = vec![];
+        loop {
+            let result = self.receiver.try_recv();
+            match result {
+                Ok(val) => {
+                    vals.push(val);
+                }
+                Err(err) => match err {
+                    mpsc::TryRecvError::Empty => {
+                        return WorkerState::Values(vals);
+                    }
+                    mpsc::TryRecvError::Disconnected => {
+                        if vals.is_empty() {
+                            return WorkerState::Complete;
+                        } else {
+                            return WorkerState::Values(vals);
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    /// Stop starting new items without waiting for the ones in progress to finish
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn finish(self) {
+        self.cancel();
+
+        // Wait for the thread to finish
+        self.handle.join().unwrap();
+    }
+}
+
+#[test]
+fn create_generator() {
+    let vals = vec![1, 2, 3];
+    let worker = Worker::new(vals, |val| val + 1, 3);
+
+    let mut results = Vec::new();
+
+    while let WorkerState::Values(mut vals) = worker.fetch() {
+        results.append(&mut vals);
+    }
+
+    results.sort();
+    assert_eq!(results, vec![2, 3, 4]);
+
+    // Calling fetch again returns complete
+    assert_eq!(worker.fetch(), WorkerState::Complete);
+}
+
+#[test]
+fn work_can_be_generated_lazily() {
+    let worker = Worker::new((0..10).map(|val| val * 2), |val| val + 1, 4);
+
+    let mut results = Vec::new();
+    while let WorkerState::Values(mut vals) = worker.fetch() {
+        results.append(&mut vals);
+    }
+
+    results.sort();
+    assert_eq!(results, vec![1, 3, 5, 7, 9, 11, 13, 15, 17, 19]);
+}
+
+#[test]
+fn cancelling_stops_the_work_in_progress() {
+    let (started_tx, started_rx) = mpsc::channel();
+    let started_tx = std::sync::Mutex::new(started_tx);
+    let worker = Worker::cancellable(
+        0..1000,
+        move |val, cancelled: &AtomicBool| {
+            let _ = started_tx.lock().unwrap().send(());
+            while !cancelled.load(Ordering::Relaxed) {
+                thread::yield_now();
+            }
+            val
+        },
+        4,
+    );
+
+    // Cancelling returns straight away & the items in progress see it
+    started_rx.recv().unwrap();
+    worker.cancel();
+
+    let mut results = Vec::new();
+    while let WorkerState::Values(mut vals) = worker.fetch() {
+        results.append(&mut vals);
+    }
+    assert!(results.len() <= 4);
+    worker.finish();
+}
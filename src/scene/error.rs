@@ -0,0 +1,66 @@
+use std::fmt;
+
+/// A problem reading a scene file, along with where it was found
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneError {
+    pub message: String,
+    /// The key whose value couldn't be used, if the problem was with a value
+    pub key: Option<String>,
+    /// 1-based position in the file. A line of 0 means the problem wasn't in the text.
+    pub line: usize,
+    pub column: usize,
+    pub file: Option<String>,
+}
+
+impl SceneError {
+    pub fn new(message: String, line: usize, column: usize) -> Self {
+        Self {
+            message,
+            key: None,
+            line,
+            column,
+            file: None,
+        }
+    }
+
+    pub fn io(path: &str, error: std::io::Error) -> Self {
+        Self {
+            message: error.to_string(),
+            key: None,
+            line: 0,
+            column: 0,
+            file: Some(path.to_owned()),
+        }
+    }
+
+    pub fn with_key(mut self, key: &str) -> Self {
+        self.key = Some(key.to_owned());
+        self
+    }
+
+    /// Record the file the error came from, unless an included file already has
+    pub fn in_file(mut self, path: &str) -> Self {
+        if self.file.is_none() {
+            self.file = Some(path.to_owned());
+        }
+        self
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        if self.line > 0 {
+            write!(f, "{}:{}:", self.line, self.column)?;
+        }
+        write!(f, " {}", self.message)?;
+        if let Some(key) = &self.key {
+            write!(f, " (key `{}`)", key)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SceneError {}
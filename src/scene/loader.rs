@@ -0,0 +1,498 @@
+use super::{
+    node::{check_keys, get, parse_yaml, required, Node, Value},
+    SceneError,
+};
+use crate::{
+    image::Color,
+    maths::{Matrix4x4, Point, Vector},
+    primitives::{Camera, Material, Microfacet, PointLight, Shape, StripePattern, World},
+};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Everything needed to render a scene file
+pub struct Scene {
+    pub world: World,
+    pub camera: Camera,
+    /// A hash of the text of the scene & every file it includes, to tell when any of them change
+    pub source_hash: u64,
+}
+
+const CAMERA_KEYS: [&str; 7] = [
+    "add",
+    "width",
+    "height",
+    "field-of-view",
+    "from",
+    "to",
+    "up",
+];
+const LIGHT_KEYS: [&str; 3] = ["add", "at", "intensity"];
+const SHAPE_KEYS: [&str; 3] = ["add", "material", "transform"];
+const MATERIAL_KEYS: [&str; 13] = [
+    "color",
+    "ambient",
+    "diffuse",
+    "specular",
+    "shininess",
+    "reflective",
+    "transparency",
+    "refractive-index",
+    "emission",
+    "absorption",
+    "metallic",
+    "roughness",
+    "pattern",
+];
+const PATTERN_KEYS: [&str; 2] = ["type", "colors"];
+
+const DEFINE_KEYS: [&str; 3] = ["define", "extend", "value"];
+const INCLUDE_KEYS: [&str; 1] = ["include"];
+
+/// Named values which later entries can refer to
+type Definitions = HashMap<String, Node>;
+
+pub fn load_scene(path: &str) -> Result<Scene, SceneError> {
+    let mut builder = SceneBuilder::new();
+    builder.load_file(Path::new(path))?;
+    builder.finish().map_err(|error| error.in_file(path))
+}
+
+/// Read a scene from YAML in the style of the Ray Tracer Challenge's scene files.
+/// Included files are found relative to the working directory.
+pub fn parse_scene(text: &str) -> Result<Scene, SceneError> {
+    let mut builder = SceneBuilder::new();
+    builder.source_hash = hash_text(builder.source_hash, text);
+    builder.load_items(&parse_yaml(text)?, Path::new("."))?;
+    builder.finish()
+}
+
+struct SceneBuilder {
+    world: World,
+    camera: Option<Camera>,
+    definitions: Definitions,
+    // The files currently being loaded, to catch files which include themselves
+    files: Vec<PathBuf>,
+    source_hash: u64,
+}
+
+impl SceneBuilder {
+    fn new() -> Self {
+        Self {
+            world: World::new(),
+            camera: None,
+            definitions: HashMap::new(),
+            files: vec![],
+            source_hash: FNV_OFFSET,
+        }
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), SceneError> {
+        let name = path.display().to_string();
+        let text = std::fs::read_to_string(path).map_err(|error| SceneError::io(&name, error))?;
+        let canonical = path
+            .canonicalize()
+            .map_err(|error| SceneError::io(&name, error))?;
+
+        self.source_hash = hash_text(self.source_hash, &text);
+        self.files.push(canonical);
+        let directory = path.parent().unwrap_or_else(|| Path::new("."));
+        let result = parse_yaml(&text).and_then(|root| self.load_items(&root, directory));
+        self.files.pop();
+
+        result.map_err(|error| error.in_file(&name))
+    }
+
+    fn load_items(&mut self, root: &Node, directory: &Path) -> Result<(), SceneError> {
+        for item in root.as_sequence("scene")? {
+            let entries = item.as_mapping("scene")?;
+
+            if let Some(kind) = get(entries, "add") {
+                self.add(kind, item, entries)?;
+            } else if get(entries, "define").is_some() {
+                self.define(item, entries)?;
+            } else if let Some(include) = get(entries, "include") {
+                check_keys(entries, &INCLUDE_KEYS)?;
+                self.include(include, directory)?;
+            } else {
+                return Err(item
+                    .error("expected an `add`, `define` or `include` entry")
+                    .with_key("add"));
+            }
+        }
+        Ok(())
+    }
+
+    fn add(
+        &mut self,
+        kind: &Node,
+        item: &Node,
+        entries: &[(Node, Node)],
+    ) -> Result<(), SceneError> {
+        match kind.as_str("add")? {
+            "camera" => self.camera = Some(parse_camera(item, entries)?),
+            "light" => {
+                let light = parse_light(item, entries)?;
+                self.world = std::mem::replace(&mut self.world, World::new()).add_light(light);
+            }
+            "sphere" | "plane" => {
+                let shape = parse_shape(kind, entries, &self.definitions)?;
+                self.world = std::mem::replace(&mut self.world, World::new()).add_object(shape);
+            }
+            other => {
+                return Err(kind
+                    .error(&format!("unknown object `{}`", other))
+                    .with_key("add"))
+            }
+        }
+        Ok(())
+    }
+
+    /*
+        Store a named value. Lists have any names inside them replaced straight away
+        & `extend` starts from an earlier definition, so definitions can only refer
+        to ones before them & can't form loops.
+    */
+    fn define(&mut self, item: &Node, entries: &[(Node, Node)]) -> Result<(), SceneError> {
+        check_keys(entries, &DEFINE_KEYS)?;
+
+        let name = required(item, entries, "define")?.as_str("define")?;
+        let value = expand_names(required(item, entries, "value")?, &self.definitions)?;
+
+        let value = match get(entries, "extend") {
+            Some(base) => {
+                let base_name = base.as_str("extend")?;
+                let base_value = self.definitions.get(base_name).ok_or_else(|| {
+                    base.error(&format!("unknown definition `{}`", base_name))
+                        .with_key("extend")
+                })?;
+                merge(base_value, &value).ok_or_else(|| {
+                    value
+                        .error("can only extend a definition of the same kind")
+                        .with_key("extend")
+                })?
+            }
+            None => value,
+        };
+
+        self.definitions.insert(name.to_owned(), value);
+        Ok(())
+    }
+
+    fn include(&mut self, include: &Node, directory: &Path) -> Result<(), SceneError> {
+        let path = directory.join(include.as_str("include")?);
+
+        if let Ok(canonical) = path.canonicalize() {
+            if self.files.contains(&canonical) {
+                let chain: Vec<String> = self
+                    .files
+                    .iter()
+                    .chain(std::iter::once(&canonical))
+                    .map(|file| file.display().to_string())
+                    .collect();
+                return Err(include
+                    .error(&format!("files include each other: {}", chain.join(" -> ")))
+                    .with_key("include"));
+            }
+        }
+
+        self.load_file(&path).map_err(|error| {
+            // Report a missing file at the include rather than as a bare file error
+            if error.line == 0 && error.file == Some(path.display().to_string()) {
+                include.error(&error.message).with_key("include")
+            } else {
+                error
+            }
+        })
+    }
+
+    fn finish(self) -> Result<Scene, SceneError> {
+        let camera = self
+            .camera
+            .ok_or_else(|| SceneError::new("the scene has no camera".to_owned(), 0, 0))?;
+        Ok(Scene {
+            world: self.world,
+            camera,
+            source_hash: self.source_hash,
+        })
+    }
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// A hash like `source_hash` for a world built in code, to tell when it changes
+pub fn hash_world(world: &World) -> u64 {
+    hash_text(FNV_OFFSET, &format!("{:?}", world))
+}
+
+/// Add some text to an FNV-1a hash, which unlike the standard library's hasher is the same on every build
+fn hash_text(hash: u64, text: &str) -> u64 {
+    text.bytes().fold(hash, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Look up a value given by name, or use the value as it is
+fn resolve<'a>(
+    node: &'a Node,
+    key: &str,
+    definitions: &'a Definitions,
+) -> Result<&'a Node, SceneError> {
+    match &node.value {
+        Value::Scalar(name) => definitions.get(name).ok_or_else(|| {
+            node.error(&format!("unknown definition `{}`", name))
+                .with_key(key)
+        }),
+        _ => Ok(node),
+    }
+}
+
+/// Replace any names in a list with the items of the list they name
+fn expand_names(node: &Node, definitions: &Definitions) -> Result<Node, SceneError> {
+    let items = match &node.value {
+        Value::Sequence(items) => items,
+        _ => return Ok(node.clone()),
+    };
+
+    let mut expanded = vec![];
+    for item in items {
+        match &item.value {
+            Value::Scalar(_) => match &resolve(item, "value", definitions)?.value {
+                Value::Sequence(named) => expanded.extend(named.iter().cloned()),
+                _ => return Err(item.error("expected the name of a list").with_key("value")),
+            },
+            _ => expanded.push(item.clone()),
+        }
+    }
+
+    Ok(Node {
+        value: Value::Sequence(expanded),
+        line: node.line,
+        column: node.column,
+    })
+}
+
+/// Combine a definition with the entries which extend it. Later entries win.
+fn merge(base: &Node, extension: &Node) -> Option<Node> {
+    let value = match (&base.value, &extension.value) {
+        (Value::Mapping(base_entries), Value::Mapping(entries)) => {
+            let mut merged: Vec<(Node, Node)> = base_entries
+                .iter()
+                .filter(|(name, _)| !entries.iter().any(|(other, _)| other.value == name.value))
+                .cloned()
+                .collect();
+            merged.extend(entries.iter().cloned());
+            Value::Mapping(merged)
+        }
+        (Value::Sequence(base_items), Value::Sequence(items)) => {
+            Value::Sequence(base_items.iter().chain(items.iter()).cloned().collect())
+        }
+        _ => return None,
+    };
+
+    Some(Node {
+        value,
+        line: extension.line,
+        column: extension.column,
+    })
+}
+
+fn parse_color(node: &Node, key: &str) -> Result<Color, SceneError> {
+    let [r, g, b] = node.as_numbers::<3>(key)?;
+    Ok(Color::new(r, g, b))
+}
+
+fn parse_point(node: &Node, key: &str) -> Result<Point, SceneError> {
+    let [x, y, z] = node.as_numbers::<3>(key)?;
+    Ok(Point::new(x, y, z))
+}
+
+fn parse_camera(item: &Node, entries: &[(Node, Node)]) -> Result<Camera, SceneError> {
+    check_keys(entries, &CAMERA_KEYS)?;
+
+    let width = required(item, entries, "width")?.as_positive_i32("width")?;
+    let height = required(item, entries, "height")?.as_positive_i32("height")?;
+    let fov = required(item, entries, "field-of-view")?.as_f64("field-of-view")?;
+    let from = parse_point(required(item, entries, "from")?, "from")?;
+    let to_node = required(item, entries, "to")?;
+    let to = parse_point(to_node, "to")?;
+    let up_node = required(item, entries, "up")?;
+    let [x, y, z] = up_node.as_numbers::<3>("up")?;
+    let up = Vector::new(x, y, z);
+    check_view(to_node, up_node, from, to, up)?;
+
+    Ok(Camera::new(
+        width,
+        height,
+        fov,
+        Matrix4x4::view(from, to, up),
+    ))
+}
+
+/// Make sure a camera looking from `from` to `to` with `up` as the top of the image has a direction to look in
+pub(super) fn check_view(
+    to_node: &Node,
+    up_node: &Node,
+    from: Point,
+    to: Point,
+    up: Vector,
+) -> Result<(), SceneError> {
+    let forward = (to - from).len();
+    if forward.is_nan() || forward < 1e-9 {
+        return Err(to_node
+            .error("the camera must look at a point other than where it is")
+            .with_key("to"));
+    }
+
+    let side = Vector::cross((to - from).normalize(), up.normalize()).len();
+    if side.is_nan() || side < 1e-9 {
+        return Err(up_node
+            .error("`up` can't point along the direction the camera looks")
+            .with_key("up"));
+    }
+    Ok(())
+}
+
+fn parse_light(item: &Node, entries: &[(Node, Node)]) -> Result<PointLight, SceneError> {
+    check_keys(entries, &LIGHT_KEYS)?;
+
+    let position = parse_point(required(item, entries, "at")?, "at")?;
+    let intensity = parse_color(required(item, entries, "intensity")?, "intensity")?;
+    Ok(PointLight::new(position, intensity))
+}
+
+fn parse_shape(
+    kind: &Node,
+    entries: &[(Node, Node)],
+    definitions: &Definitions,
+) -> Result<Shape, SceneError> {
+    check_keys(entries, &SHAPE_KEYS)?;
+
+    let transform = match get(entries, "transform") {
+        Some(node) => {
+            let transform = parse_transform(&expand_names(node, definitions)?)?;
+            if !transform.is_invertable() {
+                return Err(node
+                    .error("the transform can't be undone, e.g. it scales by 0")
+                    .with_key("transform"));
+            }
+            transform
+        }
+        None => Matrix4x4::identity(),
+    };
+    let material = match get(entries, "material") {
+        Some(node) => parse_material(resolve(node, "material", definitions)?)?,
+        None => Material::default(),
+    };
+
+    match kind.as_str("add")? {
+        "plane" => Ok(Shape::plane(transform, material)),
+        _ => Ok(Shape::sphere(transform, material)),
+    }
+}
+
+/// Transforms are listed in the order they're applied to the shape
+fn parse_transform(node: &Node) -> Result<Matrix4x4, SceneError> {
+    let mut transform = Matrix4x4::identity();
+
+    for step in node.as_sequence("transform")? {
+        let parts = step.as_sequence("transform")?;
+        let (operation, arguments) = parts
+            .split_first()
+            .ok_or_else(|| step.error("empty transform").with_key("transform"))?;
+        let name = operation.as_str("transform")?;
+        let arguments = arguments
+            .iter()
+            .map(|argument| argument.as_f64("transform"))
+            .collect::<Result<Vec<f64>, SceneError>>()?;
+
+        let expected = match name {
+            "translate" | "scale" => 3,
+            "rotate-x" | "rotate-y" | "rotate-z" => 1,
+            "shear" => 6,
+            _ => {
+                return Err(operation
+                    .error(&format!("unknown transform `{}`", name))
+                    .with_key("transform"))
+            }
+        };
+        if arguments.len() != expected {
+            return Err(step
+                .error(&format!("`{}` takes {} numbers", name, expected))
+                .with_key("transform"));
+        }
+
+        let a = &arguments;
+        transform = match name {
+            "translate" => transform.translate(a[0], a[1], a[2]),
+            "scale" => transform.scale(a[0], a[1], a[2]),
+            "rotate-x" => transform.rotate_x(a[0]),
+            "rotate-y" => transform.rotate_y(a[0]),
+            "rotate-z" => transform.rotate_z(a[0]),
+            _ => Matrix4x4::shearing(a[0], a[1], a[2], a[3], a[4], a[5]) * transform,
+        };
+    }
+
+    Ok(transform)
+}
+
+fn parse_material(node: &Node) -> Result<Material, SceneError> {
+    let entries = node.as_mapping("material")?;
+    check_keys(entries, &MATERIAL_KEYS)?;
+
+    let mut material = Material::default();
+    let number = |key: &str, default: f64| match get(entries, key) {
+        Some(value) => value.as_f64(key),
+        None => Ok(default),
+    };
+    let color = |key: &str, default: Color| match get(entries, key) {
+        Some(value) => parse_color(value, key),
+        None => Ok(default),
+    };
+
+    material.color = color("color", material.color)?;
+    material.ambient = number("ambient", material.ambient)?;
+    material.diffuse = number("diffuse", material.diffuse)?;
+    material.specular = number("specular", material.specular)?;
+    material.shininess = number("shininess", material.shininess)?;
+    material.reflective = number("reflective", material.reflective)?;
+    material.transparency = number("transparency", material.transparency)?;
+    material.refractive_index = number("refractive-index", material.refractive_index)?;
+    material.emission = color("emission", material.emission)?;
+    material.absorption = color("absorption", material.absorption)?;
+
+    if get(entries, "metallic").is_some() || get(entries, "roughness").is_some() {
+        material.microfacet = Some(Microfacet::new(
+            number("metallic", 0.0)?,
+            number("roughness", 0.5)?,
+        ));
+    }
+
+    if let Some(pattern) = get(entries, "pattern") {
+        material.pattern = Some(parse_pattern(pattern)?);
+    }
+
+    Ok(material)
+}
+
+fn parse_pattern(node: &Node) -> Result<StripePattern, SceneError> {
+    let entries = node.as_mapping("pattern")?;
+    check_keys(entries, &PATTERN_KEYS)?;
+
+    let kind = required(node, entries, "type")?;
+    if kind.as_str("type")? != "stripes" {
+        return Err(kind.error("unknown pattern").with_key("type"));
+    }
+
+    let colors = required(node, entries, "colors")?;
+    match colors.as_sequence("colors")? {
+        [first, second] => Ok(StripePattern::new(
+            parse_color(first, "colors")?,
+            parse_color(second, "colors")?,
+        )),
+        _ => Err(colors.error("expected 2 colors").with_key("colors")),
+    }
+}
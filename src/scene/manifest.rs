@@ -0,0 +1,122 @@
+use super::{
+    loader::check_view,
+    node::{check_keys, get, parse_yaml, required, Node},
+    SceneError,
+};
+use crate::maths::{Matrix4x4, Point, Vector};
+use crate::primitives::Camera;
+use std::path::{Path, PathBuf};
+
+const JOB_KEYS: [&str; 6] = ["scene", "output", "width", "height", "samples", "camera"];
+const VIEW_KEYS: [&str; 4] = ["from", "to", "up", "field-of-view"];
+
+/// One render listed in a batch manifest
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchJob {
+    pub scene: PathBuf,
+    pub output: PathBuf,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub samples: Option<u32>,
+    pub camera: Option<CameraView>,
+}
+
+/// Where to look from instead of the scene's own camera
+#[derive(Clone, Debug, PartialEq)]
+pub struct CameraView {
+    pub from: Point,
+    pub to: Point,
+    pub up: Vector,
+    pub fov: Option<f64>,
+}
+
+impl CameraView {
+    /*
+        Move a scene's camera to this view, keeping its size & (unless given) its field of view.
+        Views read from a manifest are checked, but one made in code may have no direction to
+        look in, in which case there's no camera.
+    */
+    pub fn apply(&self, camera: &Camera) -> Option<Camera> {
+        let transform = Matrix4x4::view(self.from, self.to, self.up);
+        let determinant = transform.determinant();
+        if !determinant.is_finite() || determinant == 0.0 {
+            return None;
+        }
+
+        Some(Camera::new(
+            camera.width(),
+            camera.height(),
+            self.fov.unwrap_or(camera.fov()),
+            transform,
+        ))
+    }
+}
+
+/// Read the jobs from a manifest. Scene & output paths are relative to the manifest.
+pub fn load_manifest(path: &str) -> Result<Vec<BatchJob>, SceneError> {
+    let text = std::fs::read_to_string(path).map_err(|error| SceneError::io(path, error))?;
+    let directory = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+    parse_manifest(&text, directory).map_err(|error| error.in_file(path))
+}
+
+/// Read the jobs from a manifest's YAML, a list of entries with a `scene` & an `output` each
+pub fn parse_manifest(text: &str, directory: &Path) -> Result<Vec<BatchJob>, SceneError> {
+    parse_yaml(text)?
+        .as_sequence("manifest")?
+        .iter()
+        .map(|item| parse_job(item, directory))
+        .collect()
+}
+
+fn parse_job(item: &Node, directory: &Path) -> Result<BatchJob, SceneError> {
+    let entries = item.as_mapping("manifest")?;
+    check_keys(entries, &JOB_KEYS)?;
+
+    let scene = directory.join(required(item, entries, "scene")?.as_str("scene")?);
+    let output = directory.join(required(item, entries, "output")?.as_str("output")?);
+    let width = get(entries, "width")
+        .map(|node| node.as_positive_i32("width"))
+        .transpose()?;
+    let height = get(entries, "height")
+        .map(|node| node.as_positive_i32("height"))
+        .transpose()?;
+    let samples = get(entries, "samples")
+        .map(|node| {
+            node.as_positive_i32("samples")
+                .map(|samples| samples as u32)
+        })
+        .transpose()?;
+    let camera = get(entries, "camera").map(parse_view).transpose()?;
+
+    Ok(BatchJob {
+        scene,
+        output,
+        width,
+        height,
+        samples,
+        camera,
+    })
+}
+
+fn parse_view(node: &Node) -> Result<CameraView, SceneError> {
+    let entries = node.as_mapping("camera")?;
+    check_keys(entries, &VIEW_KEYS)?;
+
+    let [fx, fy, fz] = required(node, entries, "from")?.as_numbers::<3>("from")?;
+    let to_node = required(node, entries, "to")?;
+    let [tx, ty, tz] = to_node.as_numbers::<3>("to")?;
+    let up_node = required(node, entries, "up")?;
+    let [ux, uy, uz] = up_node.as_numbers::<3>("up")?;
+    let fov = get(entries, "field-of-view")
+        .map(|node| node.as_f64("field-of-view"))
+        .transpose()?;
+
+    let view = CameraView {
+        from: Point::new(fx, fy, fz),
+        to: Point::new(tx, ty, tz),
+        up: Vector::new(ux, uy, uz),
+        fov,
+    };
+    check_view(to_node, up_node, view.from, view.to, view.up)?;
+    Ok(view)
+}
@@ -0,0 +1,115 @@
+use super::{parse_manifest, BatchJob, CameraView};
+use crate::maths::{Matrix4x4, Point, Vector};
+use crate::primitives::Camera;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn a_manifest_lists_jobs_relative_to_its_directory() {
+    let manifest = "
+- scene: scenes/cornell_box.yml
+  output: renders/cornell.png
+
+- scene: /absolute/scene.yml
+  output: close_up.png
+  width: 64
+  height: 32
+  samples: 4
+  camera:
+    from: [0, 1, -5]
+    to: [0, 1, 0]
+    up: [0, 1, 0]
+    field-of-view: 0.5
+";
+    let jobs = parse_manifest(manifest, Path::new("batch")).unwrap();
+
+    assert_eq!(
+        jobs,
+        vec![
+            BatchJob {
+                scene: PathBuf::from("batch/scenes/cornell_box.yml"),
+                output: PathBuf::from("batch/renders/cornell.png"),
+                width: None,
+                height: None,
+                samples: None,
+                camera: None,
+            },
+            BatchJob {
+                scene: PathBuf::from("/absolute/scene.yml"),
+                output: PathBuf::from("batch/close_up.png"),
+                width: Some(64),
+                height: Some(32),
+                samples: Some(4),
+                camera: Some(CameraView {
+                    from: Point::new(0.0, 1.0, -5.0),
+                    to: Point::new(0.0, 1.0, 0.0),
+                    up: Vector::new(0.0, 1.0, 0.0),
+                    fov: Some(0.5),
+                }),
+            },
+        ]
+    );
+}
+
+#[test]
+fn a_job_needs_an_output() {
+    let error = parse_manifest("- scene: a.yml\n", Path::new(".")).unwrap_err();
+
+    assert_eq!(error.message, "missing a required entry");
+    assert_eq!(error.key, Some("output".to_owned()));
+    assert_eq!(error.line, 1);
+}
+
+#[test]
+fn sizes_must_be_positive() {
+    let error = parse_manifest(
+        "- scene: a.yml\n  output: a.png\n  samples: 0\n",
+        Path::new("."),
+    )
+    .unwrap_err();
+
+    assert_eq!(error.key, Some("samples".to_owned()));
+    assert_eq!(error.line, 3);
+}
+
+#[test]
+fn unknown_keys_in_a_job_are_an_error() {
+    let error = parse_manifest(
+        "- scene: a.yml\n  output: a.png\n  sampels: 4\n",
+        Path::new("."),
+    )
+    .unwrap_err();
+
+    assert_eq!(error.message, "unknown key");
+    assert_eq!(error.key, Some("sampels".to_owned()));
+}
+
+#[test]
+fn a_camera_view_must_have_a_direction_to_look_in() {
+    let job = "- scene: a.yml\n  output: a.png\n  camera:\n    from: [0, 1, -5]\n";
+    let error = parse_manifest(
+        &format!("{}    to: [0, 1, -5]\n    up: [0, 1, 0]\n", job),
+        Path::new("."),
+    )
+    .unwrap_err();
+    assert_eq!(error.key, Some("to".to_owned()));
+    assert_eq!(error.line, 5);
+
+    let error = parse_manifest(
+        &format!("{}    to: [0, 1, 0]\n    up: [0, 0, 2]\n", job),
+        Path::new("."),
+    )
+    .unwrap_err();
+    assert_eq!(error.key, Some("up".to_owned()));
+    assert_eq!(error.line, 6);
+
+    // Views made in code aren't checked until they're used
+    let view = CameraView {
+        from: Point::new(0.0, 0.0, 0.0),
+        to: Point::new(0.0, 0.0, 0.0),
+        up: Vector::up(),
+        fov: None,
+    };
+    assert!(view
+        .apply(&Camera::new(10, 10, 1.0, Matrix4x4::identity()))
+        .is_none());
+}
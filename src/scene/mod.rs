@@ -0,0 +1,16 @@
+// Code files
+mod error;
+mod loader;
+mod manifest;
+mod node;
+
+// Exports
+pub use error::SceneError;
+pub use loader::{hash_world, load_scene, parse_scene, Scene};
+pub use manifest::{load_manifest, parse_manifest, BatchJob, CameraView};
+
+// Tests
+#[cfg(test)]
+mod manifest_test;
+#[cfg(test)]
+mod scene_test;
@@ -0,0 +1,211 @@
+use super::SceneError;
+use std::collections::HashMap;
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
+
+/// A YAML value which remembers where it came from in the file
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Scalar(String),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub value: Value,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Node {
+    fn new(value: Value, mark: Marker) -> Self {
+        Self {
+            value,
+            line: mark.line(),
+            column: mark.col() + 1,
+        }
+    }
+
+    /// Create an error pointing at this node
+    pub fn error(&self, message: &str) -> SceneError {
+        SceneError::new(message.to_owned(), self.line, self.column)
+    }
+
+    pub fn as_str(&self, key: &str) -> Result<&str, SceneError> {
+        match &self.value {
+            Value::Scalar(value) => Ok(value),
+            _ => Err(self.error("expected a string").with_key(key)),
+        }
+    }
+
+    pub fn as_f64(&self, key: &str) -> Result<f64, SceneError> {
+        self.as_str(key)?
+            .parse::<f64>()
+            .map_err(|_| self.error("expected a number").with_key(key))
+    }
+
+    pub fn as_i32(&self, key: &str) -> Result<i32, SceneError> {
+        self.as_str(key)?
+            .parse::<i32>()
+            .map_err(|_| self.error("expected a whole number").with_key(key))
+    }
+
+    /// A whole number of at least 1, such as a size or a sample count
+    pub fn as_positive_i32(&self, key: &str) -> Result<i32, SceneError> {
+        match self.as_i32(key)? {
+            value if value >= 1 => Ok(value),
+            _ => Err(self.error("expected a number of at least 1").with_key(key)),
+        }
+    }
+
+    pub fn as_sequence(&self, key: &str) -> Result<&[Node], SceneError> {
+        match &self.value {
+            Value::Sequence(items) => Ok(items),
+            _ => Err(self.error("expected a list").with_key(key)),
+        }
+    }
+
+    pub fn as_mapping(&self, key: &str) -> Result<&[(Node, Node)], SceneError> {
+        match &self.value {
+            Value::Mapping(entries) => Ok(entries),
+            _ => Err(self.error("expected a mapping").with_key(key)),
+        }
+    }
+
+    /// Read a list of exactly `N` numbers
+    pub fn as_numbers<const N: usize>(&self, key: &str) -> Result<[f64; N], SceneError> {
+        let items = self.as_sequence(key)?;
+        if items.len() != N {
+            return Err(self.error(&format!("expected {} numbers", N)).with_key(key));
+        }
+        let mut numbers = [0.0; N];
+        for (number, item) in numbers.iter_mut().zip(items) {
+            *number = item.as_f64(key)?;
+        }
+        Ok(numbers)
+    }
+}
+
+/// Find the value of a key in a mapping's entries
+pub fn get<'a>(entries: &'a [(Node, Node)], key: &str) -> Option<&'a Node> {
+    entries
+        .iter()
+        .find(|(name, _)| name.as_str(key).map(|name| name == key).unwrap_or(false))
+        .map(|(_, value)| value)
+}
+
+pub fn required<'a>(
+    parent: &Node,
+    entries: &'a [(Node, Node)],
+    key: &str,
+) -> Result<&'a Node, SceneError> {
+    get(entries, key).ok_or_else(|| parent.error("missing a required entry").with_key(key))
+}
+
+/// Reject any keys which would otherwise be silently ignored, such as misspellings
+pub fn check_keys(entries: &[(Node, Node)], allowed: &[&str]) -> Result<(), SceneError> {
+    for (name, _) in entries {
+        let key = name.as_str("")?;
+        if !allowed.contains(&key) {
+            return Err(name.error("unknown key").with_key(key));
+        }
+    }
+    Ok(())
+}
+
+enum Partial {
+    Sequence(Vec<Node>, Marker, usize),
+    Mapping(Vec<(Node, Node)>, Option<Node>, Marker, usize),
+}
+
+/// Builds a tree of nodes from the parser's events
+#[derive(Default)]
+struct TreeBuilder {
+    stack: Vec<Partial>,
+    anchors: HashMap<usize, Node>,
+    root: Option<Node>,
+}
+
+impl TreeBuilder {
+    fn add(&mut self, node: Node, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+
+        match self.stack.last_mut() {
+            Some(Partial::Sequence(items, ..)) => items.push(node),
+            Some(Partial::Mapping(entries, key, ..)) => match key.take() {
+                Some(key) => entries.push((key, node)),
+                None => *key = Some(node),
+            },
+            None => {
+                // Only the first document is used
+                if self.root.is_none() {
+                    self.root = Some(node);
+                }
+            }
+        }
+    }
+}
+
+impl MarkedEventReceiver for TreeBuilder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(value, _, anchor, _) => {
+                self.add(Node::new(Value::Scalar(value), mark), anchor)
+            }
+            Event::Alias(anchor) => {
+                let node = self
+                    .anchors
+                    .get(&anchor)
+                    .cloned()
+                    .unwrap_or_else(|| Node::new(Value::Scalar("~".to_owned()), mark));
+                self.add(node, 0);
+            }
+            Event::SequenceStart(anchor) => {
+                self.stack.push(Partial::Sequence(vec![], mark, anchor))
+            }
+            Event::MappingStart(anchor) => {
+                self.stack
+                    .push(Partial::Mapping(vec![], None, mark, anchor))
+            }
+            Event::SequenceEnd | Event::MappingEnd => match self.stack.pop() {
+                Some(Partial::Sequence(items, start, anchor)) => {
+                    self.add(Node::new(Value::Sequence(items), start), anchor)
+                }
+                Some(Partial::Mapping(entries, _, start, anchor)) => {
+                    self.add(Node::new(Value::Mapping(entries), start), anchor)
+                }
+                None => {}
+            },
+            _ => {}
+        }
+    }
+}
+
+/// Parse YAML text into a tree of nodes
+pub fn parse_yaml(text: &str) -> Result<Node, SceneError> {
+    let mut builder = TreeBuilder::default();
+    Parser::new(text.chars())
+        .load(&mut builder, false)
+        .map_err(|error| {
+            // The scanner's message includes its own position which is reported separately
+            let message = error.to_string();
+            let message = message.split(" at line ").next().unwrap_or_default();
+            SceneError::new(
+                message.to_owned(),
+                error.marker().line(),
+                error.marker().col() + 1,
+            )
+        })?;
+
+    // An empty file is an empty scene
+    Ok(builder.root.unwrap_or(Node {
+        value: Value::Sequence(vec![]),
+        line: 1,
+        column: 1,
+    }))
+}
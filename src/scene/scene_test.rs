@@ -0,0 +1,386 @@
+use super::{hash_world, load_scene, parse_scene, SceneError};
+use crate::{
+    cornell_box::create_cornell_box,
+    image::Color,
+    maths::{Matrix4x4, Point, Vector},
+    primitives::{PointLight, Shape},
+};
+
+const CORNELL_BOX: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/scenes/cornell_box.yml");
+
+const SIMPLE_SCENE: &str = "
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 0.785
+  from: [0, 1.5, -5]
+  to: [0, 1, 0]
+  up: [0, 1, 0]
+
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 1, 1]
+
+- add: sphere
+  material:
+    color: [0.1, 1, 0.5]
+    diffuse: 0.7
+  transform:
+    - [scale, 2, 2, 2]
+    - [translate, 0, 1, 0]
+";
+
+fn scene_error(text: &str) -> SceneError {
+    match parse_scene(text) {
+        Ok(_) => panic!("expected the scene to fail to load"),
+        Err(error) => error,
+    }
+}
+
+#[test]
+fn parsing_a_simple_scene() {
+    let scene = parse_scene(SIMPLE_SCENE).unwrap();
+    let world = scene.world.generate();
+
+    assert_eq!(scene.camera.width(), 100);
+    assert_eq!(scene.camera.height(), 50);
+    assert_eq!(scene.camera.fov(), 0.785);
+    assert_eq!(
+        scene.camera.transform(),
+        Matrix4x4::view(
+            Point::new(0.0, 1.5, -5.0),
+            Point::new(0.0, 1.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0)
+        )
+    );
+
+    assert_eq!(
+        world.lights(),
+        vec![PointLight::new(
+            Point::new(-10.0, 10.0, -10.0),
+            Color::white()
+        )]
+    );
+
+    let sphere = &world.objects()[0];
+    assert_eq!(
+        sphere.transformation(),
+        Matrix4x4::translation(0.0, 1.0, 0.0) * Matrix4x4::scaling(2.0, 2.0, 2.0)
+    );
+    assert_eq!(sphere.material().color, Color::new(0.1, 1.0, 0.5));
+    assert_eq!(sphere.material().diffuse, 0.7);
+}
+
+#[test]
+fn the_cornell_box_fixture_matches_the_built_in_scene() {
+    let scene = load_scene(CORNELL_BOX).unwrap();
+    let loaded = scene.world.generate();
+    let built_in = create_cornell_box().generate();
+
+    assert_eq!(loaded.lights(), built_in.lights());
+    assert_eq!(loaded.objects(), built_in.objects());
+
+    // Materials compare without their patterns so check them by sampling
+    let point = Point::new(0.5, 0.0, 0.0);
+    for (loaded, built_in) in loaded.objects().iter().zip(built_in.objects().iter()) {
+        assert_eq!(
+            loaded.material().color_at(&point),
+            built_in.material().color_at(&point)
+        );
+    }
+    assert_eq!(scene.camera.width(), 1024);
+}
+
+#[test]
+fn unknown_keys_are_reported_with_their_position() {
+    let error = scene_error(
+        "
+- add: sphere
+  material:
+    colour: [1, 0, 0]
+",
+    );
+
+    assert_eq!(error.key, Some("colour".to_owned()));
+    assert_eq!((error.line, error.column), (4, 5));
+    assert_eq!(error.to_string(), "4:5: unknown key (key `colour`)");
+}
+
+#[test]
+fn values_of_the_wrong_type_are_reported() {
+    let error = scene_error(
+        "
+- add: light
+  at: [0, 1, 0]
+  intensity: bright
+",
+    );
+
+    assert_eq!(error.key, Some("intensity".to_owned()));
+    assert_eq!((error.line, error.column), (4, 14));
+}
+
+#[test]
+fn camera_sizes_must_be_positive() {
+    for size in ["-4", "0"] {
+        let error = scene_error(&SIMPLE_SCENE.replace("width: 100", &format!("width: {}", size)));
+        assert_eq!(error.message, "expected a number of at least 1");
+        assert_eq!(error.key, Some("width".to_owned()));
+        assert_eq!((error.line, error.column), (3, 10));
+    }
+}
+
+#[test]
+fn transforms_which_cant_be_undone_are_reported() {
+    let error = scene_error("- add: sphere\n  transform:\n    - [scale, 0, 1, 1]\n");
+    assert_eq!(error.key, Some("transform".to_owned()));
+    assert_eq!((error.line, error.column), (3, 5));
+}
+
+#[test]
+fn the_camera_must_have_a_direction_to_look_in() {
+    let error = scene_error(&SIMPLE_SCENE.replace("to: [0, 1, 0]", "to: [0, 1.5, -5]"));
+    assert_eq!(error.key, Some("to".to_owned()));
+    assert_eq!((error.line, error.column), (7, 7));
+
+    let error = scene_error(&SIMPLE_SCENE.replace("up: [0, 1, 0]", "up: [0, -0.5, 5]"));
+    assert_eq!(error.key, Some("up".to_owned()));
+    assert_eq!((error.line, error.column), (8, 7));
+
+    let error = scene_error(&SIMPLE_SCENE.replace("up: [0, 1, 0]", "up: [0, 0, 0]"));
+    assert_eq!(error.key, Some("up".to_owned()));
+}
+
+#[test]
+fn unknown_objects_and_transforms_are_reported() {
+    let error = scene_error("- add: teapot\n");
+    assert_eq!(error.key, Some("add".to_owned()));
+    assert_eq!(error.line, 1);
+
+    let error = scene_error("- add: sphere\n  transform:\n    - [squash, 1]\n");
+    assert_eq!(error.message, "unknown transform `squash`");
+    assert_eq!(error.line, 3);
+}
+
+#[test]
+fn a_scene_needs_a_camera() {
+    let error = scene_error("- add: sphere\n");
+    assert_eq!(error.message, "the scene has no camera");
+}
+
+#[test]
+fn yaml_syntax_errors_are_reported_with_their_position() {
+    let error = scene_error("- add: sphere\n  transform: [1, 2\n");
+    assert!(error.line > 1, "{:?}", error);
+}
+
+#[test]
+fn missing_files_are_reported() {
+    let error = load_scene("does/not/exist.yml").err().unwrap();
+    assert_eq!(error.file, Some("does/not/exist.yml".to_owned()));
+    assert!(error.to_string().starts_with("does/not/exist.yml:"));
+}
+
+#[test]
+fn shapes_default_to_a_unit_sphere_with_the_default_material() {
+    let scene = parse_scene(
+        "
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+- add: sphere
+",
+    )
+    .unwrap();
+
+    assert_eq!(scene.world.generate().objects(), vec![Shape::default()]);
+}
+
+const CAMERA: &str = "
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+";
+
+#[test]
+fn shapes_can_use_defined_materials_and_transforms() {
+    let text = CAMERA.to_owned()
+        + "
+- define: shiny
+  value:
+    color: [1, 0, 0]
+    specular: 0.9
+- define: lift
+  value:
+    - [translate, 0, 1, 0]
+- define: big-lift
+  value:
+    - [scale, 2, 2, 2]
+    - lift
+- add: sphere
+  material: shiny
+  transform:
+    - big-lift
+    - [translate, 1, 0, 0]
+";
+    let world = parse_scene(&text).unwrap().world.generate();
+    let sphere = &world.objects()[0];
+
+    assert_eq!(sphere.material().color, Color::red());
+    assert_eq!(sphere.material().specular, 0.9);
+    assert_eq!(
+        sphere.transformation(),
+        Matrix4x4::scaling(2.0, 2.0, 2.0)
+            .translate(0.0, 1.0, 0.0)
+            .translate(1.0, 0.0, 0.0)
+    );
+}
+
+#[test]
+fn extending_a_definition_overrides_its_values() {
+    let text = CAMERA.to_owned()
+        + "
+- define: base
+  value:
+    color: [1, 1, 1]
+    diffuse: 0.7
+- define: blue
+  extend: base
+  value:
+    color: [0, 0, 1]
+- add: sphere
+  material: blue
+";
+    let world = parse_scene(&text).unwrap().world.generate();
+    let material = world.objects()[0].material().clone();
+
+    assert_eq!(material.color, Color::blue());
+    assert_eq!(material.diffuse, 0.7);
+}
+
+#[test]
+fn unknown_definitions_are_reported() {
+    let error = scene_error("- add: sphere\n  material: missing\n");
+    assert_eq!(error.message, "unknown definition `missing`");
+    assert_eq!(error.key, Some("material".to_owned()));
+    assert_eq!((error.line, error.column), (2, 13));
+
+    let error = scene_error("- define: blue\n  extend: missing\n  value: {}\n");
+    assert_eq!(error.key, Some("extend".to_owned()));
+}
+
+fn write_scene_files(directory: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+    let directory = std::env::temp_dir().join(directory);
+    std::fs::create_dir_all(&directory).unwrap();
+    for (name, text) in files {
+        std::fs::write(directory.join(name), text).unwrap();
+    }
+    directory
+}
+
+#[test]
+fn files_including_each_other_are_reported() {
+    let directory = write_scene_files(
+        "rtracer_include_cycle_test",
+        &[
+            ("a.yml", "- include: b.yml\n"),
+            ("b.yml", "\n- include: a.yml\n"),
+        ],
+    );
+
+    let error = load_scene(directory.join("a.yml").to_str().unwrap())
+        .err()
+        .unwrap();
+
+    assert!(
+        error.message.starts_with("files include each other"),
+        "{}",
+        error
+    );
+    assert_eq!(error.line, 2);
+    assert!(error.file.unwrap().ends_with("b.yml"));
+}
+
+#[test]
+fn errors_in_included_files_report_the_included_file() {
+    let directory = write_scene_files(
+        "rtracer_include_error_test",
+        &[
+            ("main.yml", "- include: materials.yml\n"),
+            (
+                "materials.yml",
+                "- define: red\n  value:\n    colour: [1, 0, 0]\n- add: sphere\n  material: red\n",
+            ),
+        ],
+    );
+
+    let error = load_scene(directory.join("main.yml").to_str().unwrap())
+        .err()
+        .unwrap();
+
+    assert_eq!(error.key, Some("colour".to_owned()));
+    assert_eq!(error.line, 3);
+    assert!(error.file.unwrap().ends_with("materials.yml"));
+}
+
+#[test]
+fn including_a_missing_file_is_reported_at_the_include() {
+    let directory = write_scene_files(
+        "rtracer_include_missing_test",
+        &[("main.yml", "\n- include: missing.yml\n")],
+    );
+
+    let error = load_scene(directory.join("main.yml").to_str().unwrap())
+        .err()
+        .unwrap();
+
+    assert_eq!(error.key, Some("include".to_owned()));
+    assert_eq!(error.line, 2);
+    assert!(error.file.unwrap().ends_with("main.yml"));
+}
+
+#[test]
+fn editing_a_scene_or_its_includes_changes_its_hash() {
+    let main = SIMPLE_SCENE.to_owned() + "- include: light.yml\n";
+    let light = "- add: light\n  at: [0, 10, 0]\n  intensity: [1, 1, 1]\n";
+    let directory = write_scene_files(
+        "rtracer_source_hash_test",
+        &[("main.yml", &main), ("light.yml", light)],
+    );
+    let hash = || {
+        load_scene(directory.join("main.yml").to_str().unwrap())
+            .unwrap()
+            .source_hash
+    };
+
+    let original = hash();
+    assert_eq!(hash(), original);
+
+    std::fs::write(directory.join("light.yml"), light.replace("10", "11")).unwrap();
+    let edited_include = hash();
+    assert_ne!(edited_include, original);
+
+    std::fs::write(directory.join("main.yml"), main.replace("0.7", "0.8")).unwrap();
+    assert_ne!(hash(), edited_include);
+}
+
+#[test]
+fn a_world_built_in_code_has_a_hash_too() {
+    assert_ne!(hash_world(&create_cornell_box()), 0);
+    assert_eq!(
+        hash_world(&create_cornell_box()),
+        hash_world(&create_cornell_box())
+    );
+
+    let moved_light =
+        create_cornell_box().add_light(PointLight::new(Point::new(0.0, 1.0, 0.0), Color::white()));
+    assert_ne!(hash_world(&moved_light), hash_world(&create_cornell_box()));
+}